@@ -17,6 +17,8 @@ local M = {}
 ---@field name string The font name. If you don't need features or variations, consider using the string shorthand of Font.
 ---@field features? FontFeature[]
 ---@field variations? FontVariation[]
+---@field bold? boolean Use the bold faces in place of the regular ones
+---@field italic? boolean Use the italic faces in place of the regular ones
 
 ---@alias Font string | FontFull
 
@@ -26,22 +28,152 @@ local M = {}
 ---@field kind FontSizeKind
 ---@field size number
 
+---@class SymbolRange
+---@field [1] integer The first codepoint in the range
+---@field [2] integer The last codepoint in the range, inclusive
+
+---@alias AnimationCurveKind 'smooth' | 'linear' | 'ease_out_cubic' | 'spring' | 'instant'
+
+---@class AnimationCurve
+---@field kind AnimationCurveKind
+---@field duration? number Seconds for linear and ease_out_cubic curves, 0.15 by default
+---@field stiffness? number Stiffness of the critically damped spring, 200 by default
+
+---@alias CursorEffectKind 'none' | 'smear' | 'trail' | 'particles'
+
+---@class CursorEffect
+---@field kind CursorEffectKind
+---@field leading? number Smear: seconds for the corners leading the motion to settle, 0.05 by default
+---@field trailing? number Smear: seconds for the trailing corners to settle, 0.2 by default
+---@field duration? number Trail and particles: seconds to fade out, 0.25 and 0.5 by default
+---@field opacity? number Trail: starting opacity, 0.5 by default
+---@field count? number Particles: particles per cell traveled, 4 by default
+---@field speed? number Particles: cells per second the particles drift, 2 by default
+
+---@class FloatTransition
+---@field duration? number Seconds that each transition takes, 0.15 by default
+---@field fade? boolean Whether floats fade in and out, false by default
+---@field slide? number Cells above its position that a float slides from, 0 by default
+---@field move? boolean Whether floats glide to new positions, false by default
+
+---@class DimInactive
+---@field enabled? boolean Whether inactive windows are dimmed, true by default. Floats that cannot be focused are not dimmed.
+---@field amount? number Opacity of the black drawn over inactive windows from 0 to 1, 0.3 by default
+---@field duration? number Seconds to dim or brighten as focus moves, 0.15 by default
+
 ---@class Color
 ---@field r number
 ---@field g number
 ---@field b number
 ---@field a number
 
+---@class FloatShadow
+---@field blur? number Pixels over which the shadow fades out, 12 by default
+---@field offset_x? number Pixels the shadow is offset to the right, 0 by default
+---@field offset_y? number Pixels the shadow is offset downward, 4 by default
+---@field color? Color Black at 40% opacity by default
+
+---@class FloatOutline
+---@field width? number Width of the line in pixels, 1 by default
+---@field color? Color Gray by default
+
+---@class FloatDecoration
+---@field radius? number Radius of the rounded corners in pixels, 0 by default
+---@field shadow? FloatShadow Drop shadow, none if unset
+---@field outline? FloatOutline Line along the edge, none if unset
+
+---@class Scrollbar
+---@field enabled? boolean Whether scrollbars are drawn, true by default
+---@field width? number Width in pixels, 6 by default
+---@field timeout? number Seconds the scrollbar stays visible after scrolling, 1 by default
+---@field fade? number Seconds over which the scrollbar fades out, 0.3 by default
+---@field color? Color Gray at 60% opacity by default
+
+---@class Toasts
+---@field enabled? boolean Whether messages are shown as toasts, true by default
+---@field timeout? number Seconds before a toast dismisses itself, or 0 to keep it until the message is cleared, 4 by default. Prompts are always kept.
+---@field corner? 'top_left' | 'top_right' | 'bottom_left' | 'bottom_right' Where toasts stack from, 'top_right' by default
+---@field duration? number Seconds for toasts to fade and move, 0.2 by default
+---@field radius? number Radius of the rounded corners in pixels, 6 by default
+
+---@class Image
+---@field id integer Identifies the image so that it can be replaced or hidden
+---@field window? integer Window to show the image over, the current window by default
+---@field grid? integer Grid to show the image over instead of a window
+---@field row? integer Zero-based row of the top left cell, 0 by default
+---@field col? integer Zero-based column of the top left cell, 0 by default
+---@field width? integer Number of cells the image spans horizontally, 1 by default
+---@field height? integer Number of cells the image spans vertically, 1 by default
+---@field path? string Path to a PNG file
+---@field png? string Contents of a PNG file
+---@field rgba? string Raw 8-bit RGBA pixels with straight alpha
+---@field pixel_width? integer Width of the raw pixels
+---@field pixel_height? integer Height of the raw pixels
+
+---@class Overlay
+---@field kind 'image' | 'badge' | 'swatch' | 'underline'
+---@field width? integer Number of cells spanned by images, badges, and underlines, 1 by default
+---@field height? integer Number of cells spanned by images and badges, 1 by default
+---@field color? Color Required for badges, swatches, and underlines
+---@field thickness? number Thickness of underlines in pixels, 1 by default
+---@field path? string Path to a PNG file for images
+---@field png? string Contents of a PNG file for images
+---@field rgba? string Raw 8-bit RGBA pixels with straight alpha for images
+---@field pixel_width? integer Width of the raw pixels
+---@field pixel_height? integer Height of the raw pixels
+
 ---@class Config
 ---@field fonts? Font[]
 ---@field font_size? FontSize
 ---@field underline_offset? number
+---@field letter_spacing? number
+---@field line_height? number
+---@field box_drawing? boolean
+---@field symbol_ranges? SymbolRange[]
+---@field break_ligatures_at_cursor? boolean
+---@field break_ligatures_modes? string[]
+---@field highlight_features? table<string, FontFeature[]>
+---@field highlight_fonts? table<string, Font>
 ---@field cursor_speed? number
 ---@field scroll_speed? number
+---@field cursor_blink_fade? number
+---@field cursor_curve? AnimationCurve
+---@field scroll_curve? AnimationCurve
+---@field cursor_effect? CursorEffect
+---@field float_transition? FloatTransition
+---@field float_decoration? FloatDecoration
+---@field window_move_duration? number
+---@field dim_inactive? DimInactive
+---@field scrollbar? Scrollbar
+---@field toasts? Toasts
+---@field link_opener? string[]
 ---@field bg_override? Color
 
 ---@alias motion 'still' | 'animating'
 
+-- The extmark IDs with overlays by buffer, then namespace
+local overlay_marks = {}
+
+---Remove the overlays of extmarks that were deleted or whose namespace was
+---cleared and of buffers that were unloaded, which Neovim does not report
+local function prune_overlays()
+  for buffer, namespaces in pairs(overlay_marks) do
+    if vim.api.nvim_buf_is_loaded(buffer) then
+      for ns_id, marks in pairs(namespaces) do
+        for mark_id in pairs(marks) do
+          if #vim.api.nvim_buf_get_extmark_by_id(buffer, ns_id, mark_id, {}) == 0 then
+            marks[mark_id] = nil
+            vim.rpcnotify(1, 'neophyte.overlay_remove', { buffer, ns_id, mark_id })
+          end
+        end
+      end
+    else
+      overlay_marks[buffer] = nil
+      vim.rpcnotify(1, 'neophyte.overlay_clear', { buffer })
+    end
+  end
+end
+
 ---Set Neophyte configuration
 ---@param config Config
 function M.setup(config)
@@ -65,6 +197,73 @@ function M.setup(config)
     end
   })
 
+  -- Neovim leaves the columns for line numbers, signs, and folds out of the
+  -- viewport margins, so they are reported here to keep them from scrolling.
+  -- The buffer of each window is reported to find the overlays of its
+  -- extmarks.
+  local gutters = {}
+  local buffers = {}
+  local function send_windows()
+    for _, info in ipairs(vim.fn.getwininfo()) do
+      if gutters[info.winid] ~= info.textoff then
+        gutters[info.winid] = info.textoff
+        vim.rpcnotify(1, 'neophyte.set_gutter', { info.winid, info.textoff })
+      end
+      if buffers[info.winid] ~= info.bufnr then
+        buffers[info.winid] = info.bufnr
+        vim.rpcnotify(1, 'neophyte.set_buffer', { info.winid, info.bufnr })
+      end
+    end
+  end
+
+  vim.api.nvim_create_autocmd('WinScrolled', {
+    group = group,
+    callback = function()
+      for win, change in pairs(vim.v.event) do
+        local handle = tonumber(win)
+        if handle ~= nil and change.leftcol ~= 0 then
+          vim.rpcnotify(1, 'neophyte.scroll_horizontal', { handle, change.leftcol })
+        end
+      end
+      send_windows()
+    end
+  })
+
+  vim.api.nvim_create_autocmd({ 'BufWinEnter', 'WinNew', 'WinResized', 'TextChanged', 'TextChangedI' }, {
+    group = group,
+    callback = send_windows,
+  })
+
+  vim.api.nvim_create_autocmd('OptionSet', {
+    group = group,
+    pattern = { 'number', 'relativenumber', 'numberwidth', 'signcolumn', 'foldcolumn', 'statuscolumn' },
+    callback = send_windows,
+  })
+
+  vim.api.nvim_create_autocmd('WinClosed', {
+    group = group,
+    callback = function(args)
+      local window = tonumber(args.match)
+      gutters[window] = nil
+      buffers[window] = nil
+    end
+  })
+
+  vim.api.nvim_create_autocmd('BufUnload', {
+    group = group,
+    callback = function(args)
+      if overlay_marks[args.buf] ~= nil then
+        overlay_marks[args.buf] = nil
+        vim.rpcnotify(1, 'neophyte.overlay_clear', { args.buf })
+      end
+    end
+  })
+
+  vim.api.nvim_create_autocmd({ 'BufEnter', 'TextChanged', 'TextChangedI', 'CursorHold', 'CursorHoldI' }, {
+    group = group,
+    callback = prune_overlays,
+  })
+
   if config.fonts ~= nil then
     M.set_fonts(config.fonts)
   end
@@ -87,6 +286,38 @@ function M.setup(config)
     M.set_underline_offset(config.underline_offset)
   end
 
+  if config.letter_spacing ~= nil then
+    M.set_letter_spacing(config.letter_spacing)
+  end
+
+  if config.line_height ~= nil then
+    M.set_line_height(config.line_height)
+  end
+
+  if config.box_drawing ~= nil then
+    M.set_box_drawing(config.box_drawing)
+  end
+
+  if config.symbol_ranges ~= nil then
+    M.set_symbol_ranges(config.symbol_ranges)
+  end
+
+  if config.break_ligatures_at_cursor ~= nil then
+    M.set_break_ligatures_at_cursor(config.break_ligatures_at_cursor)
+  end
+
+  if config.break_ligatures_modes ~= nil then
+    M.set_break_ligatures_modes(config.break_ligatures_modes)
+  end
+
+  if config.highlight_features ~= nil then
+    M.set_highlight_features(config.highlight_features)
+  end
+
+  if config.highlight_fonts ~= nil then
+    M.set_highlight_fonts(config.highlight_fonts)
+  end
+
   if config.cursor_speed ~= nil then
     M.set_cursor_speed(config.cursor_speed)
   end
@@ -95,6 +326,50 @@ function M.setup(config)
     M.set_scroll_speed(config.scroll_speed)
   end
 
+  if config.cursor_blink_fade ~= nil then
+    M.set_cursor_blink_fade(config.cursor_blink_fade)
+  end
+
+  if config.cursor_curve ~= nil then
+    M.set_cursor_curve(config.cursor_curve)
+  end
+
+  if config.scroll_curve ~= nil then
+    M.set_scroll_curve(config.scroll_curve)
+  end
+
+  if config.cursor_effect ~= nil then
+    M.set_cursor_effect(config.cursor_effect)
+  end
+
+  if config.float_transition ~= nil then
+    M.set_float_transition(config.float_transition)
+  end
+
+  if config.float_decoration ~= nil then
+    M.set_float_decoration(config.float_decoration)
+  end
+
+  if config.window_move_duration ~= nil then
+    M.set_window_move_duration(config.window_move_duration)
+  end
+
+  if config.dim_inactive ~= nil then
+    M.set_dim_inactive(config.dim_inactive)
+  end
+
+  if config.scrollbar ~= nil then
+    M.set_scrollbar(config.scrollbar)
+  end
+
+  if config.toasts ~= nil then
+    M.set_toasts(config.toasts)
+  end
+
+  if config.link_opener ~= nil then
+    M.set_link_opener(config.link_opener)
+  end
+
   if config.bg_override ~= nil then
     local bg = config.bg_override
     assert(bg)
@@ -151,6 +426,78 @@ function M.set_underline_offset(offset)
   vim.rpcnotify(1, 'neophyte.set_underline_offset', { offset })
 end
 
+---Set the cell width as a multiple of the font's cell width
+---@param multiplier number
+function M.set_letter_spacing(multiplier)
+  vim.rpcnotify(1, 'neophyte.set_letter_spacing', { multiplier })
+end
+
+---Get the cell width as a multiple of the font's cell width
+---@return number
+function M.get_letter_spacing()
+  return vim.rpcrequest(1, 'neophyte.get_letter_spacing', {})
+end
+
+---Set the cell height as a multiple of the font's line height. The 'linespace' option adds to this.
+---@param multiplier number
+function M.set_line_height(multiplier)
+  vim.rpcnotify(1, 'neophyte.set_line_height', { multiplier })
+end
+
+---Get the cell height as a multiple of the font's line height
+---@return number
+function M.get_line_height()
+  return vim.rpcrequest(1, 'neophyte.get_line_height', {})
+end
+
+---Set whether box-drawing, block, braille, and powerline characters are drawn by Neophyte to fit the cell exactly rather than taken from fonts. Enabled by default.
+---@param enabled boolean
+function M.set_box_drawing(enabled)
+  vim.rpcnotify(1, 'neophyte.set_box_drawing', { enabled })
+end
+
+---Get whether box-drawing and similar characters are drawn by Neophyte
+---@return boolean
+function M.get_box_drawing()
+  return vim.rpcrequest(1, 'neophyte.get_box_drawing', {})
+end
+
+---Set the codepoint ranges for symbols that are scaled and centered to fit the cell, or two cells if followed by a space. Defaults to the private use areas used by Nerd Fonts and other icon fonts.
+---@param ranges SymbolRange[]
+function M.set_symbol_ranges(ranges)
+  vim.rpcnotify(1, 'neophyte.set_symbol_ranges', ranges)
+end
+
+---Set whether ligatures are broken up around the cursor so that the characters under it are visible
+---@param enabled boolean
+function M.set_break_ligatures_at_cursor(enabled)
+  vim.rpcnotify(1, 'neophyte.set_break_ligatures_at_cursor', { enabled })
+end
+
+---Get whether ligatures are broken up around the cursor
+---@return boolean
+function M.get_break_ligatures_at_cursor()
+  return vim.rpcrequest(1, 'neophyte.get_break_ligatures_at_cursor', {})
+end
+
+---Set the modes in which ligatures are broken up around the cursor, such as { 'insert', 'replace' }. Mode names are those from the 'guicursor' option.
+---@param modes string[]
+function M.set_break_ligatures_modes(modes)
+  vim.rpcnotify(1, 'neophyte.set_break_ligatures_modes', modes)
+end
+
+---Set font features for text in the given highlight groups, such as { String = { { name = 'liga', value = 0 } } } to disable ligatures in strings. These are applied after the font's own features.
+---@param features table<string, FontFeature[]>
+function M.set_highlight_features(features)
+  vim.rpcnotify(1, 'neophyte.set_highlight_features', features)
+end
+
+---Set fonts for text in the given highlight groups, such as { Comment = 'Victor Mono' }. Text falls back to the regular fonts for characters these fonts don't have.
+---@param fonts table<string, Font>
+function M.set_highlight_fonts(fonts)
+  vim.rpcnotify(1, 'neophyte.set_highlight_fonts', fonts)
+end
+
 ---Get the names of loaded fonts
 ---@return string[]
 function M.get_fonts()
@@ -181,6 +528,175 @@ function M.get_scroll_speed()
   return vim.rpcrequest(1, 'neophyte.get_scroll_speed', {})
 end
 
+---Set the seconds over which the cursor fades while blinking. Zero toggles
+---the cursor instead. Blinking follows the blinkwait, blinkon, and blinkoff
+---values of 'guicursor'. Fades are limited to 10 seconds.
+---@param seconds number
+function M.set_cursor_blink_fade(seconds)
+  vim.rpcnotify(1, 'neophyte.set_cursor_blink_fade', { seconds })
+end
+
+---Set how the cursor animates to a new position
+---@param curve AnimationCurve
+function M.set_cursor_curve(curve)
+  vim.rpcnotify(1, 'neophyte.set_cursor_curve', { curve })
+end
+
+---Set how windows animate when scrolling
+---@param curve AnimationCurve
+function M.set_scroll_curve(curve)
+  vim.rpcnotify(1, 'neophyte.set_scroll_curve', { curve })
+end
+
+---Set the effect drawn over the text and beneath the cursor while it moves
+---@param effect CursorEffect
+function M.set_cursor_effect(effect)
+  vim.rpcnotify(1, 'neophyte.set_cursor_effect', { effect })
+end
+
+---Set how floating windows animate as they open, close, and move
+---@param transition FloatTransition
+function M.set_float_transition(transition)
+  vim.rpcnotify(1, 'neophyte.set_float_transition', { transition })
+end
+
+---Set the shadows, rounded corners, and outlines drawn for floating windows
+---@param decoration FloatDecoration
+function M.set_float_decoration(decoration)
+  vim.rpcnotify(1, 'neophyte.set_float_decoration', { decoration })
+end
+
+---Set the seconds for split windows to glide to a new position when they
+---move, such as when opening a split or resizing. Zero makes them jump.
+---Durations are limited to 10 seconds.
+---@param seconds number
+function M.set_window_move_duration(seconds)
+  vim.rpcnotify(1, 'neophyte.set_window_move_duration', { seconds })
+end
+
+---Set how windows other than the one with the cursor are dimmed
+---@param dim DimInactive
+function M.set_dim_inactive(dim)
+  vim.rpcnotify(1, 'neophyte.set_dim_inactive', { dim })
+end
+
+---Enable or disable dimming windows other than the one with the cursor
+---@param enabled boolean
+function M.set_dim_inactive_enabled(enabled)
+  vim.rpcnotify(1, 'neophyte.set_dim_inactive_enabled', { enabled })
+end
+
+---Get whether windows other than the one with the cursor are dimmed
+---@return boolean
+function M.get_dim_inactive_enabled()
+  return vim.rpcrequest(1, 'neophyte.get_dim_inactive_enabled', {})
+end
+
+---Set how the scrollbars of normal windows are drawn. The scrollbar shows
+---when the window scrolls and can be dragged or clicked to scroll.
+---@param scrollbar Scrollbar
+function M.set_scrollbar(scrollbar)
+  vim.rpcnotify(1, 'neophyte.set_scrollbar', { scrollbar })
+end
+
+---Set how messages are shown as toasts, which are colored by the kind of
+---message. Requires starting Neophyte with `--messages`.
+---@param toasts Toasts
+function M.set_toasts(toasts)
+  vim.rpcnotify(1, 'neophyte.set_toasts', { toasts })
+end
+
+---Set the command that hyperlinks are opened with on ctrl+click. The url is
+---passed as the last argument.
+---@param opener string[] The program followed by any arguments
+function M.set_link_opener(opener)
+  vim.rpcnotify(1, 'neophyte.set_link_opener', { opener })
+end
+
+---Replace the path of an image with the contents of the file
+---@generic T
+---@param image T
+---@return T
+local function read_image_path(image)
+  image = vim.deepcopy(image)
+  if image.path ~= nil then
+    local file = assert(io.open(image.path, 'rb'))
+    image.png = file:read('*a')
+    file:close()
+    image.path = nil
+  end
+  return image
+end
+
+---Show an image over the cells of a window. The image scrolls with the
+---window contents. Showing an image with the same ID replaces it.
+---@param image Image
+function M.image_show(image)
+  image = read_image_path(image)
+  if image.window == nil and image.grid == nil then
+    image.window = vim.api.nvim_get_current_win()
+  end
+  vim.rpcnotify(1, 'neophyte.image_show', { image })
+end
+
+---Hide an image shown with image_show
+---@param id integer
+function M.image_hide(id)
+  vim.rpcnotify(1, 'neophyte.image_hide', { id })
+end
+
+---Attach a GUI overlay to an extmark, replacing any that it had. The overlay
+---follows the extmark as it moves with edits and scrolling and is removed
+---along with the extmark or its buffer. The extmark must be created with
+---ui_watched = true.
+---@param buffer integer Buffer handle, or 0 for the current buffer
+---@param ns_id integer
+---@param mark_id integer
+---@param overlay Overlay
+function M.overlay_set(buffer, ns_id, mark_id, overlay)
+  if buffer == 0 then
+    buffer = vim.api.nvim_get_current_buf()
+  end
+  overlay = read_image_path(overlay)
+  overlay.buffer = buffer
+  overlay.ns_id = ns_id
+  overlay.mark_id = mark_id
+  vim.rpcnotify(1, 'neophyte.overlay_set', { overlay })
+  overlay_marks[buffer] = overlay_marks[buffer] or {}
+  overlay_marks[buffer][ns_id] = overlay_marks[buffer][ns_id] or {}
+  overlay_marks[buffer][ns_id][mark_id] = true
+end
+
+---Create a UI-watched extmark with a GUI overlay attached
+---@param buffer integer Buffer handle, or 0 for the current buffer
+---@param ns_id integer
+---@param line integer Zero-based line of the extmark
+---@param col integer Zero-based byte column of the extmark
+---@param overlay Overlay
+---@return integer mark_id
+function M.overlay_add(buffer, ns_id, line, col, overlay)
+  local mark_id = vim.api.nvim_buf_set_extmark(buffer, ns_id, line, col, {
+    ui_watched = true,
+  })
+  M.overlay_set(buffer, ns_id, mark_id, overlay)
+  return mark_id
+end
+
+---Remove the GUI overlay from an extmark. The extmark itself is left alone.
+---@param buffer integer Buffer handle, or 0 for the current buffer
+---@param ns_id integer
+---@param mark_id integer
+function M.overlay_remove(buffer, ns_id, mark_id)
+  if buffer == 0 then
+    buffer = vim.api.nvim_get_current_buf()
+  end
+  local marks = overlay_marks[buffer] and overlay_marks[buffer][ns_id]
+  if marks ~= nil then
+    marks[mark_id] = nil
+  end
+  vim.rpcnotify(1, 'neophyte.overlay_remove', { buffer, ns_id, mark_id })
+end
+
 ---Sets the window to fullscreen or windowed
 ---@param is_fullscreen boolean
 function M.set_fullscreen(is_fullscreen)
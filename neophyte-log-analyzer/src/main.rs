@@ -32,7 +32,7 @@ fn main() -> anyhow::Result<()> {
         .into_iter()
         .map(|(path, timings)| (path, Stats::from_timings(timings)))
         .collect();
-    timings.sort_unstable_by(|l, r| r.1.cmp(&l.1));
+    timings.sort_unstable_by_key(|r| std::cmp::Reverse(r.1));
 
     let mut table = Table::new();
     table.add_row(row![
@@ -71,7 +71,7 @@ impl Stats {
         let total: u128 = timings.iter().cloned().sum();
         let mean = total / count;
         let mid = timings.len() / 2;
-        let median = if count % 2 == 0 {
+        let median = if count.is_multiple_of(2) {
             (timings[mid - 1] + timings[mid]) / 2
         } else {
             timings[mid - 1]
@@ -41,9 +41,7 @@ impl Debug for GridLine {
             .cells
             .iter()
             .flat_map(|cell| {
-                std::iter::repeat(cell.text.chars())
-                    .take(cell.repeat.unwrap_or(1) as usize)
-                    .flatten()
+                std::iter::repeat_n(cell.text.chars(), cell.repeat.unwrap_or(1) as usize).flatten()
             })
             .collect();
         s.field("cells", &cells);
@@ -12,6 +12,9 @@ pub struct HlAttrDefine {
     pub rgb_attr: Attributes,
     /// Highlights in terminal 256-color codes
     pub cterm_attr: Attributes,
+    /// The target of a hyperlink, such as from an OSC 8 sequence or a
+    /// markdown link
+    pub url: Option<String>,
     /// A semantic description of the highlights active in a cell. Ordered by
     /// priority from low to high.
     pub info: Vec<Info>,
@@ -20,21 +23,35 @@ pub struct HlAttrDefine {
 impl Parse for HlAttrDefine {
     fn parse(value: Value) -> Option<Self> {
         let mut iter = Values::new(value)?;
+        let id = iter.next()?;
+        let rgb_attr: Value = iter.next()?;
         Some(Self {
-            id: iter.next()?,
-            rgb_attr: iter.next()?,
+            id,
+            url: parse_url(&rgb_attr),
+            rgb_attr: Attributes::parse(rgb_attr)?,
             cterm_attr: iter.next()?,
             info: iter.next()?,
         })
     }
 }
 
+/// The url entry of a map of highlight attributes
+fn parse_url(attributes: &Value) -> Option<String> {
+    let Value::Map(map) = attributes else {
+        return None;
+    };
+    map.iter()
+        .find(|(k, _)| k.as_str() == Some("url"))
+        .and_then(|(_, v)| v.as_str())
+        .map(String::from)
+}
+
 // NOTE: Ignoring the altfont attribute. Not sure what this type should be or
 // what it is used for.
 
 /// Attributes of a highlight attribute definition. Colors may be given in RGB
 /// or terminal 256-color.
-#[derive(Clone, Copy, Serialize)]
+#[derive(Clone, Copy, Default, Serialize)]
 pub struct Attributes {
     /// foreground color.
     pub foreground: Option<Rgb>,
@@ -45,17 +62,6 @@ pub struct Attributes {
     packed: u16,
 }
 
-impl Default for Attributes {
-    fn default() -> Self {
-        Self {
-            foreground: None,
-            background: None,
-            special: None,
-            packed: 100 << Self::BLEND_TRAILING,
-        }
-    }
-}
-
 #[rustfmt::skip]
 impl Attributes {
     const REVERSE:       u16 = 0b0000000000000001;
@@ -120,9 +126,18 @@ impl Attributes {
 
     /// Blend level (0-100). Could be used by UIs to support blending floating
     /// windows to the background or to signal a transparent cursor
+    pub fn blend_level(&self) -> u8 {
+        ((self.packed & Self::BLEND_MASK) >> Self::BLEND_TRAILING) as u8
+    }
+
+    /// The opacity given by the blend level, from 0 to 1
     pub fn blend(&self) -> f32 {
-        let percentage = (self.packed & Self::BLEND_MASK >> Self::BLEND_TRAILING) as u8;
-        f32::from(100 - percentage) / 100.
+        f32::from(100 - self.blend_level().min(100)) / 100.
+    }
+
+    pub fn set_blend_level(&mut self, blend: u8) {
+        self.packed &= !Self::BLEND_MASK;
+        self.packed |= u16::from(blend.min(100)) << Self::BLEND_TRAILING;
     }
 
     fn maybe_set(&mut self, value: Value, mask: u16) -> Option<()> {
@@ -150,11 +165,7 @@ impl Parse for Attributes {
                 "underdouble" => out.maybe_set(v, Self::UNDERDOUBLE)?,
                 "underdotted" => out.maybe_set(v, Self::UNDERDOTTED)?,
                 "underdashed" => out.maybe_set(v, Self::UNDERDASHED)?,
-                "blend" => {
-                    let blend = u16::parse(v)?;
-                    out.packed &= !Self::BLEND_MASK;
-                    out.packed |= blend << Self::BLEND_TRAILING;
-                }
+                "blend" => out.set_blend_level(u8::parse(v)?),
                 _ => {} // Ignore undocumented attributes
             }
         }
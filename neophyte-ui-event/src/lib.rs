@@ -16,7 +16,7 @@ mod grid_scroll;
 pub mod hl_attr_define;
 mod hl_group_set;
 mod message_content;
-mod messagepack_ext_types;
+pub mod messagepack_ext_types;
 mod mode_change;
 pub mod mode_info_set;
 pub mod msg_history_show;
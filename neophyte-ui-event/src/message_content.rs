@@ -3,7 +3,7 @@ use rmpv::Value;
 use serde::Serialize;
 
 /// A text sequence with highlighting.
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
 pub struct Content {
     /// Chunks to be concatenated without extra spacing
     pub chunks: Vec<ContentChunk>,
@@ -18,7 +18,7 @@ impl Parse for Content {
 }
 
 /// A highlighted portion of text
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ContentChunk {
     /// The ID of the highlight to apply
     pub attr_id: u32,
@@ -6,7 +6,19 @@ macro_rules! msgpack_ext {
     ($x:ident, $doc:meta) => {
         #[derive(Debug, Clone, Serialize)]
         #[$doc]
-        pub struct $x(#[allow(unused)] Value);
+        pub struct $x(Value);
+
+        impl $x {
+            /// The integer handle, as used by the Neovim API
+            pub fn handle(&self) -> Option<i64> {
+                let Value::Ext(_, data) = &self.0 else {
+                    return None;
+                };
+                rmpv::decode::read_value(&mut data.as_slice())
+                    .ok()?
+                    .as_i64()
+            }
+        }
 
         impl Parse for $x {
             fn parse(value: Value) -> Option<Self> {
@@ -48,10 +48,12 @@ impl From<bool> for ReplaceLast {
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum Kind {
     /// Unknown
     Unknown,
+    /// Written message after :write
+    Bufwrite,
     /// Confirm dialog from :confirm
     Confirm,
     /// Substitute confirm dialog, :s_c
@@ -64,36 +66,88 @@ pub enum Kind {
     Echomsg,
     /// From :echoerr
     Echoerr,
+    /// Insert mode completion message
+    Completion,
+    /// Output of a list command, e.g. :ls
+    ListCmd,
     /// Error in Lua code
     LuaError,
+    /// From print() in Lua
+    LuaPrint,
     /// Error response from rpcrequest()
     RpcError,
     /// press-enter prompt after multiple messages
     ReturnPrompt,
     /// Quickfix navigation message
     Quickfix,
+    /// Pattern shown when searching
+    SearchCmd,
     /// Search count message from shortmess S flag
     SearchCount,
+    /// Command run with :!
+    ShellCmd,
+    /// Error output from a shell command
+    ShellErr,
+    /// Output from a shell command
+    ShellOut,
+    /// Nonzero exit code from a shell command
+    ShellRet,
+    /// Undo and redo message
+    Undo,
+    /// Message from 'verbose'
+    Verbose,
+    /// Completions listed with 'wildmenu'
+    Wildlist,
     /// Warning, e.g. "search hit BOTTOM"
     Wmsg,
+    /// A command typed in the cmdline
+    TypedCmd,
+}
+
+impl Kind {
+    /// Whether the message reports an error
+    pub fn is_error(self) -> bool {
+        matches!(
+            self,
+            Self::Emsg | Self::Echoerr | Self::LuaError | Self::RpcError | Self::ShellErr
+        )
+    }
+
+    /// Whether Neovim is waiting for the user to answer the message
+    pub fn is_prompt(self) -> bool {
+        matches!(self, Self::Confirm | Self::ConfirmSub | Self::ReturnPrompt)
+    }
 }
 
 impl Parse for Kind {
     fn parse(value: Value) -> Option<Self> {
         let s = String::parse(value)?;
         Some(match s.as_str() {
-            "Confirm" => Self::Confirm,
-            "ConfirmSub" => Self::ConfirmSub,
-            "Emsg" => Self::Emsg,
-            "Echo" => Self::Echo,
-            "Echomsg" => Self::Echomsg,
-            "Echoerr" => Self::Echoerr,
-            "LuaError" => Self::LuaError,
-            "RpcError" => Self::RpcError,
-            "ReturnPrompt" => Self::ReturnPrompt,
-            "Quickfix" => Self::Quickfix,
-            "SearchCount" => Self::SearchCount,
-            "Wmsg" => Self::Wmsg,
+            "bufwrite" => Self::Bufwrite,
+            "confirm" => Self::Confirm,
+            "confirm_sub" => Self::ConfirmSub,
+            "emsg" => Self::Emsg,
+            "echo" => Self::Echo,
+            "echomsg" => Self::Echomsg,
+            "echoerr" => Self::Echoerr,
+            "completion" => Self::Completion,
+            "list_cmd" => Self::ListCmd,
+            "lua_error" => Self::LuaError,
+            "lua_print" => Self::LuaPrint,
+            "rpc_error" => Self::RpcError,
+            "return_prompt" => Self::ReturnPrompt,
+            "quickfix" => Self::Quickfix,
+            "search_cmd" => Self::SearchCmd,
+            "search_count" => Self::SearchCount,
+            "shell_cmd" => Self::ShellCmd,
+            "shell_err" => Self::ShellErr,
+            "shell_out" => Self::ShellOut,
+            "shell_ret" => Self::ShellRet,
+            "undo" => Self::Undo,
+            "verbose" => Self::Verbose,
+            "wildlist" => Self::Wildlist,
+            "wmsg" => Self::Wmsg,
+            "typed_cmd" => Self::TypedCmd,
             _ => Self::Unknown,
         })
     }
@@ -3,8 +3,6 @@ use crate::{Parse, Values};
 use rmpv::Value;
 use serde::Serialize;
 
-// TODO: Figure out how to use this event
-
 /// Indicates the margins of a window grid which are _not_ part of the viewport
 /// as indicated by the `win_viewport` event. This happens in the presence of
 /// `winbar` and floating window borders.
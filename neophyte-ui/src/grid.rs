@@ -8,7 +8,7 @@ use neophyte_linalg::{CellVec, Vec2};
 use neophyte_ui_event::{Anchor, GridScroll, HlAttrDefine, grid_line, hl_attr_define::Attributes};
 use packed_char::{Contents, PackedChar, U22};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fmt::{self, Debug, Display, Formatter},
     io::Write,
     iter::TakeWhile,
@@ -25,11 +25,93 @@ pub type Id = u32;
 pub struct Grid {
     pub id: Id,
     pub scroll_delta: i32,
+    /// Columns scrolled horizontally since the last flush
+    pub scroll_delta_cols: i32,
+    /// Rows and columns around the edges of the grid that are not part of the
+    /// viewport and stay in place while scrolling
+    pub margins: Margins,
+    /// Columns at the left of the viewport for line numbers, signs, and folds,
+    /// which Neovim does not include in the margins
+    gutter: u32,
+    /// The buffer shown by the window, as reported from Lua
+    pub buffer: Option<i64>,
+    /// The buffer lines shown by the window, if the grid is a window
+    pub viewport: Option<Viewport>,
+    /// UI-watched extmarks that are visible in the grid
+    pub extmarks: HashMap<ExtmarkId, Extmark>,
     pub dirty: DirtyFlags,
+    /// Extmarks that Neovim reported since the last flush
+    reported_extmarks: HashSet<ExtmarkId>,
     window: Window,
     contents: GridContents,
 }
 
+/// The range of buffer lines that a window shows
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    /// The handle of the window
+    pub window: i64,
+    /// The first line shown, zero-based
+    pub topline: u32,
+    /// One past the last line shown
+    pub botline: u32,
+    /// The number of lines in the buffer
+    pub line_count: u32,
+}
+
+/// Identifies an extmark
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExtmarkId {
+    /// Namespace ID
+    pub ns_id: u32,
+    /// Extmark ID
+    pub mark_id: u32,
+}
+
+/// The position of a UI-watched extmark in a grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Extmark {
+    /// The handle of the window showing the extmark
+    pub window: i64,
+    /// The cell the extmark is on
+    pub position: CellVec<u16>,
+}
+
+/// The number of cells on each side of a grid outside of its viewport, such as
+/// the winbar or a floating window border
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Margins {
+    pub top: u32,
+    pub bottom: u32,
+    pub left: u32,
+    pub right: u32,
+}
+
+impl Margins {
+    /// Whether the cell at the given row and column is inside the viewport
+    /// of a grid with the given size
+    pub fn is_inner(self, size: CellVec<u16>, row: usize, col: usize) -> bool {
+        self.inner_rows(size.0.y).contains(&row) && self.inner_cols(size.0.x).contains(&col)
+    }
+
+    /// The rows inside the viewport of a grid with the given height
+    pub fn inner_rows(self, height: u16) -> std::ops::Range<usize> {
+        Self::inner(height, self.top, self.bottom)
+    }
+
+    /// The columns inside the viewport of a grid with the given width
+    pub fn inner_cols(self, width: u16) -> std::ops::Range<usize> {
+        Self::inner(width, self.left, self.right)
+    }
+
+    fn inner(len: u16, start: u32, end: u32) -> std::ops::Range<usize> {
+        let len = len as usize;
+        let start = (start as usize).min(len);
+        let end = len.saturating_sub(end as usize).max(start);
+        start..end
+    }
+}
+
 #[derive(PartialEq, Eq, Debug, Default, Clone, Copy, PartialOrd, Ord)]
 pub struct DirtyFlags(u8);
 
@@ -73,6 +155,22 @@ impl Grid {
         &self.contents
     }
 
+    /// The margins together with the gutter, which stay in place while the
+    /// viewport scrolls
+    pub fn fixed_margins(&self) -> Margins {
+        Margins {
+            left: self.margins.left + self.gutter,
+            ..self.margins
+        }
+    }
+
+    pub fn set_gutter(&mut self, cols: u32) {
+        if self.gutter != cols {
+            self.gutter = cols;
+            self.dirty.set_contents();
+        }
+    }
+
     pub fn contents_mut(&mut self) -> &mut GridContents {
         self.dirty.set_contents();
         &mut self.contents
@@ -87,10 +185,44 @@ impl Grid {
         &mut self.window
     }
 
-    /// Reset dirty flags and scroll delta
+    /// Move the extmarks in the scrolled region with the cells they are on.
+    /// Extmarks that scroll out of the region are forgotten until Neovim
+    /// redraws them.
+    pub fn scroll_extmarks(&mut self, top: u16, bot: u16, left: u16, right: u16, rows: i32) {
+        let rows_range = i32::from(top)..i32::from(bot);
+        let cols_range = left..right;
+        self.extmarks.retain(|_, extmark| {
+            let CellVec(Vec2 { x, y }) = extmark.position;
+            if !rows_range.contains(&i32::from(y)) || !cols_range.contains(&x) {
+                return true;
+            }
+            let y = i32::from(y) - rows;
+            extmark.position.0.y = y as u16;
+            rows_range.contains(&y)
+        });
+    }
+
+    /// Record the position of an extmark that Neovim reported
+    pub fn report_extmark(&mut self, id: ExtmarkId, extmark: Extmark) {
+        self.extmarks.insert(id, extmark);
+        self.reported_extmarks.insert(id);
+    }
+
+    /// Forget the extmarks on a redrawn row unless Neovim reported them again
+    /// since the last flush. Neovim reports the extmarks on each line it
+    /// draws, so the others were deleted or moved off the row.
+    pub fn forget_unreported_extmarks(&mut self, row: u16) {
+        self.extmarks.retain(|id, extmark| {
+            extmark.position.0.y != row || self.reported_extmarks.contains(id)
+        });
+    }
+
+    /// Reset dirty flags and scroll deltas
     pub fn clear_dirty(&mut self) {
         self.dirty.clear();
+        self.reported_extmarks.clear();
         self.scroll_delta = 0;
+        self.scroll_delta_cols = 0;
     }
 }
 
@@ -213,6 +345,15 @@ impl GridContents {
         }
     }
 
+    /// The highlights of the cells in the given row
+    pub fn row_highlights(&self, row: usize) -> Option<impl Iterator<Item = HlId> + '_> {
+        let width = self.size.0.x as usize;
+        let start = row * width;
+        self.buffer
+            .get(start..start + width)
+            .map(|cells| cells.iter().map(|cell| cell.highlight))
+    }
+
     /// Iterate over the grid contents row by row
     pub fn rows(
         &self,
@@ -257,6 +398,9 @@ impl Debug for GridContents {
 pub enum OnceOrChars<'a> {
     Char(std::iter::Once<char>),
     Chars(std::str::Chars<'a>),
+    /// A cell that is not drawn. Unlike an empty continuation cell, it does
+    /// not belong to the cell before it.
+    Hidden,
 }
 
 impl From<char> for OnceOrChars<'_> {
@@ -278,6 +422,7 @@ impl Iterator for OnceOrChars<'_> {
         match self {
             OnceOrChars::Char(iter) => iter.next(),
             OnceOrChars::Chars(iter) => iter.next(),
+            OnceOrChars::Hidden => None,
         }
     }
 }
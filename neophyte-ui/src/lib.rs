@@ -2,20 +2,31 @@ pub mod cmdline;
 pub mod grid;
 pub mod messages;
 pub mod options;
+pub mod width;
 pub mod window;
 
 use self::{
-    cmdline::Cmdline, grid::Grid, messages::Messages, options::GuiFont, window::WindowOffset,
+    cmdline::Cmdline,
+    grid::{Extmark, ExtmarkId, Grid, Margins, Viewport},
+    messages::Messages,
+    options::GuiFont,
+    width::CharWidth,
+    window::WindowOffset,
 };
 use neophyte_linalg::{CellVec, PixelVec, Vec2};
 use neophyte_ui_event::{
     Chdir, CmdlineBlockAppend, CmdlineBlockShow, CmdlinePos, DefaultColorsSet, Event, GridClear,
     GridCursorGoto, GridDestroy, GridLine, GridResize, GridScroll, HlGroupSet, ModeChange,
     ModeInfoSet, MsgHistoryShow, MsgRuler, MsgSetPos, MsgShowcmd, MsgShowmode, OptionSet,
-    PopupmenuSelect, PopupmenuShow, TablineUpdate, WinClose, WinExternalPos, WinFloatPos, WinHide,
-    WinPos, WinViewport, hl_attr_define::Attributes, mode_info_set::ModeInfo,
+    PopupmenuSelect, PopupmenuShow, TablineUpdate, WinClose, WinExternalPos, WinExtmark,
+    WinFloatPos, WinHide, WinPos, WinViewport, WinViewportMargins, hl_attr_define::Attributes,
+    messagepack_ext_types, mode_info_set::ModeInfo,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    ops::Range,
 };
-use std::{collections::HashMap, fmt::Debug};
 use window::{FloatingWindow, NormalWindow, Window};
 
 pub type HlId = u32;
@@ -48,6 +59,16 @@ pub struct Ui {
     /// UI highlights, indexed by their ID
     // TODO: Only store the rgb_attr part
     pub highlights: Vec<Option<Attributes>>,
+    /// The highlight group names that make up each highlight, ordered by
+    /// priority from low to high
+    pub highlight_names: HashMap<HlId, Vec<String>>,
+    /// The blend level of the popupmenu, set by the pumblend option
+    pub pumblend: u8,
+    /// Popupmenu highlights without a blend level of their own, which take
+    /// theirs from pumblend
+    pumblend_highlights: HashSet<HlId>,
+    /// The hyperlink targets of highlights that have them
+    pub highlight_urls: HashMap<HlId, String>,
     /// A lookup from highlight names to highlight IDs
     pub highlight_groups: HashMap<String, HlId>,
     /// Whether the highlights changed since the last flush
@@ -58,6 +79,15 @@ pub struct Ui {
     pub modes: Vec<ModeInfo>,
     /// UI options set by the option_set event
     pub guifont_update: Option<GuiFont>,
+    /// Fonts for double-width characters set by the option_set event
+    pub guifontwide_update: Option<GuiFont>,
+    /// Pixels between rows set by the option_set event
+    pub linespace_update: Option<u32>,
+    /// Whether Neovim shapes Arabic text, set by the option_set event
+    pub arabicshape_update: Option<bool>,
+    /// Display widths of text laid out by the UI, from the ambiwidth and emoji
+    /// options
+    pub char_width: CharWidth,
     /// Default highlight colors
     pub default_colors: DefaultColorsSet,
     /// Manages ext_hlstate events
@@ -71,6 +101,13 @@ pub struct Ui {
     /// Did we receive a flush event?
     pub did_flush: bool,
     pub ignore_next_scroll: bool,
+    /// The grid for each window handle
+    window_grids: HashMap<i64, grid::Id>,
+    /// The gutter width of each window handle, which may be reported before
+    /// Neovim positions the window
+    gutters: HashMap<i64, u32>,
+    /// The buffer shown by each window handle, likewise reported from Lua
+    buffers: HashMap<i64, i64>,
 }
 
 impl Ui {
@@ -100,6 +137,60 @@ impl Ui {
             .flatten()
     }
 
+    /// The grid that shows the window with the given handle
+    pub fn window_grid(&self, window: i64) -> Option<grid::Id> {
+        self.window_grids.get(&window).copied()
+    }
+
+    /// Scroll the grid of the given window horizontally, in lieu of Neovim
+    /// reporting horizontal scrolling through UI events
+    pub fn scroll_horizontal(&mut self, window: i64, cols: i32) {
+        if let Some(&grid) = self.window_grids.get(&window)
+            && let Some(grid) = self.grid_mut(grid)
+        {
+            grid.scroll_delta_cols += cols;
+        }
+    }
+
+    /// Set the number of columns at the left of the window for line numbers,
+    /// signs, and folds, in lieu of Neovim reporting them through UI events
+    pub fn set_gutter(&mut self, window: i64, cols: u32) {
+        self.gutters.insert(window, cols);
+        if let Some(&grid) = self.window_grids.get(&window)
+            && let Some(grid) = self.grid_mut(grid)
+        {
+            grid.set_gutter(cols);
+        }
+    }
+
+    /// Set the buffer shown by the window, which Neovim does not report
+    /// through UI events
+    pub fn set_buffer(&mut self, window: i64, buffer: i64) {
+        self.buffers.insert(window, buffer);
+        if let Some(&grid) = self.window_grids.get(&window)
+            && let Some(grid) = self.grid_mut(grid)
+        {
+            grid.buffer = Some(buffer);
+        }
+    }
+
+    /// Remember which grid shows the window
+    fn set_window_grid(&mut self, window: &messagepack_ext_types::Window, grid: grid::Id) {
+        if let Some(handle) = window.handle() {
+            self.window_grids.insert(handle, grid);
+            let cols = self.gutters.get(&handle).copied();
+            let buffer = self.buffers.get(&handle).copied();
+            if let Some(grid) = self.grid_mut(grid) {
+                if let Some(cols) = cols {
+                    grid.set_gutter(cols);
+                }
+                if buffer.is_some() {
+                    grid.buffer = buffer;
+                }
+            }
+        }
+    }
+
     /// Get the grid with the given ID or create it if it does not exist
     fn get_or_create_grid(&mut self, id: grid::Id) -> &mut Grid {
         match self.grid_index(id) {
@@ -117,6 +208,9 @@ impl Ui {
         self.did_highlights_change = false;
         self.did_flush = false;
         self.guifont_update = None;
+        self.guifontwide_update = None;
+        self.linespace_update = None;
+        self.arabicshape_update = None;
         self.ignore_next_scroll = false;
         self.messages.dirty = false;
         for grid in self.grids.iter_mut() {
@@ -129,6 +223,26 @@ impl Ui {
         match event {
             Event::OptionSet(event) => match event {
                 OptionSet::Guifont(s) if !s.is_empty() => self.guifont_update = Some(s.into()),
+                OptionSet::Guifontwide(s) => self.guifontwide_update = Some(s.into()),
+                OptionSet::Linespace(linespace) => self.linespace_update = Some(linespace),
+                OptionSet::Arabicshape(arabicshape) => self.arabicshape_update = Some(arabicshape),
+                OptionSet::Ambiwidth(ambiwidth) => {
+                    self.char_width.set_ambiwidth(ambiwidth);
+                    self.messages.dirty = true;
+                }
+                OptionSet::Emoji(emoji) => {
+                    self.char_width.emoji = emoji;
+                    self.messages.dirty = true;
+                }
+                OptionSet::Pumblend(pumblend) => {
+                    self.pumblend = pumblend.min(100) as u8;
+                    for &id in self.pumblend_highlights.iter() {
+                        if let Some(Some(hl)) = self.highlights.get_mut(id as usize) {
+                            hl.set_blend_level(self.pumblend);
+                        }
+                    }
+                    self.did_highlights_change = true;
+                }
                 _ => {}
             },
             Event::DefaultColorsSet(event) => {
@@ -141,7 +255,29 @@ impl Ui {
                 if i > self.highlights.len() {
                     self.highlights.resize(i * 2, None);
                 }
-                self.highlights.insert(i, Some(event.rgb_attr));
+                let mut attr = event.rgb_attr;
+                let is_popupmenu = event.info.iter().any(|info| {
+                    info.ui_name
+                        .as_ref()
+                        .is_some_and(|name| name.starts_with("Pmenu"))
+                });
+                if is_popupmenu && attr.blend_level() == 0 {
+                    attr.set_blend_level(self.pumblend);
+                    self.pumblend_highlights.insert(event.id);
+                } else {
+                    self.pumblend_highlights.remove(&event.id);
+                }
+                self.highlights.insert(i, Some(attr));
+                match event.url {
+                    Some(url) => self.highlight_urls.insert(event.id, url),
+                    None => self.highlight_urls.remove(&event.id),
+                };
+                let names = event
+                    .info
+                    .into_iter()
+                    .filter_map(|info| info.hi_name.or(info.ui_name))
+                    .collect();
+                self.highlight_names.insert(event.id, names);
             }
             Event::HlGroupSet(HlGroupSet { name, hl_id }) => {
                 self.did_highlights_change = true;
@@ -165,11 +301,13 @@ impl Ui {
                     .contents_mut()
                     .resize(CellVec(Vec2::new(width, height)));
             }
-            Event::GridClear(GridClear { grid }) => self
-                .grid_mut(grid)
-                .expect("Tried to clear nonexistent grid")
-                .contents_mut()
-                .clear(),
+            Event::GridClear(GridClear { grid }) => {
+                let grid = self
+                    .grid_mut(grid)
+                    .expect("Tried to clear nonexistent grid");
+                grid.extmarks.clear();
+                grid.contents_mut().clear();
+            }
             Event::GridDestroy(GridDestroy { grid }) => self.delete_grid(grid),
             Event::GridCursorGoto(GridCursorGoto { grid, row, column }) => {
                 self.cursor.pos = CellVec::new(column, row);
@@ -182,12 +320,14 @@ impl Ui {
                 left,
                 right,
                 rows,
-                cols: _,
+                cols,
             }) => {
-                self.grid_mut(grid)
-                    .expect("Tried to scroll nonexistent grid")
-                    .contents_mut()
-                    .scroll(top, bot, left, right, rows);
+                let grid = self
+                    .grid_mut(grid)
+                    .expect("Tried to scroll nonexistent grid");
+                grid.scroll_delta_cols += cols;
+                grid.scroll_extmarks(top, bot, left, right, rows);
+                grid.contents_mut().scroll(top, bot, left, right, rows);
             }
             Event::GridLine(GridLine {
                 grid,
@@ -195,21 +335,23 @@ impl Ui {
                 col_start,
                 cells,
             }) => {
-                self.grid_mut(grid)
-                    .expect("Tried to update a line of a nonexistent grid")
-                    .contents_mut()
-                    .grid_line(row, col_start, cells);
+                let grid = self
+                    .grid_mut(grid)
+                    .expect("Tried to update a line of a nonexistent grid");
+                grid.forget_unreported_extmarks(row);
+                grid.contents_mut().grid_line(row, col_start, cells);
             }
 
             Event::WinPos(WinPos {
                 grid,
-                win: _,
+                win,
                 start_row,
                 start_col,
                 width,
                 height,
             }) => {
                 self.show_normal(grid);
+                self.set_window_grid(&win, grid);
                 *self
                     .grid_mut(grid)
                     .expect("Tried to update the position of a nonexistent grid")
@@ -220,7 +362,7 @@ impl Ui {
             }
             Event::WinFloatPos(WinFloatPos {
                 grid,
-                win: _,
+                win,
                 anchor,
                 anchor_grid,
                 anchor_row,
@@ -229,6 +371,7 @@ impl Ui {
                 zindex,
             }) => {
                 self.show_float(DrawItem::new(grid, zindex));
+                self.set_window_grid(&win, grid);
                 *self
                     .grid_mut(grid)
                     .expect("Tried to update the position of a nonexistent grid")
@@ -250,6 +393,16 @@ impl Ui {
             }
             Event::WinClose(WinClose { grid }) => {
                 self.hide(grid);
+                let gutters = &mut self.gutters;
+                let buffers = &mut self.buffers;
+                self.window_grids.retain(|window, &mut window_grid| {
+                    let is_closed = window_grid == grid;
+                    if is_closed {
+                        gutters.remove(window);
+                        buffers.remove(window);
+                    }
+                    !is_closed
+                });
                 // It seems like we shouldn't be able to receive this event
                 // when a grid doesn't exist, but I have had this happen when
                 // opening DAP UI.
@@ -260,20 +413,71 @@ impl Ui {
             Event::WinViewport(WinViewport {
                 grid,
                 scroll_delta,
-                win: _,
-                topline: _,
-                botline: _,
+                win,
+                topline,
+                botline,
                 curline: _,
                 curcol: _,
-                line_count: _,
+                line_count,
+            }) => {
+                self.set_window_grid(&win, grid);
+                let ignore_next_scroll = self.ignore_next_scroll;
+                let grid = self
+                    .grid_mut(grid)
+                    .expect("Tried to update the viewport of a nonexistent grid");
+                grid.viewport = win.handle().map(|window| Viewport {
+                    window,
+                    topline,
+                    botline,
+                    line_count,
+                });
+                if !ignore_next_scroll {
+                    grid.scroll_delta = scroll_delta;
+                }
+            }
+            Event::WinViewportMargins(WinViewportMargins {
+                grid,
+                win,
+                top,
+                bottom,
+                left,
+                right,
+            }) => {
+                self.set_window_grid(&win, grid);
+                let margins = Margins {
+                    top,
+                    bottom,
+                    left,
+                    right,
+                };
+                let grid = self
+                    .grid_mut(grid)
+                    .expect("Tried to update the margins of a nonexistent grid");
+                if grid.margins != margins {
+                    grid.margins = margins;
+                    grid.dirty.set_contents();
+                }
+            }
+            Event::WinExtmark(WinExtmark {
+                grid,
+                win,
+                ns_id,
+                mark_id,
+                row,
+                col,
             }) => {
-                if !self.ignore_next_scroll {
-                    self.grid_mut(grid)
-                        .expect("Tried to update the viewport of a nonexistent grid")
-                        .scroll_delta = scroll_delta;
+                if let Some(window) = win.handle()
+                    && let Some(grid) = self.grid_mut(grid)
+                {
+                    grid.report_extmark(
+                        ExtmarkId { ns_id, mark_id },
+                        Extmark {
+                            window,
+                            position: CellVec::new(col, row),
+                        },
+                    );
                 }
             }
-            Event::WinViewportMargins(_) | Event::WinExtmark(_) => {}
 
             Event::PopupmenuShow(event) => self.popupmenu = Some(event),
             Event::PopupmenuSelect(PopupmenuSelect { selected }) => {
@@ -458,6 +662,53 @@ impl Ui {
         }
         None
     }
+
+    /// The hyperlink at the given cell, if any
+    pub fn link_at(&self, cell: GridUnderCursor) -> Option<Link<'_>> {
+        let grid = self.grid(cell.grid)?;
+        let highlights: Vec<_> = grid
+            .contents()
+            .row_highlights(cell.position.0.y as usize)?
+            .collect();
+        let url_of = |col: usize| {
+            highlights
+                .get(col)
+                .and_then(|highlight| self.highlight_urls.get(highlight))
+        };
+        let col = cell.position.0.x as usize;
+        let url = url_of(col)?;
+        let start = (0..col)
+            .rev()
+            .take_while(|&i| url_of(i) == Some(url))
+            .last()
+            .unwrap_or(col);
+        let end = (col..highlights.len())
+            .take_while(|&i| url_of(i) == Some(url))
+            .count()
+            + col;
+        Some(Link {
+            url,
+            highlight: highlights[col],
+            grid: cell.grid,
+            row: cell.position.0.y,
+            cols: start as u32..end as u32,
+        })
+    }
+}
+
+/// A run of cells with the same hyperlink target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link<'a> {
+    /// The hyperlink target
+    pub url: &'a str,
+    /// The highlight of the cell under the mouse
+    pub highlight: HlId,
+    /// The grid containing the link
+    pub grid: grid::Id,
+    /// The row of the link in grid cells
+    pub row: u32,
+    /// The columns of the link in grid cells
+    pub cols: Range<u32>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -490,3 +741,166 @@ impl Default for CursorInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neophyte_ui_event::{
+        Content, MsgShow,
+        grid_line::Cell,
+        msg_show::{Kind, ReplaceLast},
+    };
+
+    fn cell(text: &str, hl_id: u32, repeat: u32) -> Cell {
+        Cell {
+            text: text.to_string(),
+            hl_id: Some(hl_id),
+            repeat: Some(repeat),
+        }
+    }
+
+    #[test]
+    fn link_spans_cells_with_the_same_url() {
+        let mut ui = Ui::new();
+        ui.process(Event::GridResize(GridResize {
+            grid: 1,
+            width: 10,
+            height: 1,
+        }));
+        ui.process(Event::GridLine(GridLine {
+            grid: 1,
+            row: 0,
+            col_start: 0,
+            cells: vec![
+                cell("a", 0, 2),
+                cell("b", 1, 3),
+                cell("c", 2, 2),
+                cell("d", 0, 3),
+            ],
+        }));
+        ui.highlight_urls.insert(1, "https://neovim.io".to_string());
+        ui.highlight_urls.insert(2, "https://neovim.io".to_string());
+        let at = |x| {
+            ui.link_at(GridUnderCursor {
+                grid: 1,
+                position: CellVec::new(x, 0),
+            })
+        };
+        let link = at(3).unwrap();
+        assert_eq!(link.url, "https://neovim.io");
+        assert_eq!(link.highlight, 1);
+        assert_eq!(link.cols, 2..7);
+        assert_eq!(at(6).unwrap().cols, 2..7);
+        assert_eq!(at(1), None);
+        assert_eq!(at(7), None);
+    }
+
+    #[test]
+    fn extmarks_scroll_with_their_cells() {
+        let mut grid = Grid::new(1);
+        let mark = |mark_id, row| {
+            (
+                ExtmarkId { ns_id: 1, mark_id },
+                Extmark {
+                    window: 1000,
+                    position: CellVec::new(0, row),
+                },
+            )
+        };
+        grid.extmarks.extend([mark(1, 1), mark(2, 3), mark(3, 9)]);
+        grid.scroll_extmarks(1, 8, 0, 10, 2);
+        let row = |mark_id| {
+            grid.extmarks
+                .get(&ExtmarkId { ns_id: 1, mark_id })
+                .map(|extmark| extmark.position.0.y)
+        };
+        assert_eq!(row(1), None);
+        assert_eq!(row(2), Some(1));
+        assert_eq!(row(3), Some(9));
+    }
+
+    #[test]
+    fn redrawn_rows_forget_unreported_extmarks() {
+        let mut grid = Grid::new(1);
+        let id = |mark_id| ExtmarkId { ns_id: 1, mark_id };
+        let mark = |row| Extmark {
+            window: 1000,
+            position: CellVec::new(0, row),
+        };
+        for (mark_id, row) in [(1, 0), (2, 1), (3, 2)] {
+            grid.report_extmark(id(mark_id), mark(row));
+        }
+        grid.clear_dirty();
+
+        // Mark 1 is reported again as its row is redrawn, mark 2 is not, and
+        // mark 3 is on a row that is not redrawn
+        grid.report_extmark(id(1), mark(0));
+        grid.forget_unreported_extmarks(0);
+        grid.forget_unreported_extmarks(1);
+        assert!(grid.extmarks.contains_key(&id(1)));
+        assert!(!grid.extmarks.contains_key(&id(2)));
+        assert!(grid.extmarks.contains_key(&id(3)));
+    }
+
+    #[test]
+    fn replaced_messages_keep_their_id() {
+        let mut ui = Ui::new();
+        let show = |kind, replace_last| {
+            Event::MsgShow(MsgShow {
+                kind,
+                content: Content::default(),
+                replace_last,
+            })
+        };
+        ui.process(show(Kind::Echo, ReplaceLast::Keep));
+        ui.process(show(Kind::Emsg, ReplaceLast::Keep));
+        let ids: Vec<_> = ui.messages.show.iter().map(|message| message.id).collect();
+        assert_ne!(ids[0], ids[1]);
+        ui.process(show(Kind::Wmsg, ReplaceLast::Replace));
+        assert_eq!(ui.messages.show.len(), 2);
+        assert_eq!(ui.messages.show[1].id, ids[1]);
+        assert_eq!(ui.messages.show[1].kind, Kind::Wmsg);
+        ui.process(Event::MsgClear);
+        assert!(ui.messages.show.is_empty());
+    }
+
+    #[test]
+    fn gutter_stays_with_the_margins() {
+        let mut grid = Grid::new(1);
+        grid.margins.left = 1;
+        grid.margins.top = 2;
+        grid.clear_dirty();
+        grid.set_gutter(4);
+        assert!(grid.dirty.contents());
+        let margins = grid.fixed_margins();
+        assert_eq!(margins.left, 5);
+        assert_eq!(margins.top, 2);
+        grid.clear_dirty();
+        grid.set_gutter(4);
+        assert!(!grid.dirty.contents());
+    }
+
+    #[test]
+    fn horizontal_scrolls_accumulate() {
+        let mut ui = Ui::new();
+        ui.process(Event::GridResize(GridResize {
+            grid: 1,
+            width: 10,
+            height: 2,
+        }));
+        let scroll = |cols| {
+            Event::GridScroll(GridScroll {
+                grid: 1,
+                top: 0,
+                bot: 2,
+                left: 0,
+                right: 10,
+                rows: 1,
+                cols,
+            })
+        };
+        ui.process(scroll(2));
+        ui.process(scroll(3));
+        assert_eq!(ui.grid(1).unwrap().scroll_delta_cols, 5);
+    }
+}
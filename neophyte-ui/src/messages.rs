@@ -1,5 +1,5 @@
 use neophyte_ui_event::{
-    Content, MsgShow, msg_history_show::MsgHistoryEntry, msg_show::ReplaceLast,
+    Content, MsgShow, msg_history_show::MsgHistoryEntry, msg_show::Kind, msg_show::ReplaceLast,
 };
 
 type Message = MsgHistoryEntry;
@@ -7,25 +7,41 @@ type Message = MsgHistoryEntry;
 #[derive(Debug, Clone, Default)]
 pub struct Messages {
     pub dirty: bool,
-    pub show: Vec<Message>,
+    pub show: Vec<ShownMessage>,
     pub history: Vec<Message>,
     pub showmode: Content,
     pub showcmd: Content,
     pub ruler: Content,
+    next_id: u64,
+}
+
+/// A message from msg_show that is still visible
+#[derive(Debug, Clone)]
+pub struct ShownMessage {
+    /// Identifies the message across updates. A message that replaces the
+    /// last one takes its ID.
+    pub id: u64,
+    pub kind: Kind,
+    pub content: Content,
 }
 
 impl Messages {
     pub fn show(&mut self, event: MsgShow) {
-        match event.replace_last {
-            ReplaceLast::Replace => {
-                self.show.pop();
+        let replaced = match event.replace_last {
+            ReplaceLast::Replace => self.show.pop(),
+            ReplaceLast::Keep => None,
+        };
+        let id = match replaced {
+            Some(message) => message.id,
+            None => {
+                self.next_id += 1;
+                self.next_id
             }
-            ReplaceLast::Keep => {}
-        }
-        let message = Message {
+        };
+        self.show.push(ShownMessage {
+            id,
             kind: event.kind,
             content: event.content,
-        };
-        self.show.push(message);
+        });
     }
 }
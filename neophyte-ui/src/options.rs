@@ -2,128 +2,313 @@ impl From<String> for GuiFont {
     fn from(option: String) -> Self {
         let mut out = GuiFont::default();
         let mut state = ParseState::Normal;
+        let mut face = FontFace::default();
         let mut current = String::new();
         for c in option.chars() {
             match state {
                 ParseState::Normal => {
-                    if c.is_whitespace() && current.is_empty() {
+                    if c.is_whitespace() && face.name.is_empty() {
                         continue;
                     }
 
                     match c {
                         '\\' => state = ParseState::Escape,
-                        ',' => {
-                            out.fonts.push(current);
-                            current = String::default();
-                            state = ParseState::Normal;
-                        }
-                        '_' => current.push(' '),
-                        ':' => state = ParseState::OptionStart,
-                        _ => current.push(c),
+                        ',' => out.push_face(std::mem::take(&mut face)),
+                        '_' => face.name.push(' '),
+                        ':' => state = ParseState::Option,
+                        _ => face.name.push(c),
                     }
                 }
 
                 ParseState::Escape => {
-                    current.push(c);
+                    face.name.push(c);
                     state = ParseState::Normal;
                 }
 
-                ParseState::OptionStart => {
-                    state = match c {
-                        'w' | 'W' => ParseState::OptionSize(0, SizeKind::Width),
-                        'h' | 'H' => ParseState::OptionSize(0, SizeKind::Height),
-                        _ => ParseState::OptionUnknown,
-                    };
-                }
-
-                ParseState::OptionSize(size, kind) => match c {
-                    '0'..='9' => {
-                        let size = size * 10 + c as u32 - '0' as u32;
-                        state = ParseState::OptionSize(size, kind);
-                    }
+                ParseState::Option => match c {
                     ',' => {
-                        out.fonts.push(current);
-                        out.size = FontSize::new(size as f32, kind);
-                        current = String::default();
+                        out.apply_option(&mut face, &current);
+                        current.clear();
+                        out.push_face(std::mem::take(&mut face));
                         state = ParseState::Normal;
                     }
                     ':' => {
-                        out.size = FontSize::new(size as f32, kind);
-                        state = ParseState::OptionStart;
-                    }
-                    _ => {
-                        log::warn!("Bad font height option");
-                        break;
-                    }
-                },
-
-                ParseState::OptionUnknown => match c {
-                    ',' => {
-                        out.fonts.push(current);
-                        current = String::default();
-                        state = ParseState::Normal;
+                        out.apply_option(&mut face, &current);
+                        current.clear();
                     }
-                    ':' => state = ParseState::OptionStart,
-                    _ => {}
+                    _ => current.push(c),
                 },
             }
         }
 
-        if let ParseState::OptionSize(size, kind) = state {
-            out.size = FontSize::new(size as f32, kind);
+        if state == ParseState::Option {
+            out.apply_option(&mut face, &current);
         }
-
-        if !current.is_empty() {
-            out.fonts.push(current);
-        }
-
+        out.push_face(face);
         out
     }
 }
 
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct GuiFont {
-    pub fonts: Vec<String>,
+    pub fonts: Vec<FontFace>,
     pub size: FontSize,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum FontSize {
-    Width(f32),
-    Height(f32),
+impl GuiFont {
+    fn push_face(&mut self, face: FontFace) {
+        if !face.name.is_empty() {
+            self.fonts.push(face);
+        }
+    }
+
+    fn apply_option(&mut self, face: &mut FontFace, option: &str) {
+        match FontOption::parse(option) {
+            Some(FontOption::Size(size)) => self.size = size,
+            Some(FontOption::Bold) => face.bold = true,
+            Some(FontOption::Italic) => face.italic = true,
+            Some(FontOption::Edging(edging)) => face.edging = edging,
+            Some(FontOption::Hinting(hinting)) => face.hinting = hinting,
+            Some(FontOption::Feature(name, value)) => face.features.push((name, value)),
+            Some(FontOption::Ignored) => {}
+            None => log::warn!("Unknown guifont option: {option}"),
+        }
+    }
 }
 
-impl FontSize {
-    fn new(size: f32, kind: SizeKind) -> Self {
-        match kind {
-            SizeKind::Width => Self::Width(size),
-            SizeKind::Height => Self::Height(size),
+/// A font from the guifont option along with its per-font options
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FontFace {
+    /// The font family name
+    pub name: String,
+    /// Use the bold variant in place of the regular one, from :b
+    pub bold: bool,
+    /// Use the italic variant in place of the regular one, from :i
+    pub italic: bool,
+    /// Antialiasing mode, from :#e-
+    pub edging: Edging,
+    /// Hinting mode, from :#h-
+    pub hinting: Hinting,
+    /// OpenType features with their values, from :#f-
+    pub features: Vec<(String, u16)>,
+}
+
+impl FontFace {
+    pub fn with_name(name: String) -> Self {
+        Self {
+            name,
+            ..Default::default()
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FontSize {
+    Width(f32),
+    Height(f32),
+}
+
 impl Default for FontSize {
     fn default() -> Self {
         Self::Height(18.)
     }
 }
 
+/// How glyph edges are rasterized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Edging {
+    /// Grayscale antialiasing
+    #[default]
+    Antialias,
+    /// Subpixel antialiasing. The glyph atlas only stores coverage, so this is
+    /// currently rendered the same as grayscale antialiasing.
+    SubpixelAntialias,
+    /// No antialiasing
+    Alias,
+}
+
+/// Whether glyph outlines are fit to the pixel grid. Swash only hints
+/// outlines fully or not at all, so :#h-full, :#h-normal, and :#h-slight all
+/// enable hinting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Hinting {
+    #[default]
+    Enabled,
+    Disabled,
+}
+
+impl Hinting {
+    pub fn is_enabled(self) -> bool {
+        self == Self::Enabled
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum FontOption {
+    /// :h or :w followed by a possibly fractional size
+    Size(FontSize),
+    /// :b
+    Bold,
+    /// :i
+    Italic,
+    /// :#e-antialias, :#e-subpixelantialias, or :#e-alias
+    Edging(Edging),
+    /// :#h-full, :#h-normal, :#h-slight, or :#h-none
+    Hinting(Hinting),
+    /// :#f-name or :#f-name=value
+    Feature(String, u16),
+    /// Options that Vim accepts but which have no effect here, such as the
+    /// character set or underline
+    Ignored,
+}
+
+impl FontOption {
+    fn parse(option: &str) -> Option<Self> {
+        if let Some(hint) = option.strip_prefix('#') {
+            let (kind, value) = hint.split_once('-')?;
+            return match kind {
+                "e" => Some(Self::Edging(match value {
+                    "antialias" | "antialiased" => Edging::Antialias,
+                    "subpixelantialias" | "subpixelantialiased" => Edging::SubpixelAntialias,
+                    "alias" | "aliased" => Edging::Alias,
+                    _ => return None,
+                })),
+                "h" => Some(Self::Hinting(match value {
+                    "full" | "normal" | "slight" => Hinting::Enabled,
+                    "none" => Hinting::Disabled,
+                    _ => return None,
+                })),
+                "f" => {
+                    let (name, value) = match value.split_once('=') {
+                        Some((name, value)) => (name, value.parse().ok()?),
+                        None => (value, 1),
+                    };
+                    (name.len() == 4).then(|| Self::Feature(name.to_string(), value))
+                }
+                _ => None,
+            };
+        }
+
+        let mut chars = option.chars();
+        let first = chars.next()?;
+        let rest = chars.as_str();
+        match first {
+            'h' | 'H' => Some(Self::Size(FontSize::Height(parse_size(rest)?))),
+            'w' | 'W' => Some(Self::Size(FontSize::Width(parse_size(rest)?))),
+            'b' | 'B' if rest.is_empty() => Some(Self::Bold),
+            'i' | 'I' if rest.is_empty() => Some(Self::Italic),
+            'u' | 'U' | 's' | 'S' if rest.is_empty() => Some(Self::Ignored),
+            'c' | 'C' | 'q' | 'Q' => Some(Self::Ignored),
+            _ => None,
+        }
+    }
+}
+
+fn parse_size(s: &str) -> Option<f32> {
+    s.parse()
+        .ok()
+        .filter(|size: &f32| size.is_finite() && *size > 0.)
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ParseState {
-    /// Appending chars as normal
+    /// Appending chars to the font name
     Normal,
     /// Found a backslash escape sequence
     Escape,
-    /// Found a :
-    OptionStart,
-    /// Found a :h or :w
-    OptionSize(u32, SizeKind),
-    /// An unknown option is being specified
-    OptionUnknown,
+    /// Found a : and appending chars to the option
+    Option,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum SizeKind {
-    Width,
-    Height,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> GuiFont {
+        s.to_string().into()
+    }
+
+    fn names(font: &GuiFont) -> Vec<&str> {
+        font.fonts.iter().map(|face| face.name.as_str()).collect()
+    }
+
+    #[test]
+    fn font_names() {
+        let font = parse("Cascadia Code, Symbols_Nerd_Font,Noto\\,Emoji");
+        assert_eq!(
+            names(&font),
+            ["Cascadia Code", "Symbols Nerd Font", "Noto,Emoji"]
+        );
+        assert_eq!(font.size, FontSize::default());
+    }
+
+    #[test]
+    fn empty_names_are_skipped() {
+        assert!(parse("").fonts.is_empty());
+        assert_eq!(names(&parse(",Hack,")), ["Hack"]);
+    }
+
+    #[test]
+    fn integer_size() {
+        let font = parse("Hack:h12");
+        assert_eq!(names(&font), ["Hack"]);
+        assert_eq!(font.size, FontSize::Height(12.));
+        assert_eq!(parse("Hack:W9").size, FontSize::Width(9.));
+    }
+
+    #[test]
+    fn fractional_size() {
+        assert_eq!(parse("Hack:h12.5").size, FontSize::Height(12.5));
+        assert_eq!(parse("Hack:w7.25,Other").size, FontSize::Width(7.25));
+    }
+
+    #[test]
+    fn bad_size_is_ignored() {
+        let font = parse("Hack:h1x:b");
+        assert_eq!(font.size, FontSize::default());
+        assert!(font.fonts[0].bold);
+        assert_eq!(parse("Hack:h").size, FontSize::default());
+        assert_eq!(parse("Hack:h-3").size, FontSize::default());
+    }
+
+    #[test]
+    fn style_flags_apply_to_their_font() {
+        let font = parse("Hack:b,Fira Code:i:h14,Iosevka:b:i");
+        assert_eq!(names(&font), ["Hack", "Fira Code", "Iosevka"]);
+        assert!(font.fonts[0].bold && !font.fonts[0].italic);
+        assert!(!font.fonts[1].bold && font.fonts[1].italic);
+        assert!(font.fonts[2].bold && font.fonts[2].italic);
+        assert_eq!(font.size, FontSize::Height(14.));
+    }
+
+    #[test]
+    fn rendering_hints() {
+        let font = parse("Hack:#e-alias:#h-none,Other:#e-antialiased:#h-slight");
+        assert_eq!(font.fonts[0].edging, Edging::Alias);
+        assert_eq!(font.fonts[0].hinting, Hinting::Disabled);
+        assert_eq!(font.fonts[1].edging, Edging::Antialias);
+        assert_eq!(font.fonts[1].hinting, Hinting::Enabled);
+        assert_eq!(
+            parse("Hack:#e-subpixelantialias").fonts[0].edging,
+            Edging::SubpixelAntialias
+        );
+    }
+
+    #[test]
+    fn features() {
+        let font = parse("Hack:#f-ss01:#f-liga=0,Other");
+        assert_eq!(
+            font.fonts[0].features,
+            [("ss01".to_string(), 1), ("liga".to_string(), 0)]
+        );
+        assert!(font.fonts[1].features.is_empty());
+    }
+
+    #[test]
+    fn unknown_options_are_ignored() {
+        let font = parse("Hack:x:#z-what:#f-toolong:cANSI:qDRAFT:u:s,Other:h10");
+        assert_eq!(names(&font), ["Hack", "Other"]);
+        assert_eq!(font.fonts[0], FontFace::with_name("Hack".to_string()));
+        assert_eq!(font.size, FontSize::Height(10.));
+    }
 }
@@ -0,0 +1,156 @@
+use crate::grid::CellContents;
+use neophyte_ui_event::option_set::Ambiwidth;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthChar;
+
+/// Computes the number of cells text occupies the same way Neovim does, as
+/// affected by the ambiwidth and emoji options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CharWidth {
+    /// Whether East Asian ambiguous-width characters are double-width, from
+    /// the ambiwidth option
+    pub ambiwidth_double: bool,
+    /// Whether emoji are double-width, from the emoji option
+    pub emoji: bool,
+}
+
+impl Default for CharWidth {
+    fn default() -> Self {
+        Self {
+            ambiwidth_double: false,
+            emoji: true,
+        }
+    }
+}
+
+impl CharWidth {
+    pub fn set_ambiwidth(&mut self, ambiwidth: Ambiwidth) {
+        self.ambiwidth_double = matches!(ambiwidth, Ambiwidth::Double);
+    }
+
+    /// The number of cells for a character
+    pub fn char_width(self, c: char) -> usize {
+        let width = c.width().unwrap_or(1);
+        if width == 2 {
+            if !self.emoji && is_emoji_wide(c) {
+                1
+            } else {
+                2
+            }
+        } else if self.ambiwidth_double && c as u32 >= 0x80 && c.width_cjk() == Some(2) {
+            2
+        } else {
+            width.max(1)
+        }
+    }
+
+    /// The number of cells for a grapheme cluster. Like Neovim, this goes by
+    /// the first character, except that an emoji variation selector makes a
+    /// pictograph double-width.
+    pub fn grapheme_width(self, grapheme: &str) -> usize {
+        let mut chars = grapheme.chars();
+        let Some(first) = chars.next() else {
+            return 0;
+        };
+        if self.emoji && chars.any(|c| c == '\u{FE0F}') && is_pictograph(first) {
+            2
+        } else {
+            self.char_width(first)
+        }
+    }
+
+    /// The number of cells for the text
+    pub fn text_width(self, text: &str) -> usize {
+        text.graphemes(true)
+            .map(|grapheme| self.grapheme_width(grapheme))
+            .sum()
+    }
+
+    /// Lay out the text in cells with one grapheme cluster per cell, followed
+    /// by an empty continuation cell for double-width clusters
+    pub fn cells(
+        self,
+        text: &str,
+        highlight: u32,
+    ) -> impl Iterator<Item = CellContents<'_>> + Clone {
+        text.graphemes(true).flat_map(move |grapheme| {
+            let continuation = (self.grapheme_width(grapheme) > 1).then(|| CellContents {
+                highlight,
+                text: "".chars().into(),
+            });
+            std::iter::once(CellContents {
+                highlight,
+                text: grapheme.chars().into(),
+            })
+            .chain(continuation)
+        })
+    }
+}
+
+/// Whether the character is only double-width because it is an emoji, which
+/// Neovim makes single-width when the emoji option is off
+fn is_emoji_wide(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x2300..=0x23FF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0x1F000..=0x1FAFF
+    )
+}
+
+/// Whether the character is a symbol that may have emoji presentation
+fn is_pictograph(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x00A9 | 0x00AE | 0x203C..=0x3299 | 0x1F000..=0x1FAFF
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULT: CharWidth = CharWidth {
+        ambiwidth_double: false,
+        emoji: true,
+    };
+
+    #[test]
+    fn ascii_and_wide() {
+        assert_eq!(DEFAULT.text_width("abc"), 3);
+        assert_eq!(DEFAULT.text_width("漢字"), 4);
+        assert_eq!(DEFAULT.text_width("e\u{301}"), 1);
+    }
+
+    #[test]
+    fn ambiwidth() {
+        let double = CharWidth {
+            ambiwidth_double: true,
+            ..DEFAULT
+        };
+        assert_eq!(DEFAULT.text_width("±→"), 2);
+        assert_eq!(double.text_width("±→"), 4);
+        assert_eq!(double.text_width("a"), 1);
+    }
+
+    #[test]
+    fn emoji() {
+        let no_emoji = CharWidth {
+            emoji: false,
+            ..DEFAULT
+        };
+        assert_eq!(DEFAULT.text_width("😀"), 2);
+        assert_eq!(no_emoji.text_width("😀"), 1);
+        assert_eq!(DEFAULT.text_width("❤\u{FE0F}"), 2);
+        assert_eq!(no_emoji.text_width("❤\u{FE0F}"), 1);
+        assert_eq!(DEFAULT.text_width("👨\u{200D}👩\u{200D}👧"), 2);
+        assert_eq!(no_emoji.text_width("漢"), 2);
+    }
+
+    #[test]
+    fn cells_have_continuations() {
+        let cells: Vec<String> = DEFAULT
+            .cells("a漢👍🏽", 0)
+            .map(|cell| cell.text.collect())
+            .collect();
+        assert_eq!(cells, ["a", "漢", "", "👍🏽", ""]);
+    }
+}
@@ -1,15 +1,28 @@
 mod buttons;
 pub mod settings;
 
-use self::{buttons::Buttons, settings::Settings};
+use self::{
+    buttons::Buttons,
+    settings::{MAX_CURSOR_BLINK_FADE, MAX_WINDOW_MOVE_DURATION, Settings},
+};
 use crate::{
     UserEvent,
     neovim::{Neovim, action::Action, button::Button},
-    rendering::{Motion, state::RenderState},
+    rendering::{
+        Motion,
+        images::{Anchor, ImageShow},
+        overlays::OverlaySet,
+        state::{HoveredLink, RenderState},
+    },
     rpc::{self, Notification},
-    text::{font::Metrics, fonts::FontSetting},
+    text::{
+        font::Metrics,
+        fonts::{CellSpacing, FontSetting, parse_highlight_fonts},
+        ligatures::HighlightFeatures,
+    },
     ui::{
-        Ui,
+        self, Ui,
+        grid::ExtmarkId,
         options::{FontSize, GuiFont},
     },
     util::IntoSrgb,
@@ -22,6 +35,7 @@ use serde::Serialize;
 use std::{
     fs::File,
     io::Write,
+    process::Command,
     sync::{Arc, mpsc},
     time::{Duration, Instant},
 };
@@ -34,7 +48,7 @@ use winit::{
     },
     event_loop::{ActiveEventLoop, ControlFlow},
     keyboard::{Key, ModifiersState, NamedKey},
-    window::{Fullscreen, Window, WindowId},
+    window::{CursorIcon, Fullscreen, Window, WindowId},
 };
 
 pub struct EventHandler {
@@ -92,7 +106,13 @@ impl ApplicationHandler<UserEvent> for EventHandler {
                 log::debug!("Winit requested redraw");
                 self.redraw(event_loop);
             }
-            WindowEvent::Focused(focus) => self.neovim.ui_set_focus(focus),
+            WindowEvent::Focused(focus) => {
+                self.neovim.ui_set_focus(focus);
+                if let Some(render_state) = self.render_state.as_mut() {
+                    render_state.set_focused(focus);
+                }
+                self.window().request_redraw();
+            }
             _ => {}
         }
     }
@@ -185,7 +205,11 @@ impl EventHandler {
     fn notification(&mut self, notification: Notification, event_loop: &ActiveEventLoop) {
         let inner = || {
             let Notification { method, params } = notification;
-            if method.as_str() != "redraw" {
+            // Skip logging image data
+            if !matches!(
+                method.as_str(),
+                "redraw" | "neophyte.image_show" | "neophyte.overlay_set"
+            ) {
                 log::info!("Got notification {method} with {params:?}");
             }
             match method.as_str() {
@@ -221,6 +245,139 @@ impl EventHandler {
                     self.window().request_redraw();
                 }
 
+                "neophyte.set_cursor_blink_fade" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    let fade: f32 = args.next()?;
+                    if !fade.is_finite() {
+                        return None;
+                    }
+                    self.settings.cursor_blink_fade = fade.clamp(0., MAX_CURSOR_BLINK_FADE);
+                    self.window().request_redraw();
+                }
+
+                "neophyte.set_cursor_curve" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    self.settings.cursor_curve = args.next()?;
+                    self.window().request_redraw();
+                }
+
+                "neophyte.set_scroll_curve" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    self.settings.scroll_curve = args.next()?;
+                    self.window().request_redraw();
+                }
+
+                "neophyte.set_cursor_effect" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    self.settings.cursor_effect = args.next()?;
+                    self.window().request_redraw();
+                }
+
+                "neophyte.set_float_transition" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    self.settings.float_transition = args.next()?;
+                }
+
+                "neophyte.set_float_decoration" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    self.settings.float_decoration = args.next()?;
+                    self.window().request_redraw();
+                }
+
+                "neophyte.set_dim_inactive" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    self.settings.dim_inactive = args.next()?;
+                    self.window().request_redraw();
+                }
+
+                "neophyte.set_dim_inactive_enabled" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    self.settings.dim_inactive.enabled = args.next()?;
+                    self.window().request_redraw();
+                }
+
+                "neophyte.set_scrollbar" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    self.settings.scrollbar = args.next()?;
+                    self.window().request_redraw();
+                }
+
+                "neophyte.set_toasts" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    self.settings.toasts = args.next()?;
+                    self.relayout();
+                }
+
+                "neophyte.image_show" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    let image: ImageShow = args.next()?;
+                    let grid = match image.anchor {
+                        Anchor::Grid(grid) => grid,
+                        Anchor::Window(window) => self.ui.window_grid(window)?,
+                    };
+                    self.render_state.as_mut().unwrap().show_image(grid, image);
+                    self.window().request_redraw();
+                }
+
+                "neophyte.image_hide" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    let id: u64 = args.next()?;
+                    if self.render_state.as_mut().unwrap().hide_image(id) {
+                        self.window().request_redraw();
+                    }
+                }
+
+                "neophyte.overlay_set" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    let overlay: OverlaySet = args.next()?;
+                    self.render_state.as_mut().unwrap().set_overlay(overlay);
+                    self.window().request_redraw();
+                }
+
+                "neophyte.overlay_remove" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    let buffer: i64 = args.next()?;
+                    let extmark = ExtmarkId {
+                        ns_id: args.next()?,
+                        mark_id: args.next()?,
+                    };
+                    if self
+                        .render_state
+                        .as_mut()
+                        .unwrap()
+                        .remove_overlay(buffer, extmark)
+                    {
+                        self.window().request_redraw();
+                    }
+                }
+
+                "neophyte.overlay_clear" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    let buffer: i64 = args.next()?;
+                    if self.render_state.as_mut().unwrap().clear_overlays(buffer) {
+                        self.window().request_redraw();
+                    }
+                }
+
+                "neophyte.set_link_opener" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    let opener: Vec<String> = args.next()?;
+                    if opener.is_empty() {
+                        return None;
+                    }
+                    self.settings.link_opener = opener;
+                }
+
+                "neophyte.set_window_move_duration" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    let duration: f32 = args.next()?;
+                    if !duration.is_finite() {
+                        return None;
+                    }
+                    self.settings.window_move_duration =
+                        duration.clamp(0., MAX_WINDOW_MOVE_DURATION);
+                }
+
                 "neophyte.set_fonts" => {
                     let args = Values::new(params.into_iter().next()?)?;
                     let font_settings = args.map()?;
@@ -229,6 +386,68 @@ impl EventHandler {
                     self.finish_font_change();
                 }
 
+                "neophyte.set_highlight_fonts" => {
+                    let fonts = parse_highlight_fonts(params.into_iter().next()?)?;
+                    let render_state = self.render_state.as_mut()?;
+                    render_state
+                        .fonts
+                        .set_highlight_fonts(fonts, &self.ui.highlight_names);
+                    render_state.clear_glyph_cache();
+                    self.relayout();
+                }
+
+                "neophyte.set_letter_spacing" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    let letter_spacing: f32 = args.next()?;
+                    self.render_state.as_mut()?.fonts.spacing.letter_spacing = letter_spacing;
+                    self.finish_spacing_change();
+                }
+
+                "neophyte.set_line_height" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    let line_height: f32 = args.next()?;
+                    self.render_state.as_mut()?.fonts.spacing.line_height = line_height;
+                    self.finish_spacing_change();
+                }
+
+                "neophyte.set_symbol_ranges" => {
+                    let args = Values::new(params.into_iter().next()?)?;
+                    let ranges = args.map()?;
+                    self.render_state.as_mut()?.fonts.symbol_ranges = ranges;
+                    self.render_state.as_mut()?.clear_glyph_cache();
+                    self.relayout();
+                }
+
+                "neophyte.set_box_drawing" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    let enabled: bool = args.next()?;
+                    self.render_state.as_mut()?.set_box_drawing(enabled);
+                    self.relayout();
+                }
+
+                "neophyte.set_break_ligatures_at_cursor" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    let enabled: bool = args.next()?;
+                    self.render_state.as_mut()?.ligatures.break_at_cursor = enabled;
+                    self.relayout();
+                }
+
+                "neophyte.set_break_ligatures_modes" => {
+                    let args = Values::new(params.into_iter().next()?)?;
+                    let modes = args.map()?;
+                    self.render_state.as_mut()?.ligatures.break_at_cursor_modes = modes;
+                    self.relayout();
+                }
+
+                "neophyte.set_highlight_features" => {
+                    let features = HighlightFeatures::parse_map(params.into_iter().next()?)?;
+                    self.render_state
+                        .as_mut()?
+                        .ligatures
+                        .set_highlight_features(features, &self.ui.highlight_names);
+                    self.relayout();
+                }
+
                 "neophyte.set_underline_offset" => {
                     let mut args = Values::new(params.into_iter().next()?)?;
                     let offset: f32 = args.next()?;
@@ -278,6 +497,24 @@ impl EventHandler {
 
                 "neophyte.leave" => event_loop.exit(),
                 "neophyte.buf_leave" => self.ui.ignore_next_scroll = true,
+                "neophyte.scroll_horizontal" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    let window: i64 = args.next()?;
+                    let cols: i32 = args.next()?;
+                    self.ui.scroll_horizontal(window, cols);
+                }
+                "neophyte.set_gutter" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    let window: i64 = args.next()?;
+                    let cols: u32 = args.next()?;
+                    self.ui.set_gutter(window, cols);
+                }
+                "neophyte.set_buffer" => {
+                    let mut args = Values::new(params.into_iter().next()?)?;
+                    let window: i64 = args.next()?;
+                    let buffer: i64 = args.next()?;
+                    self.ui.set_buffer(window, buffer);
+                }
                 "neophyte.enable_raw_input" => self.settings.raw_input = true,
                 "neophyte.disable_raw_input" => self.settings.raw_input = false,
                 "neophyte.enable_frame_events" => {
@@ -315,25 +552,47 @@ impl EventHandler {
         }
 
         if self.ui.did_flush {
-            if let Some(guifont_update) = self.ui.guifont_update.take() {
-                let GuiFont { fonts, size } = guifont_update;
-                self.set_fonts(
-                    fonts.into_iter().map(FontSetting::with_name).collect(),
-                    size,
-                );
+            let guifont_update = self.ui.guifont_update.take();
+            let guifontwide_update = self.ui.guifontwide_update.take();
+            let linespace_update = self.ui.linespace_update.take();
+            let did_fonts_change = guifont_update.is_some()
+                || guifontwide_update.is_some()
+                || linespace_update.is_some();
+            if let Some(GuiFont { fonts, size }) = guifont_update {
+                self.set_fonts(fonts.into_iter().map(FontSetting::from).collect(), size);
+            }
+
+            if let Some(GuiFont { fonts, size: _ }) = guifontwide_update {
+                self.set_wide_fonts(fonts.into_iter().map(FontSetting::from).collect());
+            }
+
+            if let Some(linespace) = linespace_update {
+                self.render_state.as_mut().unwrap().fonts.spacing.linespace = linespace;
+            }
+
+            if did_fonts_change {
                 self.finish_font_change();
+                self.relayout_grids();
             }
 
-            let bg_override = if self.settings.transparent {
-                self.settings.bg_override
-            } else {
-                None
-            };
+            if let Some(arabicshape) = self.ui.arabicshape_update.take() {
+                let fonts = &mut self.render_state.as_mut().unwrap().fonts;
+                if fonts.arabicshape != arabicshape {
+                    fonts.arabicshape = arabicshape;
+                    self.relayout_grids();
+                }
+            }
 
-            self.render_state
-                .as_mut()
-                .unwrap()
-                .update(&self.ui, bg_override);
+            let bg_override = self.bg_override();
+            self.render_state.as_mut().unwrap().update(
+                &self.ui,
+                bg_override,
+                self.settings.float_transition,
+                self.settings.window_move_duration,
+                self.settings.toasts,
+            );
+            // The text under the mouse may have changed
+            self.hover_link();
             self.ui.clear_dirty();
             self.window().request_redraw();
         }
@@ -378,6 +637,12 @@ impl EventHandler {
                     .send_response(rpc::Response::result(msgid, scroll_speed.into()));
             }
 
+            "neophyte.get_dim_inactive_enabled" => {
+                let enabled = self.settings.dim_inactive.enabled;
+                self.neovim
+                    .send_response(rpc::Response::result(msgid, enabled.into()));
+            }
+
             "neophyte.get_font_width" => {
                 let width = self.metrics().width / self.window().scale_factor() as f32;
                 self.neovim
@@ -390,6 +655,35 @@ impl EventHandler {
                     .send_response(rpc::Response::result(msgid, width.into()));
             }
 
+            "neophyte.get_letter_spacing" => {
+                let letter_spacing = self.spacing().letter_spacing;
+                self.neovim
+                    .send_response(rpc::Response::result(msgid, letter_spacing.into()));
+            }
+
+            "neophyte.get_line_height" => {
+                let line_height = self.spacing().line_height;
+                self.neovim
+                    .send_response(rpc::Response::result(msgid, line_height.into()));
+            }
+
+            "neophyte.get_box_drawing" => {
+                let enabled = self.render_state.as_ref().unwrap().box_drawing();
+                self.neovim
+                    .send_response(rpc::Response::result(msgid, enabled.into()));
+            }
+
+            "neophyte.get_break_ligatures_at_cursor" => {
+                let enabled = self
+                    .render_state
+                    .as_ref()
+                    .unwrap()
+                    .ligatures
+                    .break_at_cursor;
+                self.neovim
+                    .send_response(rpc::Response::result(msgid, enabled.into()));
+            }
+
             "neophyte.get_underline_offset" => {
                 let offset = self.settings.underline_offset;
                 self.neovim
@@ -424,6 +718,10 @@ impl EventHandler {
         }
 
         log::info!("Got keyboard input: {event:?}");
+        if let Some(render_state) = self.render_state.as_mut() {
+            render_state.reset_cursor_blink();
+            self.window().request_redraw();
+        }
         match &event.logical_key {
             Key::Named(key) => {
                 let c = || {
@@ -514,6 +812,11 @@ impl EventHandler {
             return;
         };
         self.mouse.position = position;
+        if self.mouse.scrollbar_drag.is_some() {
+            self.drag_scrollbar();
+            return;
+        }
+        self.hover_link();
         if let Some(grid) = self.ui.grid_under_cursor(position, self.cell_size().cast()) {
             self.neovim.input_mouse(
                 self.mouse.buttons.first().unwrap_or(Button::Move),
@@ -546,6 +849,18 @@ impl EventHandler {
             _ => unreachable!(),
         }
 
+        if button == Button::Left {
+            if depressed && self.press_scrollbar() {
+                return;
+            }
+            if depressed && self.modifiers.control_key() && self.open_hovered_link() {
+                return;
+            }
+            if !depressed && self.mouse.scrollbar_drag.take().is_some() {
+                return;
+            }
+        }
+
         if !depressed {
             return;
         }
@@ -565,6 +880,130 @@ impl EventHandler {
         }
     }
 
+    /// Start dragging the scrollbar under the mouse. Returns whether there was
+    /// a scrollbar under the mouse.
+    fn press_scrollbar(&mut self) -> bool {
+        let cell_size = self.cell_size();
+        let Some(grid) = self.ui.grid_under_cursor(self.mouse.position, cell_size) else {
+            return false;
+        };
+        let Some((viewport, geometry)) =
+            self.render_state
+                .as_ref()
+                .unwrap()
+                .scrollbar(grid.grid, cell_size, &self.settings)
+        else {
+            return false;
+        };
+        let point = self.mouse.position.cast_as::<f32>();
+        if !geometry.track_contains(point) {
+            return false;
+        }
+
+        // Clicking the track away from the thumb centers the thumb on the
+        // mouse
+        let below_thumb = geometry.below_thumb(point);
+        let grab = if (0. ..geometry.thumb_height).contains(&below_thumb) {
+            below_thumb
+        } else {
+            geometry.thumb_height / 2.
+        };
+        self.mouse.scrollbar_drag = Some(ScrollbarDrag {
+            grid: grid.grid,
+            grab,
+            topline: viewport.topline,
+        });
+        self.drag_scrollbar();
+        true
+    }
+
+    /// Scroll the window whose scrollbar is being dragged to follow the mouse
+    fn drag_scrollbar(&mut self) {
+        let cell_size = self.cell_size();
+        let Some(drag) = self.mouse.scrollbar_drag.as_mut() else {
+            return;
+        };
+        let render_state = self.render_state.as_mut().unwrap();
+        render_state.show_scrollbar(drag.grid);
+        let Some((viewport, geometry)) =
+            render_state.scrollbar(drag.grid, cell_size, &self.settings)
+        else {
+            return;
+        };
+        let thumb_offset = self.mouse.position.0.y as f32 - geometry.track_offset.0.y - drag.grab;
+        let topline = geometry.topline_at(thumb_offset);
+        if topline != drag.topline {
+            drag.topline = topline;
+            self.neovim.set_topline(viewport.window, topline);
+        }
+        self.window().request_redraw();
+    }
+
+    /// Underline the hyperlink under the mouse and show a hand cursor over it
+    fn hover_link(&mut self) {
+        let link = self
+            .ui
+            .grid_under_cursor(self.mouse.position, self.cell_size())
+            .and_then(|cell| self.ui.link_at(cell))
+            .map(|link| {
+                let fg = self
+                    .ui
+                    .highlights
+                    .get(link.highlight as usize)
+                    .and_then(|highlight| highlight.as_ref()?.foreground)
+                    .or(self.ui.default_colors.rgb_fg)
+                    .unwrap_or_default();
+                HoveredLink {
+                    grid: link.grid,
+                    row: link.row,
+                    cols: link.cols,
+                    color: fg.into_srgb(1.),
+                }
+            });
+        let render_state = self.render_state.as_mut().unwrap();
+        if render_state.hovered_link() == link.as_ref() {
+            return;
+        }
+        let icon = if link.is_some() {
+            CursorIcon::Pointer
+        } else {
+            CursorIcon::Default
+        };
+        render_state.set_hovered_link(link);
+        let window = self.window();
+        window.set_cursor(icon);
+        window.request_redraw();
+    }
+
+    /// Open the hyperlink under the mouse with the link opener. Returns
+    /// whether there was a link under the mouse.
+    fn open_hovered_link(&self) -> bool {
+        let Some(url) = self
+            .ui
+            .grid_under_cursor(self.mouse.position, self.cell_size())
+            .and_then(|cell| self.ui.link_at(cell))
+            .map(|link| link.url.to_string())
+        else {
+            return false;
+        };
+        let Some((program, args)) = self.settings.link_opener.split_first() else {
+            return false;
+        };
+        log::info!("Opening link {url}");
+        match Command::new(program).args(args).arg(&url).spawn() {
+            // Reap the opener without blocking the event loop
+            Ok(mut child) => {
+                std::thread::spawn(move || {
+                    if let Err(e) = child.wait() {
+                        log::error!("{e}");
+                    }
+                });
+            }
+            Err(e) => log::error!("Failed to open {url} with {program}: {e}"),
+        }
+        true
+    }
+
     fn mouse_wheel(&mut self, delta: MouseScrollDelta, phase: TouchPhase) {
         log::info!("Got mouse wheel: {delta:?}, {phase:?}");
         let reset = matches!(
@@ -672,6 +1111,9 @@ impl EventHandler {
 
         let cell_size = self.cell_size();
         let render_state = self.render_state.as_mut().unwrap();
+        // Measured before rendering so that delays aren't pushed back by the
+        // time spent drawing
+        let frame_start = Instant::now();
         let motion = render_state.advance(elapsed, cell_size.cast_as(), &self.settings);
         render_state.render(
             cell_size,
@@ -690,15 +1132,19 @@ impl EventHandler {
 
         log::debug!("Redraw: {elapsed:?}, {motion:?}");
         match motion {
-            Motion::Still => self.last_render_time = None,
+            Motion::Still => {
+                event_loop.set_control_flow(ControlFlow::Wait);
+                self.last_render_time = None;
+            }
             Motion::Animating => {
+                event_loop.set_control_flow(ControlFlow::Wait);
                 self.window().request_redraw();
                 if self.last_render_time.is_none() {
                     self.last_render_time = Some(Instant::now());
                 }
             }
             Motion::Delay(duration) => {
-                event_loop.set_control_flow(ControlFlow::WaitUntil(Instant::now() + duration));
+                event_loop.set_control_flow(ControlFlow::WaitUntil(frame_start + duration));
                 self.last_render_time = None;
             }
         }
@@ -752,10 +1198,50 @@ impl EventHandler {
             .set_fonts(fonts, size)
     }
 
+    fn set_wide_fonts(&mut self, fonts: Vec<FontSetting>) {
+        self.render_state
+            .as_mut()
+            .unwrap()
+            .fonts
+            .set_wide_fonts(fonts)
+    }
+
     fn cell_size(&self) -> Vec2<u32> {
         self.render_state.as_ref().unwrap().fonts.cell_size()
     }
 
+    fn spacing(&self) -> CellSpacing {
+        self.render_state.as_ref().unwrap().fonts.spacing
+    }
+
+    /// Lay out the grids again with the new cell size. Neovim only redraws if
+    /// the grid dimensions change, so the existing contents are updated here.
+    fn finish_spacing_change(&mut self) {
+        self.resize();
+        self.relayout();
+    }
+
+    /// Update the rendered grid contents without waiting for Neovim to redraw
+    fn relayout(&mut self) {
+        self.relayout_grids();
+        let bg_override = self.bg_override();
+        self.render_state.as_mut().unwrap().update(
+            &self.ui,
+            bg_override,
+            self.settings.float_transition,
+            self.settings.window_move_duration,
+            self.settings.toasts,
+        );
+        self.window().request_redraw();
+    }
+
+    /// Mark all grids to be laid out again on the next render state update
+    fn relayout_grids(&mut self) {
+        for grid in self.ui.grids.iter_mut() {
+            grid.dirty.set_contents();
+        }
+    }
+
     fn finish_font_change(&mut self) {
         self.render_state.as_mut().unwrap().clear_glyph_cache();
         self.resize();
@@ -777,6 +1263,14 @@ impl EventHandler {
         self.neovim.ui_try_resize_grid(1, size.0.x, size.0.y);
     }
 
+    fn bg_override(&self) -> Option<[f32; 4]> {
+        if self.settings.transparent {
+            self.settings.bg_override
+        } else {
+            None
+        }
+    }
+
     fn render_size(&mut self) -> PixelVec<u32> {
         if let Some(size) = self.settings.render_size {
             size
@@ -791,6 +1285,17 @@ struct Mouse {
     position: PixelVec<u32>,
     scroll: Vec2<i32>,
     buttons: Buttons,
+    scrollbar_drag: Option<ScrollbarDrag>,
+}
+
+/// A scrollbar being dragged with the mouse
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ScrollbarDrag {
+    grid: ui::grid::Id,
+    /// The distance of the mouse below the top of the thumb, in pixels
+    grab: f32,
+    /// The last zero-based topline sent to Neovim
+    topline: u32,
 }
 
 impl Mouse {
@@ -1,3 +1,10 @@
+use crate::rendering::{
+    animation::Curve,
+    pipelines::{cursor_effects::CursorEffect, float_decoration::FloatDecoration},
+    scrollbar::ScrollbarSettings,
+    toasts::ToastSettings,
+    transition::{DimInactive, FloatTransition},
+};
 use neophyte_linalg::PixelVec;
 use std::path::PathBuf;
 
@@ -7,6 +14,29 @@ pub struct Settings {
     pub cursor_speed: f32,
     /// Multiplier of the default scroll speed
     pub scroll_speed: f32,
+    /// Seconds over which the cursor fades in and out while blinking, or zero
+    /// to toggle
+    pub cursor_blink_fade: f32,
+    /// How the cursor moves to a new position
+    pub cursor_curve: Curve,
+    /// How grids scroll to a new position
+    pub scroll_curve: Curve,
+    /// Drawn over the text and beneath the cursor while it moves
+    pub cursor_effect: CursorEffect,
+    /// How floating windows open, close, and move
+    pub float_transition: FloatTransition,
+    /// Shadows, rounded corners, and outlines for floating windows
+    pub float_decoration: FloatDecoration,
+    /// How windows other than the one with the cursor are dimmed
+    pub dim_inactive: DimInactive,
+    /// Overlay scrollbars for normal windows
+    pub scrollbar: ScrollbarSettings,
+    /// Messages drawn as cards in a corner of the window
+    pub toasts: ToastSettings,
+    /// Seconds for split windows to move to a new position, or zero to jump
+    pub window_move_duration: f32,
+    /// The command and arguments that hyperlinks are passed to when opened
+    pub link_opener: Vec<String>,
     /// Additional offset to apply to underlines
     pub underline_offset: i32,
     /// The size of the render surface, or None to use the default
@@ -20,6 +50,17 @@ pub struct Settings {
     pub send_frame_events: bool,
 }
 
+/// The longest that the cursor can take to fade while blinking, in seconds
+pub const MAX_CURSOR_BLINK_FADE: f32 = 10.;
+
+/// The longest that split windows can take to move, in seconds
+pub const MAX_WINDOW_MOVE_DURATION: f32 = 10.;
+
+#[cfg(target_os = "macos")]
+const DEFAULT_LINK_OPENER: &str = "open";
+#[cfg(not(target_os = "macos"))]
+const DEFAULT_LINK_OPENER: &str = "xdg-open";
+
 impl Settings {
     pub fn new(transparent: bool) -> Self {
         Self {
@@ -34,6 +75,17 @@ impl Default for Settings {
         Self {
             cursor_speed: 1.,
             scroll_speed: 1.,
+            cursor_blink_fade: 0.,
+            cursor_curve: Curve::default(),
+            scroll_curve: Curve::default(),
+            cursor_effect: CursorEffect::default(),
+            float_transition: FloatTransition::default(),
+            float_decoration: FloatDecoration::default(),
+            dim_inactive: DimInactive::default(),
+            scrollbar: ScrollbarSettings::default(),
+            toasts: ToastSettings::default(),
+            window_move_duration: 0.,
+            link_opener: vec![DEFAULT_LINK_OPENER.to_string()],
             underline_offset: 0,
             render_size: None,
             render_target: None,
@@ -13,7 +13,7 @@ use crate::rpc::{self, Request};
 use rmpv::Value;
 use std::{
     ffi::OsStr,
-    io::{self, ErrorKind},
+    io,
     process::{Child, Command, Stdio},
     sync::{Arc, RwLock, mpsc},
 };
@@ -40,11 +40,11 @@ impl Neovim {
         let stdout = child
             .stdout
             .take()
-            .ok_or_else(|| Error::new(ErrorKind::Other, "Can't open stdout"))?;
+            .ok_or_else(|| Error::other("Can't open stdout"))?;
         let stdin = child
             .stdin
             .take()
-            .ok_or_else(|| Error::new(ErrorKind::Other, "Can't open stdin"))?;
+            .ok_or_else(|| Error::other("Can't open stdin"))?;
 
         let (tx, rx) = mpsc::channel();
         let incoming = Arc::new(RwLock::new(Incoming::new()));
@@ -89,7 +89,8 @@ impl Neovim {
 
     // TODO: Proper public API
     pub fn ui_attach(&mut self, cmdline: bool, messages: bool) {
-        let extensions: Vec<_> = std::iter::once("ext_multigrid")
+        let extensions: Vec<_> = ["ext_multigrid", "ext_hlstate"]
+            .into_iter()
             .chain(cmdline.then_some("ext_cmdline"))
             .chain(messages.then_some("ext_messages"))
             .collect();
@@ -141,6 +142,18 @@ impl Neovim {
         self.call("nvim_ui_set_focus", vec![focus.into()]);
     }
 
+    /// Scroll the window so that the given zero-based line is at the top
+    pub fn set_topline(&mut self, window: i64, topline: u32) {
+        self.exec_lua(
+            "local win, topline = ...
+            vim.api.nvim_win_call(win, function()
+              vim.fn.winrestview({ topline = topline })
+            end)"
+                .to_string(),
+            vec![window.into(), (topline + 1).into()],
+        );
+    }
+
     pub fn exec_lua(&mut self, lua: String, args: Vec<Value>) {
         self.call("nvim_exec_lua", vec![lua.into(), args.into()]);
     }
@@ -0,0 +1,286 @@
+//! Animation of a value toward a target that may move while the animation is
+//! in progress
+
+use neophyte_linalg::PixelVec;
+use neophyte_ui_event::{MaybeInto, Parse};
+use std::{
+    ops::{Add, Mul, Sub},
+    time::Duration,
+};
+
+/// How an animated value approaches its target
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Curve {
+    /// Eases out over a duration that grows with the distance traveled
+    #[default]
+    Smooth,
+    /// Moves at a constant speed over the given number of seconds
+    Linear { duration: f32 },
+    /// Decelerates over the given number of seconds
+    EaseOutCubic { duration: f32 },
+    /// Settles like a critically damped spring with the given stiffness
+    Spring { stiffness: f32 },
+    /// Jumps straight to the target
+    Instant,
+}
+
+impl Curve {
+    /// The rate at which time passes for the curve. The smooth curve is normalized
+    /// by distance rather than measured in seconds, so it is scaled by a
+    /// factor that depends on the units being animated.
+    pub fn rate(self, smooth_rate: f32) -> f32 {
+        match self {
+            Self::Smooth => smooth_rate,
+            _ => 1.,
+        }
+    }
+
+    /// The time to reach the target from the given distance, or None if the
+    /// curve is not timed
+    fn duration(self, distance: f32) -> Option<f32> {
+        match self {
+            Self::Smooth => Some(distance.sqrt() + distance.ln_1p()),
+            Self::Linear { duration } | Self::EaseOutCubic { duration } => Some(duration),
+            Self::Spring { .. } | Self::Instant => None,
+        }
+    }
+
+    /// The fraction of the distance covered at the given fraction of the
+    /// duration
+    fn ease(self, s: f32) -> f32 {
+        let v = 1. - s;
+        match self {
+            Self::Smooth => 1. - v * v,
+            Self::Linear { .. } => s,
+            Self::EaseOutCubic { .. } => 1. - v * v * v,
+            Self::Spring { .. } | Self::Instant => 1.,
+        }
+    }
+}
+
+impl Parse for Curve {
+    fn parse(value: rmpv::Value) -> Option<Self> {
+        let rmpv::Value::Map(map) = value else {
+            return None;
+        };
+        let mut kind: Option<String> = None;
+        let mut duration: f32 = 0.15;
+        let mut stiffness: f32 = 200.;
+        for (k, v) in map {
+            match k.as_str()? {
+                "kind" => kind = Some(v.maybe_into()?),
+                "duration" => duration = v.maybe_into()?,
+                "stiffness" => stiffness = v.maybe_into()?,
+                _ => {}
+            }
+        }
+        let is_valid = |n: f32| n.is_finite() && n > 0.;
+        if !is_valid(duration) || !is_valid(stiffness) {
+            return None;
+        }
+        match kind?.as_str() {
+            "smooth" => Some(Self::Smooth),
+            "linear" => Some(Self::Linear { duration }),
+            "ease_out_cubic" => Some(Self::EaseOutCubic { duration }),
+            "spring" => Some(Self::Spring { stiffness }),
+            "instant" => Some(Self::Instant),
+            _ => None,
+        }
+    }
+}
+
+/// A quantity that can be animated
+pub trait Animatable:
+    Copy + Default + Add<Output = Self> + Sub<Output = Self> + Mul<f32, Output = Self>
+{
+    fn magnitude(self) -> f32;
+}
+
+impl Animatable for f32 {
+    fn magnitude(self) -> f32 {
+        self.abs()
+    }
+}
+
+impl Animatable for PixelVec<f32> {
+    fn magnitude(self) -> f32 {
+        self.length()
+    }
+}
+
+/// Tracks the offset of an animated value from its target. When the target
+/// moves, the animation restarts from the current offset and velocity so that
+/// motion stays continuous.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Animation<T> {
+    /// The offset from the target when the animation started
+    start: T,
+    /// Velocity added to the curve's own starting velocity to match the
+    /// velocity before the target moved, in units per second
+    added_velocity: T,
+    /// Time since the animation started, in seconds scaled by the speed
+    t: f32,
+}
+
+/// Offsets and velocities below this are considered to have settled
+const SETTLED: f32 = 0.01;
+
+impl<T: Animatable> Animation<T> {
+    /// Advance the animation by the given time, scaled by the speed. Returns
+    /// whether the animation is still in progress.
+    pub fn advance(&mut self, delta_time: Duration, speed: f32, curve: Curve) -> bool {
+        self.t += delta_time.as_secs_f32() * speed;
+        if self.is_settled(curve) {
+            *self = Self::default();
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Move the target by the given amount, leaving the value where it is.
+    /// If the value was already moving, it keeps its velocity.
+    pub fn retarget(&mut self, delta: T, curve: Curve) {
+        let velocity = self.velocity(curve);
+        let mut next = Self {
+            start: self.offset(curve) + delta,
+            added_velocity: T::default(),
+            t: 0.,
+        };
+        if velocity.magnitude() > 0. {
+            next.added_velocity = velocity - next.velocity(curve);
+        }
+        *self = next;
+    }
+
+    /// The current offset from the target
+    pub fn offset(&self, curve: Curve) -> T {
+        self.offset_at(self.t, curve)
+    }
+
+    /// The fraction of the distance from the start of the animation that
+    /// remains
+    pub fn remaining(&self, curve: Curve) -> f32 {
+        let start = self.start.magnitude();
+        if start == 0. {
+            0.
+        } else {
+            (self.offset(curve).magnitude() / start).min(1.)
+        }
+    }
+
+    fn velocity(&self, curve: Curve) -> T {
+        const H: f32 = 1e-3;
+        (self.offset_at(self.t + H, curve) - self.offset_at(self.t, curve)) * H.recip()
+    }
+
+    fn is_settled(&self, curve: Curve) -> bool {
+        match curve {
+            Curve::Spring { .. } => {
+                self.offset(curve).magnitude() < SETTLED
+                    && self.velocity(curve).magnitude() < SETTLED
+            }
+            _ => self.offset(curve).magnitude() == 0.,
+        }
+    }
+
+    fn offset_at(&self, t: f32, curve: Curve) -> T {
+        match curve {
+            Curve::Instant => T::default(),
+            Curve::Spring { stiffness } => {
+                // Critically damped: x(t) = (x0 + (v0 + w x0) t) e^(-w t)
+                let w = stiffness.sqrt();
+                (self.start + (self.added_velocity + self.start * w) * t) * (-w * t).exp()
+            }
+            _ => {
+                let Some(duration) = curve.duration(self.start.magnitude()) else {
+                    return T::default();
+                };
+                if duration <= 0. || t >= duration {
+                    return T::default();
+                }
+                let s = t / duration;
+                let v = 1. - s;
+                // The Hermite basis for the starting tangent adds velocity
+                // without moving the endpoints
+                self.start * (1. - curve.ease(s)) + self.added_velocity * (duration * s * v * v)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVES: [Curve; 4] = [
+        Curve::Smooth,
+        Curve::Linear { duration: 0.2 },
+        Curve::EaseOutCubic { duration: 0.2 },
+        Curve::Spring { stiffness: 200. },
+    ];
+
+    fn settle(animation: &mut Animation<f32>, curve: Curve) -> usize {
+        let mut frames = 0;
+        while animation.advance(Duration::from_millis(16), 1., curve) {
+            frames += 1;
+            assert!(frames < 1000, "{curve:?} did not settle");
+        }
+        frames
+    }
+
+    #[test]
+    fn reaches_target() {
+        for curve in CURVES {
+            let mut animation = Animation::default();
+            animation.retarget(10., curve);
+            assert_eq!(animation.offset(curve), 10.);
+            settle(&mut animation, curve);
+            assert_eq!(animation.offset(curve), 0.);
+        }
+    }
+
+    #[test]
+    fn instant_does_not_animate() {
+        let mut animation = Animation::default();
+        animation.retarget(10., Curve::Instant);
+        assert_eq!(animation.offset(Curve::Instant), 0.);
+        assert_eq!(settle(&mut animation, Curve::Instant), 0);
+    }
+
+    #[test]
+    fn retargeting_preserves_velocity() {
+        for curve in CURVES {
+            let mut animation = Animation::default();
+            animation.retarget(10., curve);
+            animation.advance(Duration::from_millis(50), 1., curve);
+            let offset = animation.offset(curve);
+            let velocity = animation.velocity(curve);
+            animation.retarget(5., curve);
+            assert!((animation.offset(curve) - (offset + 5.)).abs() < 1e-4);
+            assert!(
+                (animation.velocity(curve) - velocity).abs() < velocity.abs() * 0.05,
+                "{curve:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_non_finite_settings() {
+        use rmpv::Value;
+        let parse = |kind: &str, key: &str, n: f64| {
+            Curve::parse(Value::Map(vec![
+                ("kind".into(), kind.into()),
+                (key.into(), n.into()),
+            ]))
+        };
+        assert_eq!(
+            parse("linear", "duration", 0.5),
+            Some(Curve::Linear { duration: 0.5 })
+        );
+        for n in [f64::INFINITY, f64::NEG_INFINITY, f64::NAN] {
+            assert_eq!(parse("linear", "duration", n), None);
+            assert_eq!(parse("spring", "stiffness", n), None);
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use super::text::Text;
 use crate::{
-    text::{cache::FontCache, fonts::Fonts},
+    text::{cache::FontCache, fonts::Fonts, ligatures::Ligatures},
     ui::{
         cmdline::{Cmdline, Mode},
         grid::CellContents,
+        width::CharWidth,
     },
 };
 use neophyte_linalg::{CellVec, Vec2};
@@ -27,6 +28,7 @@ impl CmdlineGrid {
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         cmdline: &Cmdline,
+        char_width: CharWidth,
         base_grid_size: Vec2<u16>,
         grid_bind_group_layout: &wgpu::BindGroupLayout,
         highlights: &[Option<Attributes>],
@@ -35,6 +37,7 @@ impl CmdlineGrid {
         fonts: &Fonts,
         font_cache: &mut FontCache,
         shape_context: &mut ShapeContext,
+        ligatures: &Ligatures,
     ) {
         if let Some(mode) = &cmdline.mode {
             match mode {
@@ -53,13 +56,13 @@ impl CmdlineGrid {
                                 highlight: 0,
                                 text: prompt.first_char.unwrap_or(' ').into(),
                             })
-                            .chain(prompt.prompt.chars().map(|c| CellContents {
-                                highlight: 0,
-                                text: c.into(),
-                            }))
-                            .chain(iter_line(first_line)),
+                            .chain(char_width.cells(&prompt.prompt, 0))
+                            .chain(iter_line(first_line, char_width)),
                         ))
-                        .chain(content_lines.map(|line| IterVariants::Tail(iter_line(line))))
+                        .chain(
+                            content_lines
+                                .map(|line| IterVariants::Tail(iter_line(line, char_width))),
+                        )
                         .enumerate()
                         .map(|(i, line)| (i as i32, line)),
                         grid_bind_group_layout,
@@ -69,6 +72,8 @@ impl CmdlineGrid {
                         fonts,
                         font_cache,
                         shape_context,
+                        ligatures,
+                        None,
                     )
                 }
                 Mode::Block {
@@ -89,6 +94,8 @@ impl CmdlineGrid {
                 fonts,
                 font_cache,
                 shape_context,
+                ligatures,
+                None,
             )
         }
 
@@ -97,13 +104,14 @@ impl CmdlineGrid {
     }
 }
 
-fn iter_line(content: &Content) -> impl Iterator<Item = CellContents> + Clone {
-    content.chunks.iter().flat_map(|chunk| {
-        chunk.text_chunk.chars().map(|c| CellContents {
-            highlight: chunk.attr_id,
-            text: c.into(),
-        })
-    })
+fn iter_line(
+    content: &Content,
+    char_width: CharWidth,
+) -> impl Iterator<Item = CellContents<'_>> + Clone {
+    content
+        .chunks
+        .iter()
+        .flat_map(move |chunk| char_width.cells(&chunk.text_chunk, chunk.attr_id))
 }
 
 #[derive(Clone)]
@@ -1,26 +1,213 @@
-use super::{scrolling_grids::ScrollingGrids, text::Text};
+use super::{
+    Motion,
+    animation::Curve,
+    images::Image,
+    overlays::{Overlay, Overlays, Shape},
+    pipelines::image::Placed,
+    scrollbar::{Geometry, Scrollbar, ScrollbarSettings},
+    scrolling_grids::ScrollingGrids,
+    text::Text,
+    transition::{Dim, DimInactive, FloatTransition, Transition},
+};
 use crate::{
-    text::{cache::FontCache, fonts::Fonts},
-    ui::{self, Ui},
+    text::{cache::FontCache, fonts::Fonts, ligatures::Ligatures},
+    ui::{
+        self, Ui,
+        grid::{Extmark, ExtmarkId},
+        window::Window,
+    },
 };
+use neophyte_linalg::{CellVec, PixelVec, Vec2};
 use neophyte_ui_event::rgb::Rgb;
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 use swash::shape::ShapeContext;
 
 pub struct Grid {
     pub text: Text,
+    /// Text in the grid margins, which does not scroll
+    pub margins: Text,
     pub scrolling: ScrollingGrids,
+    pub transition: Transition,
+    pub dim: Dim,
+    pub scrollbar: Scrollbar,
+    /// Images shown over the grid by ID
+    pub images: HashMap<u64, Image>,
+    /// UI-watched extmarks that are visible in the grid
+    pub extmarks: HashMap<ExtmarkId, Extmark>,
+    /// The buffer shown by the window, which owns the extmarks
+    buffer: Option<i64>,
+    /// The cursor position that shaping runs were last broken at
+    cursor: Option<CellVec<i32>>,
+    /// The window position as of the last time the grid was shown
+    position: Option<CellVec<f32>>,
+    is_shown: bool,
+    is_float: bool,
+    /// Whether the grid is a normal or floating window, as opposed to the
+    /// global grid or messages
+    is_window: bool,
+    /// Whether the cursor can enter the window. Floats such as the popup menu
+    /// are not focusable.
+    is_focusable: bool,
 }
 
 impl Grid {
-    pub fn new(text: Text, scrolling: ScrollingGrids) -> Self {
-        Self { text, scrolling }
+    pub fn new(text: Text, margins: Text, scrolling: ScrollingGrids) -> Self {
+        Self {
+            text,
+            margins,
+            scrolling,
+            transition: Transition::default(),
+            dim: Dim::default(),
+            scrollbar: Scrollbar::default(),
+            images: HashMap::new(),
+            extmarks: HashMap::new(),
+            buffer: None,
+            cursor: None,
+            position: None,
+            is_shown: false,
+            is_float: false,
+            is_window: false,
+            is_focusable: false,
+        }
+    }
+
+    /// The scrolling text and the fixed margins with their depth and scroll
+    /// offset
+    pub fn texts(&self, z: f32, cell_size: Vec2<u32>) -> [(f32, PixelVec<i32>, &Text); 2] {
+        [
+            (
+                z,
+                self.scrolling.offset().round_to_pixels(cell_size),
+                &self.text,
+            ),
+            (z, PixelVec::new(0, 0), &self.margins),
+        ]
+    }
+
+    /// Whether the grid is drawn to its own layer and blended over the grids
+    /// beneath, either because it is fading or because of winblend or
+    /// pumblend
+    pub fn is_translucent(&self) -> bool {
+        self.transition.opacity() < 1. || self.text.is_translucent()
+    }
+
+    /// Whether the grid is a floating window
+    pub fn is_float(&self) -> bool {
+        self.is_float
+    }
+
+    /// The position and size of the window including its margins, in pixels
+    pub fn bounds(&self, cell_size: Vec2<u32>) -> Option<(PixelVec<i32>, PixelVec<u32>)> {
+        self.margins.clip_region(cell_size)
+    }
+
+    /// The scrollbar of a normal window with the given width in pixels, or
+    /// None if the window has no scrollbar
+    pub fn scrollbar_geometry(&self, cell_size: Vec2<u32>, width: f32) -> Option<Geometry> {
+        if !self.is_window || self.is_float {
+            return None;
+        }
+        let (offset, size) = self.text.clip_region(cell_size)?;
+        Geometry::new(
+            self.scrollbar.viewport()?,
+            offset.cast_as(),
+            size.cast_as(),
+            width,
+        )
+    }
+
+    /// The pixel position of the top left cell of the scrolling text, and
+    /// the position and size of the region it is clipped to
+    #[allow(clippy::type_complexity)]
+    pub fn placement(
+        &self,
+        cell_size: Vec2<u32>,
+    ) -> Option<(PixelVec<i32>, (PixelVec<i32>, PixelVec<u32>))> {
+        let offset = self.text.offset()?.round_to_pixels(cell_size)
+            + self.scrolling.offset().round_to_pixels(cell_size);
+        Some((offset, self.text.clip_region(cell_size)?))
+    }
+
+    /// The images over the grid and those attached to its extmarks with their
+    /// placement in pixels, which scroll with the text and are clipped to the
+    /// same region
+    pub fn placed_images<'a>(
+        &'a self,
+        z: f32,
+        cell_size: Vec2<u32>,
+        overlays: &'a Overlays,
+    ) -> impl Iterator<Item = Placed<'a>> {
+        let cell_size_i32 = cell_size.try_cast::<i32>().unwrap();
+        self.placement(cell_size)
+            .into_iter()
+            .flat_map(move |(offset, clip)| {
+                let images = self
+                    .images
+                    .values()
+                    .map(|image| (&image.texture, image.position, image.size));
+                let overlays = self.extmarks.iter().filter_map(|(&id, extmark)| {
+                    let Overlay::Image { texture, size } = overlays.get(self.buffer?, id)? else {
+                        return None;
+                    };
+                    Some((texture, extmark.position.cast_as(), *size))
+                });
+                images
+                    .chain(overlays)
+                    .map(move |(texture, position, size)| Placed {
+                        texture,
+                        offset: offset + position.into_pixels(cell_size_i32),
+                        size: size.into_pixels(cell_size),
+                        clip,
+                        z,
+                    })
+            })
+    }
+
+    /// The shapes attached to the extmarks of the grid with the top left
+    /// corner of their cells in pixels. Extmarks outside the clip region are
+    /// skipped.
+    pub fn placed_shapes<'a>(
+        &'a self,
+        cell_size: Vec2<u32>,
+        overlays: &'a Overlays,
+    ) -> impl Iterator<Item = (Shape, PixelVec<f32>)> + 'a {
+        self.placement(cell_size)
+            .into_iter()
+            .flat_map(move |(offset, (clip_offset, clip_size))| {
+                let clip_max = clip_offset + clip_size.cast_as();
+                self.extmarks.iter().filter_map(move |(&id, extmark)| {
+                    let Overlay::Shape(shape) = overlays.get(self.buffer?, id)? else {
+                        return None;
+                    };
+                    let cell = offset
+                        + extmark
+                            .position
+                            .cast_as::<i32>()
+                            .into_pixels(cell_size.try_cast().unwrap());
+                    let inside = cell.0.x >= clip_offset.0.x
+                        && cell.0.y >= clip_offset.0.y
+                        && cell.0.x < clip_max.0.x
+                        && cell.0.y < clip_max.0.y;
+                    inside.then_some((*shape, cell.cast_as()))
+                })
+            })
+    }
+
+    /// Place the text at the window position plus any transition offset
+    fn update_window(&mut self) {
+        let position = self
+            .position
+            .map(|position| position + self.transition.offset());
+        self.text.update_window(position);
+        self.margins.update_window(position);
     }
 }
 
 pub struct Grids {
     grids: HashMap<ui::grid::Id, Grid>,
     draw_order: Vec<ui::grid::Id>,
+    /// The grid with the cursor
+    active: ui::grid::Id,
     bind_group_layout: wgpu::BindGroupLayout,
 }
 
@@ -29,6 +216,7 @@ impl Grids {
         Self {
             grids: HashMap::new(),
             draw_order: vec![],
+            active: 0,
             bind_group_layout: device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: Some("Grid bind group layout"),
                 entries: &[wgpu::BindGroupLayoutEntry {
@@ -54,15 +242,39 @@ impl Grids {
         fonts: &Fonts,
         font_cache: &mut FontCache,
         shape_context: &mut ShapeContext,
+        ligatures: &Ligatures,
+        float_transition: FloatTransition,
+        window_move_duration: f32,
     ) {
-        self.grids.retain(|id, _| ui.grid(*id).is_some());
+        let is_shown = |id: ui::grid::Id| ui.draw_order.iter().any(|item| item.grid == id);
+
+        // Floats that are no longer shown are kept around while they
+        // transition out
+        for id in self.draw_order.iter() {
+            if let Some(grid) = self.grids.get_mut(id)
+                && grid.is_float
+                && !grid.transition.is_closing()
+                && !is_shown(*id)
+            {
+                grid.transition.close(float_transition);
+            }
+        }
+
+        self.grids
+            .retain(|id, grid| ui.grid(*id).is_some() || grid.transition.is_closing());
 
         let fg = ui.default_colors.rgb_fg.unwrap_or(Rgb::WHITE);
         let bg = ui.default_colors.rgb_bg.unwrap_or(Rgb::BLACK);
+        let mode = ui
+            .modes
+            .get(ui.current_mode as usize)
+            .and_then(|mode| mode.name.as_deref());
+        let breaks_at_cursor = ligatures.breaks_at_cursor(mode);
 
         for ui_grid in ui.grids.iter() {
             let grid = self.grids.entry(ui_grid.id).or_insert_with(|| {
                 Grid::new(
+                    Text::new(ui_grid.contents().size.try_cast().unwrap()),
                     Text::new(ui_grid.contents().size.try_cast().unwrap()),
                     // TODO: Does these need to be initialized with data?
                     // We might just fill it anyway down below.
@@ -70,15 +282,62 @@ impl Grids {
                 )
             });
 
-            let window_position = ui.position(ui_grid.id);
+            if is_shown(ui_grid.id) {
+                let position = ui.position(ui_grid.id);
+                let (is_float, is_window, move_duration) = match ui_grid.window() {
+                    Window::Floating(_) => (true, true, float_transition.move_duration()),
+                    Window::Normal(_) => (false, true, window_move_duration),
+                    _ => (false, false, 0.),
+                };
+                let is_focusable = match ui_grid.window() {
+                    Window::Floating(window) => window.focusable,
+                    _ => is_window,
+                };
+                if is_float && !grid.is_shown {
+                    grid.transition.open(float_transition);
+                } else if grid.is_shown
+                    && let (Some(old), Some(new)) = (grid.position, position)
+                    && old != new
+                {
+                    grid.transition.move_by(new - old, move_duration);
+                }
+                grid.position = position;
+                grid.is_float = is_float;
+                grid.is_window = is_window;
+                grid.is_focusable = is_focusable;
+                grid.is_shown = true;
+            } else {
+                grid.is_shown = false;
+            }
+
+            grid.scrollbar.update(ui_grid.viewport);
+            grid.extmarks.clone_from(&ui_grid.extmarks);
+            grid.buffer = ui_grid.buffer;
+
             if ui_grid.dirty.contents() {
+                let margins = ui_grid.fixed_margins();
+                grid.scrolling.set_margins(margins);
+                grid.text.set_clip(margins);
                 if ui_grid.scroll_delta != 0 {
                     grid.scrolling
                         .push(ui_grid.contents().clone(), ui_grid.scroll_delta);
                 } else {
                     grid.scrolling.replace(ui_grid.contents().clone());
                 }
+            }
+            if ui_grid.scroll_delta_cols != 0 {
+                grid.scrolling.push_horizontal(ui_grid.scroll_delta_cols);
+            }
+            // Images move with the text they are placed over
+            let scroll_delta = CellVec::new(ui_grid.scroll_delta_cols, ui_grid.scroll_delta);
+            for image in grid.images.values_mut() {
+                image.position -= scroll_delta;
+            }
 
+            let cursor = (breaks_at_cursor && ui.cursor.grid == ui_grid.id)
+                .then(|| CellVec(ui.cursor.pos.0.cast_as()));
+            if ui_grid.dirty.contents() || cursor != grid.cursor {
+                grid.cursor = cursor;
                 grid.text.update_contents(
                     device,
                     queue,
@@ -91,15 +350,116 @@ impl Grids {
                     fonts,
                     font_cache,
                     shape_context,
+                    ligatures,
+                    cursor,
                 );
             }
 
-            grid.text.update_window(window_position);
+            if ui_grid.dirty.contents() {
+                grid.margins.update_contents(
+                    device,
+                    queue,
+                    Some(grid.scrolling.size().try_cast().unwrap()),
+                    grid.scrolling.margin_rows(),
+                    &self.bind_group_layout,
+                    &ui.highlights,
+                    fg,
+                    bg,
+                    fonts,
+                    font_cache,
+                    shape_context,
+                    ligatures,
+                    None,
+                );
+            }
+
+            grid.update_window();
         }
 
-        self.draw_order.clear();
+        self.active = ui.cursor.grid;
+
+        // Closing floats keep their place in the draw order
+        let previous_order = std::mem::take(&mut self.draw_order);
         self.draw_order
             .extend(ui.draw_order.iter().map(|draw_item| draw_item.grid));
+        for (i, id) in previous_order.into_iter().enumerate() {
+            if self
+                .grids
+                .get(&id)
+                .is_some_and(|grid| grid.transition.is_closing())
+                && !self.draw_order.contains(&id)
+            {
+                self.draw_order.insert(i.min(self.draw_order.len()), id);
+            }
+        }
+    }
+
+    pub fn advance(
+        &mut self,
+        delta_time: Duration,
+        scroll_speed: f32,
+        scroll_curve: Curve,
+        dim_inactive: DimInactive,
+        scrollbar: ScrollbarSettings,
+        cell_size: Vec2<f32>,
+    ) -> Motion {
+        let mut motion = Motion::Still;
+        for (&id, grid) in self.grids.iter_mut() {
+            motion = motion.soonest(grid.scrolling.advance(
+                delta_time,
+                scroll_speed,
+                scroll_curve,
+                cell_size,
+            ));
+            if grid.transition.advance(delta_time) {
+                motion = Motion::Animating;
+            }
+            // Floats such as the completion menu are used without the
+            // cursor, so only windows it can enter are dimmed
+            let dim = if grid.is_focusable && id != self.active {
+                dim_inactive.amount()
+            } else {
+                0.
+            };
+            grid.dim.set(dim, dim_inactive);
+            if grid.dim.advance(delta_time) {
+                motion = Motion::Animating;
+            }
+            motion = motion.soonest(grid.scrollbar.advance(delta_time, scrollbar));
+            grid.update_window();
+        }
+
+        // Floats that finished closing are no longer drawn
+        let grids = &self.grids;
+        self.draw_order
+            .retain(|id| grids[id].is_shown || grids[id].transition.is_closing());
+        motion
+    }
+
+    pub fn get(&self, id: ui::grid::Id) -> Option<&Grid> {
+        self.grids.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: ui::grid::Id) -> Option<&mut Grid> {
+        self.grids.get_mut(&id)
+    }
+
+    /// Show an image over the given grid, replacing any image with the same
+    /// ID. Returns whether the grid exists.
+    pub fn show_image(&mut self, id: u64, grid: ui::grid::Id, image: Image) -> bool {
+        self.hide_image(id);
+        let Some(grid) = self.grids.get_mut(&grid) else {
+            return false;
+        };
+        grid.images.insert(id, image);
+        true
+    }
+
+    /// Returns whether there was an image with the given ID
+    pub fn hide_image(&mut self, id: u64) -> bool {
+        self.grids
+            .values_mut()
+            .any(|grid| grid.images.remove(&id).is_some())
     }
 
     pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
@@ -110,6 +470,12 @@ impl Grids {
         self.draw_order.len()
     }
 
+    /// The index of the grid in front to back order
+    pub fn front_to_back_index(&self, id: ui::grid::Id) -> Option<usize> {
+        let i = self.draw_order.iter().position(|&grid| grid == id)?;
+        Some(self.draw_order.len() - 1 - i)
+    }
+
     pub fn front_to_back(&self) -> impl Iterator<Item = (usize, &Grid)> {
         self.draw_order
             .iter()
@@ -117,8 +483,4 @@ impl Grids {
             .enumerate()
             .map(move |(i, &grid_id)| (i, self.grids.get(&grid_id).unwrap()))
     }
-
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Grid> {
-        self.grids.values_mut()
-    }
 }
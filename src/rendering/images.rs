@@ -0,0 +1,189 @@
+//! Images placed over grid cells by plugins
+
+use super::pipelines::image::ImageTexture;
+use crate::ui;
+use neophyte_linalg::{CellVec, Vec2};
+use neophyte_ui_event::{MaybeInto, Parse};
+use std::io::Cursor;
+
+/// Where an image is anchored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    Grid(ui::grid::Id),
+    /// The grid of the window with the given handle
+    Window(i64),
+}
+
+/// A request to show an image
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageShow {
+    /// Identifies the image so that it can be replaced or hidden
+    pub id: u64,
+    pub anchor: Anchor,
+    /// The cell of the top left corner of the image
+    pub position: CellVec<u32>,
+    /// The number of cells the image spans
+    pub size: CellVec<u32>,
+    pub data: ImageData,
+}
+
+impl Parse for ImageShow {
+    fn parse(value: rmpv::Value) -> Option<Self> {
+        let rmpv::Value::Map(map) = value else {
+            return None;
+        };
+        let mut id = None;
+        let mut anchor = None;
+        let mut position = CellVec::new(0, 0);
+        let mut size = CellVec::new(1, 1);
+        let mut png = None;
+        let mut rgba = None;
+        let mut pixel_size = Vec2::new(0, 0);
+        for (k, v) in map {
+            match k.as_str()? {
+                "id" => id = Some(v.maybe_into()?),
+                "grid" => anchor = Some(Anchor::Grid(v.maybe_into()?)),
+                "window" => anchor = Some(Anchor::Window(v.maybe_into()?)),
+                "row" => position.0.y = v.maybe_into()?,
+                "col" => position.0.x = v.maybe_into()?,
+                "width" => size.0.x = v.maybe_into()?,
+                "height" => size.0.y = v.maybe_into()?,
+                "png" => png = Some(v.as_slice()?.to_vec()),
+                "rgba" => rgba = Some(v.as_slice()?.to_vec()),
+                "pixel_width" => pixel_size.x = v.maybe_into()?,
+                "pixel_height" => pixel_size.y = v.maybe_into()?,
+                _ => {}
+            }
+        }
+        let data = ImageData::from_source(png, rgba, pixel_size)?;
+        (size.0.x > 0 && size.0.y > 0).then_some(Self {
+            id: id?,
+            anchor: anchor?,
+            position,
+            size,
+            data,
+        })
+    }
+}
+
+/// An image shown over a grid
+pub struct Image {
+    pub texture: ImageTexture,
+    /// The cell of the top left corner, which moves as the grid scrolls
+    pub position: CellVec<i32>,
+    /// The number of cells the image spans
+    pub size: CellVec<u32>,
+}
+
+/// Decoded pixels in 8-bit sRGB with straight alpha
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageData {
+    pub pixels: Vec<u8>,
+    pub size: Vec2<u32>,
+}
+
+impl ImageData {
+    /// Decode exactly one of PNG data or raw pixels of the given size
+    pub fn from_source(
+        png: Option<Vec<u8>>,
+        rgba: Option<Vec<u8>>,
+        pixel_size: Vec2<u32>,
+    ) -> Option<Self> {
+        match (png, rgba) {
+            (Some(png), None) => Self::from_png(&png),
+            (None, Some(rgba)) => Self::from_rgba(rgba, pixel_size),
+            _ => None,
+        }
+    }
+
+    /// Raw pixels, or None if their length does not match the size
+    pub fn from_rgba(pixels: Vec<u8>, size: Vec2<u32>) -> Option<Self> {
+        let len = size.x as usize * size.y as usize * 4;
+        (len > 0 && pixels.len() == len).then_some(Self { pixels, size })
+    }
+
+    pub fn from_png(png: &[u8]) -> Option<Self> {
+        let mut decoder = png::Decoder::new(Cursor::new(png));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder
+            .read_info()
+            .inspect_err(|e| log::error!("Failed to decode image: {e}"))
+            .ok()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut buffer)
+            .inspect_err(|e| log::error!("Failed to decode image: {e}"))
+            .ok()?;
+        buffer.truncate(info.buffer_size());
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buffer,
+            png::ColorType::Rgb => buffer
+                .chunks_exact(3)
+                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], u8::MAX])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buffer
+                .chunks_exact(2)
+                .flat_map(|ga| [ga[0], ga[0], ga[0], ga[1]])
+                .collect(),
+            png::ColorType::Grayscale => buffer.iter().flat_map(|&g| [g, g, g, u8::MAX]).collect(),
+            // Expanded to RGB by normalize_to_color8
+            png::ColorType::Indexed => return None,
+        };
+        Self::from_rgba(pixels, Vec2::new(info.width, info.height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmpv::Value;
+
+    fn encode_png(size: Vec2<u32>, color_type: png::ColorType, data: &[u8]) -> Vec<u8> {
+        let mut out = vec![];
+        let mut encoder = png::Encoder::new(&mut out, size.x, size.y);
+        encoder.set_color(color_type);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .unwrap()
+            .write_image_data(data)
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn decodes_rgb_png_as_rgba() {
+        let png = encode_png(Vec2::new(2, 1), png::ColorType::Rgb, &[1, 2, 3, 4, 5, 6]);
+        let image = ImageData::from_png(&png).unwrap();
+        assert_eq!(image.size, Vec2::new(2, 1));
+        assert_eq!(image.pixels, [1, 2, 3, 255, 4, 5, 6, 255]);
+    }
+
+    #[test]
+    fn rgba_must_match_size() {
+        assert!(ImageData::from_rgba(vec![0; 16], Vec2::new(2, 2)).is_some());
+        assert!(ImageData::from_rgba(vec![0; 15], Vec2::new(2, 2)).is_none());
+        assert!(ImageData::from_rgba(vec![], Vec2::new(0, 0)).is_none());
+    }
+
+    #[test]
+    fn parses_image_show() {
+        let value = Value::Map(vec![
+            ("id".into(), 7.into()),
+            ("window".into(), 1000.into()),
+            ("row".into(), 2.into()),
+            ("col".into(), 4.into()),
+            ("width".into(), 10.into()),
+            ("height".into(), 5.into()),
+            ("rgba".into(), Value::Binary(vec![255; 4])),
+            ("pixel_width".into(), 1.into()),
+            ("pixel_height".into(), 1.into()),
+        ]);
+        let show = ImageShow::parse(value).unwrap();
+        assert_eq!(show.id, 7);
+        assert_eq!(show.anchor, Anchor::Window(1000));
+        assert_eq!(show.position, CellVec::new(4, 2));
+        assert_eq!(show.size, CellVec::new(10, 5));
+        assert_eq!(show.data.size, Vec2::new(1, 1));
+    }
+}
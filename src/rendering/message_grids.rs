@@ -1,7 +1,7 @@
 use super::text::Text;
 use crate::{
-    text::{cache::FontCache, fonts::Fonts},
-    ui::{grid::CellContents, messages::Messages},
+    text::{cache::FontCache, fonts::Fonts, ligatures::Ligatures},
+    ui::{grid::CellContents, messages::Messages, width::CharWidth},
 };
 use neophyte_linalg::{CellVec, Vec2};
 use neophyte_ui_event::{Content, hl_attr_define::Attributes, rgb::Rgb};
@@ -10,6 +10,8 @@ use swash::shape::ShapeContext;
 pub struct MessageGrids {
     texts: Vec<Text>,
     previous_base_grid_size: Vec2<u16>,
+    /// Whether shown messages were left to the toasts
+    had_toasts: bool,
 }
 
 impl MessageGrids {
@@ -17,6 +19,7 @@ impl MessageGrids {
         Self {
             texts: vec![],
             previous_base_grid_size: Vec2::new(0, 0),
+            had_toasts: false,
         }
     }
 
@@ -26,6 +29,8 @@ impl MessageGrids {
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         messages: &Messages,
+        toasts: bool,
+        char_width: CharWidth,
         base_grid_size: Vec2<u16>,
         grid_bind_group_layout: &wgpu::BindGroupLayout,
         highlights: &[Option<Attributes>],
@@ -34,23 +39,37 @@ impl MessageGrids {
         fonts: &Fonts,
         font_cache: &mut FontCache,
         shape_context: &mut ShapeContext,
+        ligatures: &Ligatures,
     ) {
         let did_size_change = self.previous_base_grid_size != base_grid_size;
         self.previous_base_grid_size = base_grid_size;
-        if !(messages.dirty || did_size_change) {
+        let did_toasts_change = self.had_toasts != toasts;
+        self.had_toasts = toasts;
+        if !(messages.dirty || did_size_change || did_toasts_change) {
             return;
         }
 
         self.texts.clear();
         let mut offset = 0;
         let is_history = !messages.history.is_empty();
-        let to_display = if is_history {
-            &messages.history
+        // Shown messages are drawn as toasts instead when enabled
+        let to_display: Vec<_> = if is_history {
+            messages
+                .history
+                .iter()
+                .map(|message| &message.content)
+                .collect()
+        } else if toasts {
+            vec![]
         } else {
-            &messages.show
+            messages
+                .show
+                .iter()
+                .map(|message| &message.content)
+                .collect()
         };
-        for message in to_display.iter().rev() {
-            let lines = lines(&message.content);
+        for content in to_display.into_iter().rev() {
+            let lines = lines(content, char_width);
             offset += lines.len();
             let mut text = Text::new(CellVec::new(0, 0));
 
@@ -74,6 +93,8 @@ impl MessageGrids {
                 fonts,
                 font_cache,
                 shape_context,
+                ligatures,
+                None,
             );
 
             let position = if is_history {
@@ -95,27 +116,21 @@ impl MessageGrids {
     }
 }
 
-fn lines(content: &Content) -> Vec<Vec<CellContents>> {
-    let mut lines = vec![];
-    let mut cells = content
-        .chunks
-        .iter()
-        .flat_map(|chunk| chunk.text_chunk.chars().map(|c| (c, chunk.attr_id)));
-    loop {
-        let mut line = vec![];
-        for cell in cells.by_ref() {
-            match cell.0 {
-                '\n' => break,
-                c => line.push(CellContents {
-                    text: c.into(),
-                    highlight: cell.1,
-                }),
+pub fn lines(content: &Content, char_width: CharWidth) -> Vec<Vec<CellContents<'_>>> {
+    let mut lines = vec![vec![]];
+    for chunk in content.chunks.iter() {
+        for (i, text) in chunk.text_chunk.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(vec![]);
             }
+            lines
+                .last_mut()
+                .unwrap()
+                .extend(char_width.cells(text, chunk.attr_id));
         }
-        if line.is_empty() {
-            break;
-        }
-        lines.push(line);
+    }
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
     }
     lines
 }
@@ -1,14 +1,20 @@
+pub mod animation;
 mod cmdline_grid;
 mod glyph_bind_group;
 mod glyph_push_constants;
 mod grids;
+pub mod images;
 mod message_grids;
+pub mod overlays;
 pub mod pipelines;
+pub mod scrollbar;
 mod scrolling_grids;
 pub mod state;
 mod targets;
 mod text;
 mod texture;
+pub mod toasts;
+pub mod transition;
 mod wgpu_context;
 
 use std::time::Duration;
@@ -35,6 +41,15 @@ pub enum Motion {
 }
 
 impl Motion {
+    /// The longest delay in seconds, which keeps wake-up times from
+    /// overflowing
+    const MAX_DELAY: f32 = 60. * 60. * 24.;
+
+    /// Wait for the given finite number of seconds
+    pub fn delay(seconds: f32) -> Self {
+        Self::Delay(Duration::from_secs_f32(seconds.clamp(0., Self::MAX_DELAY)))
+    }
+
     pub fn soonest(self, other: Self) -> Self {
         use Motion::*;
         match (self, other) {
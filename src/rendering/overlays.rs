@@ -0,0 +1,295 @@
+//! Decorations that plugins attach to UI-watched extmarks, which follow the
+//! extmarks as they move with edits and scrolling
+
+use super::{images::ImageData, pipelines::image::ImageTexture};
+use crate::{ui::grid::ExtmarkId, util::parse_color};
+use neophyte_linalg::{CellVec, PixelVec, Vec2};
+use neophyte_ui_event::{MaybeInto, Parse};
+use std::collections::HashMap;
+
+/// A request to attach an overlay to an extmark
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverlaySet {
+    /// The buffer the extmark belongs to
+    pub buffer: i64,
+    pub extmark: ExtmarkId,
+    pub kind: OverlayKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverlayKind {
+    /// An image spanning the given number of cells from the extmark
+    Image {
+        data: ImageData,
+        size: CellVec<u32>,
+    },
+    Shape(Shape),
+}
+
+impl Parse for OverlaySet {
+    fn parse(value: rmpv::Value) -> Option<Self> {
+        let rmpv::Value::Map(map) = value else {
+            return None;
+        };
+        let mut buffer = None;
+        let mut ns_id = None;
+        let mut mark_id = None;
+        let mut kind = None;
+        let mut size = CellVec::new(1, 1);
+        let mut color = None;
+        let mut thickness = 1.;
+        let mut png = None;
+        let mut rgba = None;
+        let mut pixel_size = Vec2::new(0, 0);
+        for (k, v) in map {
+            match k.as_str()? {
+                "buffer" => buffer = Some(v.maybe_into()?),
+                "ns_id" => ns_id = Some(v.maybe_into()?),
+                "mark_id" => mark_id = Some(v.maybe_into()?),
+                "kind" => kind = Some(String::parse(v)?),
+                "width" => size.0.x = v.maybe_into()?,
+                "height" => size.0.y = v.maybe_into()?,
+                "color" => color = Some(parse_color(v)?),
+                "thickness" => thickness = v.maybe_into()?,
+                "png" => png = Some(v.as_slice()?.to_vec()),
+                "rgba" => rgba = Some(v.as_slice()?.to_vec()),
+                "pixel_width" => pixel_size.x = v.maybe_into()?,
+                "pixel_height" => pixel_size.y = v.maybe_into()?,
+                _ => {}
+            }
+        }
+        if size.0.x == 0 || size.0.y == 0 {
+            return None;
+        }
+        let kind = match kind?.as_str() {
+            "image" => OverlayKind::Image {
+                data: ImageData::from_source(png, rgba, pixel_size)?,
+                size,
+            },
+            "badge" => OverlayKind::Shape(Shape::Badge {
+                size,
+                color: color?,
+            }),
+            "swatch" => OverlayKind::Shape(Shape::Swatch { color: color? }),
+            "underline" => OverlayKind::Shape(Shape::Underline {
+                cols: size.0.x,
+                thickness: (thickness > 0.).then_some(thickness)?,
+                color: color?,
+            }),
+            _ => return None,
+        };
+        Some(Self {
+            buffer: buffer?,
+            extmark: ExtmarkId {
+                ns_id: ns_id?,
+                mark_id: mark_id?,
+            },
+            kind,
+        })
+    }
+}
+
+/// A filled shape drawn from the cell of an extmark. Colors have straight
+/// alpha.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    /// A pill spanning the given number of cells
+    Badge { size: CellVec<u32>, color: [f32; 4] },
+    /// A dot in the middle of the cell
+    Swatch { color: [f32; 4] },
+    /// A line under the given number of cells
+    Underline {
+        cols: u32,
+        /// Pixels
+        thickness: f32,
+        color: [f32; 4],
+    },
+}
+
+impl Shape {
+    /// The top left and bottom right of the shape for an extmark whose cell
+    /// has the given top left corner. The underline is the given distance
+    /// below the top of the cell.
+    pub fn rect(
+        &self,
+        cell: PixelVec<f32>,
+        cell_size: Vec2<f32>,
+        underline_y: f32,
+    ) -> (PixelVec<f32>, PixelVec<f32>) {
+        match *self {
+            Shape::Badge { size, .. } => {
+                let inset = PixelVec::new(0., (cell_size.y * 0.15).round());
+                let size = PixelVec::new(size.0.x as f32, size.0.y as f32) * PixelVec(cell_size);
+                (cell + inset, cell + size - inset)
+            }
+            Shape::Swatch { .. } => {
+                let radius = PixelVec::new(1., 1.) * (cell_size.x.min(cell_size.y) * 0.35);
+                let center = cell + PixelVec(cell_size / 2.);
+                (center - radius, center + radius)
+            }
+            Shape::Underline {
+                cols, thickness, ..
+            } => {
+                let min = cell + PixelVec::new(0., underline_y);
+                (
+                    min,
+                    min + PixelVec::new(cols as f32 * cell_size.x, thickness),
+                )
+            }
+        }
+    }
+
+    pub fn color(&self) -> [f32; 4] {
+        match *self {
+            Shape::Badge { color, .. }
+            | Shape::Swatch { color }
+            | Shape::Underline { color, .. } => color,
+        }
+    }
+}
+
+pub enum Overlay {
+    Image {
+        texture: ImageTexture,
+        size: CellVec<u32>,
+    },
+    Shape(Shape),
+}
+
+/// Overlays by the buffer and extmark they are attached to. Neovim numbers
+/// extmarks per buffer, so the same extmark ID can appear in several buffers.
+#[derive(Default)]
+pub struct Overlays(HashMap<(i64, ExtmarkId), Overlay>);
+
+impl Overlays {
+    pub fn get(&self, buffer: i64, extmark: ExtmarkId) -> Option<&Overlay> {
+        self.0.get(&(buffer, extmark))
+    }
+
+    pub fn set(&mut self, buffer: i64, extmark: ExtmarkId, overlay: Overlay) {
+        self.0.insert((buffer, extmark), overlay);
+    }
+
+    /// Returns whether there was an overlay on the extmark
+    pub fn remove(&mut self, buffer: i64, extmark: ExtmarkId) -> bool {
+        self.0.remove(&(buffer, extmark)).is_some()
+    }
+
+    /// Remove the overlays of a buffer that went away. Returns whether there
+    /// were any.
+    pub fn remove_buffer(&mut self, buffer: i64) -> bool {
+        let len = self.0.len();
+        self.0
+            .retain(|&(overlay_buffer, _), _| overlay_buffer != buffer);
+        self.0.len() != len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmpv::Value;
+
+    fn map(entries: Vec<(&str, Value)>) -> Value {
+        Value::Map(entries.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+
+    fn color() -> Value {
+        map(vec![
+            ("r", 255.into()),
+            ("g", 0.into()),
+            ("b", 0.into()),
+            ("a", 255.into()),
+        ])
+    }
+
+    #[test]
+    fn parses_underline() {
+        let value = map(vec![
+            ("buffer", 2.into()),
+            ("ns_id", 3.into()),
+            ("mark_id", 4.into()),
+            ("kind", "underline".into()),
+            ("width", 5.into()),
+            ("thickness", 2.into()),
+            ("color", color()),
+        ]);
+        let overlay = OverlaySet::parse(value).unwrap();
+        assert_eq!(overlay.buffer, 2);
+        assert_eq!(
+            overlay.extmark,
+            ExtmarkId {
+                ns_id: 3,
+                mark_id: 4
+            }
+        );
+        assert_eq!(
+            overlay.kind,
+            OverlayKind::Shape(Shape::Underline {
+                cols: 5,
+                thickness: 2.,
+                color: [1., 0., 0., 1.],
+            })
+        );
+    }
+
+    #[test]
+    fn shapes_need_a_color() {
+        let value = map(vec![
+            ("buffer", 2.into()),
+            ("ns_id", 3.into()),
+            ("mark_id", 4.into()),
+            ("kind", "swatch".into()),
+        ]);
+        assert_eq!(OverlaySet::parse(value), None);
+    }
+
+    #[test]
+    fn overlays_need_a_buffer() {
+        let value = map(vec![
+            ("ns_id", 3.into()),
+            ("mark_id", 4.into()),
+            ("kind", "swatch".into()),
+            ("color", color()),
+        ]);
+        assert_eq!(OverlaySet::parse(value), None);
+    }
+
+    #[test]
+    fn overlays_are_per_buffer() {
+        let mut overlays = Overlays::default();
+        let extmark = ExtmarkId {
+            ns_id: 3,
+            mark_id: 4,
+        };
+        let swatch = || Overlay::Shape(Shape::Swatch { color: [0.; 4] });
+        overlays.set(1, extmark, swatch());
+        overlays.set(2, extmark, swatch());
+        assert!(overlays.remove_buffer(1));
+        assert!(!overlays.remove_buffer(1));
+        assert!(overlays.get(1, extmark).is_none());
+        assert!(overlays.get(2, extmark).is_some());
+        assert!(overlays.remove(2, extmark));
+        assert!(overlays.get(2, extmark).is_none());
+    }
+
+    #[test]
+    fn shapes_are_placed_from_the_cell() {
+        let cell = PixelVec::new(20., 40.);
+        let cell_size = Vec2::new(10., 20.);
+        let swatch = Shape::Swatch { color: [0.; 4] };
+        assert_eq!(
+            swatch.rect(cell, cell_size, 16.),
+            (PixelVec::new(21.5, 46.5), PixelVec::new(28.5, 53.5))
+        );
+        let underline = Shape::Underline {
+            cols: 3,
+            thickness: 2.,
+            color: [0.; 4],
+        };
+        assert_eq!(
+            underline.rect(cell, cell_size, 16.),
+            (PixelVec::new(20., 56.), PixelVec::new(50., 58.))
+        );
+    }
+}
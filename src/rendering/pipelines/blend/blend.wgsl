@@ -3,6 +3,12 @@ var tex: texture_2d<f32>;
 @group(0) @binding(1)
 var tex_sampler: sampler;
 
+struct PushConstants {
+    opacity: f32,
+}
+
+var<push_constant> constants: PushConstants;
+
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) uv: vec2<f32>,
@@ -32,5 +38,5 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
         tex_sampler,
         in.uv,
         0.0
-    );
+    ) * constants.opacity;
 }
@@ -1,9 +1,11 @@
 //! Alpha-blends one texture over another. This is used to combine the results
 //! of the monochrome and lines pipelines with the background cells. These need
 //! to rendered separately so that the alphas of the monochrome and lines can be
-//! used for rendering the cursor.
+//! used for rendering the cursor. It also composites translucent windows,
+//! which are drawn to their own layer, over the rest of the grids.
 
 use crate::rendering::{nearest_sampler, texture::Texture};
+use bytemuck::{Pod, Zeroable, cast_slice};
 use wgpu::include_wgsl;
 
 pub struct Pipeline {
@@ -45,7 +47,10 @@ impl Pipeline {
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Blend pipeline"),
             bind_group_layouts: &[&bind_group_layout],
-            push_constant_ranges: &[],
+            push_constant_ranges: &[wgpu::PushConstantRange {
+                stages: wgpu::ShaderStages::FRAGMENT,
+                range: 0..PushConstants::SIZE,
+            }],
         });
 
         Self {
@@ -63,7 +68,13 @@ impl Pipeline {
         self.bind_group = bind_group(device, &self.bind_group_layout, texture_view, &self.sampler);
     }
 
-    pub fn render(&self, encoder: &mut wgpu::CommandEncoder, color_target: &wgpu::TextureView) {
+    /// Blend the texture over the color target, scaled by the given opacity
+    pub fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        color_target: &wgpu::TextureView,
+        opacity: f32,
+    ) {
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Blend render pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
@@ -81,10 +92,25 @@ impl Pipeline {
 
         render_pass.set_pipeline(&self.pipeline);
         render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_push_constants(
+            wgpu::ShaderStages::FRAGMENT,
+            0,
+            cast_slice(&[PushConstants { opacity }]),
+        );
         render_pass.draw(0..6, 0..1);
     }
 }
 
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, Pod, Zeroable)]
+struct PushConstants {
+    opacity: f32,
+}
+
+impl PushConstants {
+    pub const SIZE: u32 = std::mem::size_of::<Self>() as u32;
+}
+
 fn pipeline(
     device: &wgpu::Device,
     layout: &wgpu::PipelineLayout,
@@ -51,5 +51,7 @@ fn vs_main(
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
+    // Premultiplied so that translucent cells blend over the grids beneath
+    // when their layer is composited
+    return vec4<f32>(in.color.rgb * in.color.a, in.color.a);
 }
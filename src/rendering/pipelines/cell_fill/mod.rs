@@ -117,10 +117,11 @@ impl Pipeline {
             };
             render_pass.set_bind_group(0, Some(*bg_bind_group), &[]);
 
-            if let Some(offset) = grid.offset() {
-                let size = grid.size().into_pixels(cell_size);
+            if let Some(offset) = grid.offset()
+                && let Some((clip_offset, clip_size)) = grid.clip_region(cell_size)
+            {
                 let offset = offset.round_to_pixels(cell_size);
-                set_scissor(size, offset, target_size, &mut render_pass);
+                set_scissor(clip_size, clip_offset, target_size, &mut render_pass);
                 PushConstants {
                     target_size: target_size.try_cast().unwrap(),
                     cell_size: cell_size.try_cast().unwrap(),
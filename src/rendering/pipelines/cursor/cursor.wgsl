@@ -9,6 +9,7 @@ struct FragmentInfo {
     bg: vec3<f32>,
     size: vec2<f32>,
     stretch: f32,
+    opacity: f32,
 }
 
 struct Info {
@@ -69,6 +70,6 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let t = min(1., max(0., info.fragment.stretch / 4.));
     let l = max(0., min(1., mix(l2, l1, t)));
     let color = mix(info.fragment.fg, info.fragment.bg, sample.a);
-    return vec4<f32>(color, l);
+    return vec4<f32>(color, l * info.fragment.opacity);
 }
 
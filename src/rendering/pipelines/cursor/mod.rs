@@ -3,9 +3,14 @@
 //! cursor appears to go underneath.
 
 use crate::{
-    rendering::{Motion, nearest_sampler, texture::Texture},
+    rendering::{
+        Motion,
+        animation::{Animation, Curve},
+        nearest_sampler,
+        texture::Texture,
+    },
     ui::{Ui, cmdline::Mode},
-    util::{IntoSrgb, nice_s_curve},
+    util::IntoSrgb,
 };
 use bytemuck::{Pod, Zeroable, cast_slice};
 use neophyte_linalg::{CellVec, Mat3, PixelVec, Vec2};
@@ -13,7 +18,10 @@ use neophyte_ui_event::{
     mode_info_set::{CursorShape, ModeInfo},
     rgb::Rgb,
 };
-use std::{mem::size_of, time::Duration};
+use std::{
+    mem::size_of,
+    time::{Duration, Instant},
+};
 use wgpu::include_wgsl;
 
 pub struct Pipeline {
@@ -23,7 +31,11 @@ pub struct Pipeline {
     sampler: wgpu::Sampler,
     fragment_push_constants: FragmentPushConstants,
     display_info: Option<DisplayInfo>,
-    speed: f32,
+    curve: Curve,
+    /// Where the cursor is displayed, in pixels
+    position: PixelVec<f32>,
+    /// Whether the window has focus. The cursor stays solid while unfocused.
+    is_focused: bool,
     transform: Mat3,
     show: bool,
 }
@@ -120,7 +132,9 @@ impl Pipeline {
             display_info: None,
             transform: Mat3::IDENTITY,
             show: false,
-            speed: 0.,
+            curve: Curve::default(),
+            position: PixelVec::default(),
+            is_focused: true,
         }
     }
 
@@ -163,7 +177,7 @@ impl Pipeline {
             bg,
             size: cell_size,
             stretch: 0.,
-            padding: 0.,
+            opacity: self.fragment_push_constants.opacity,
         };
 
         self.bind_group = bind_group(
@@ -187,21 +201,28 @@ impl Pipeline {
                 Mode::Normal { levels } => {
                     // We guarantee at least one level if the mode is Some
                     let level = levels.last().unwrap();
-                    let mut pos =
-                        CellVec::new(level.cursor_pos as i64, -(level.content_lines.len() as i64));
+                    // The cursor position is a byte offset into the content
+                    let mut byte_pos = level.cursor_pos as usize;
+                    let mut pos = CellVec::new(0, -(level.content_lines.len() as i64));
                     for line in level.content_lines.iter() {
                         pos.0.y += 1;
-                        let line_len = line
+                        let line: String = line
                             .chunks
                             .iter()
-                            .fold(0, |acc, chunk| acc + chunk.text_chunk.len());
-                        if line_len < pos.0.x as usize {
-                            pos.0.x -= line_len as i64;
+                            .map(|chunk| chunk.text_chunk.as_str())
+                            .collect();
+                        if line.len() < byte_pos {
+                            byte_pos -= line.len();
                         } else {
+                            let mut end = byte_pos;
+                            while !line.is_char_boundary(end) {
+                                end -= 1;
+                            }
+                            pos.0.x = ui.char_width.text_width(&line[..end]) as i64;
                             break;
                         }
                     }
-                    pos.0.x += level.prompt.len() as i64 + 1;
+                    pos.0.x += ui.char_width.text_width(&level.prompt) as i64 + 1;
                     let base = CellVec::new(0, ui.grids[0].contents().size.0.y - 1);
                     pos.cast_as::<f32>() + base.cast_as()
                 }
@@ -228,9 +249,9 @@ impl Pipeline {
             (Some(position), None) => {
                 let position = position.into_pixels(cell_size);
                 Some(DisplayInfo {
-                    start_position: position,
                     target_position: position,
-                    elapsed: Duration::ZERO,
+                    animation: Animation::default(),
+                    blink_start: Instant::now(),
                     fill,
                     cursor_size,
                     blink_rate: BlinkRate::from_mode_info(mode),
@@ -238,20 +259,19 @@ impl Pipeline {
             }
             (Some(position), Some(display_info)) => {
                 let new_target = position.into_pixels(cell_size);
-                let (start_position, elapsed) = if new_target != display_info.target_position {
-                    let current_position = display_info
-                        .start_position
-                        .lerp(display_info.target_position, t(display_info, self.speed));
-                    (current_position, Duration::ZERO)
+                let mut animation = display_info.animation;
+                let blink_start = if new_target != display_info.target_position {
+                    animation.retarget(display_info.target_position - new_target, self.curve);
+                    Instant::now()
                 } else {
-                    (display_info.start_position, display_info.elapsed)
+                    display_info.blink_start
                 };
 
                 let blink_rate = BlinkRate::from_mode_info(mode);
                 Some(DisplayInfo {
-                    start_position,
                     target_position: new_target,
-                    elapsed,
+                    animation,
+                    blink_start,
                     fill,
                     cursor_size,
                     blink_rate,
@@ -260,77 +280,98 @@ impl Pipeline {
         };
     }
 
-    pub fn advance(&mut self, delta_time: Duration, speed: f32, cell_size: Vec2<f32>) -> Motion {
-        self.speed = speed;
+    pub fn advance(
+        &mut self,
+        delta_time: Duration,
+        speed: f32,
+        curve: Curve,
+        blink_fade: Duration,
+        cell_size: Vec2<f32>,
+    ) -> Motion {
+        self.curve = curve;
         let Some(display_info) = self.display_info.as_mut() else {
             return Motion::Still;
         };
 
-        display_info.elapsed += delta_time;
-        let t = t(display_info, speed).min(1.);
-        let current_position = display_info
-            .start_position
-            .lerp(display_info.target_position, t);
+        let is_animating = display_info.animation.advance(delta_time, speed, curve);
+        let offset = display_info.animation.offset(curve);
+        let current_position = display_info.target_position + offset;
+        self.position = current_position;
         self.fragment_push_constants.stretch = 0.;
         self.fragment_push_constants.size = cell_size;
-        let (motion, transform) =
-            if t >= 1.0 || display_info.target_position == display_info.start_position {
-                (Motion::Still, Mat3::IDENTITY)
+        let (motion, transform) = if is_animating {
+            let length = offset.length();
+            let direction = if length < 0.25 {
+                PixelVec::new(1.0, 0.0)
             } else {
-                let toward = display_info.target_position - display_info.start_position;
-                let length = toward.length();
-                let direction = if length < 0.25 {
-                    PixelVec::new(1.0, 0.0)
-                } else {
-                    toward / length
-                };
-                let angle = f32::atan2(direction.0.x, direction.0.y);
-
-                let cell_diagonal = cell_size.length();
-                let dir = direction * (1. - t);
-                let translate = PixelVec::splat(0.5) - dir / cell_diagonal * 4.;
-                let transform = Mat3::translate(translate.0)
-                    * Mat3::rotate(-angle)
-                    * Mat3::scale(Vec2::new(1.0, length * (1. - t) / 2.))
-                    * Mat3::rotate(angle)
-                    * Mat3::translate(-translate.0);
-
-                self.fragment_push_constants.stretch = (1. - t) * length / cell_diagonal;
-                (Motion::Animating, transform)
+                -offset / length
             };
+            let angle = f32::atan2(direction.0.x, direction.0.y);
+
+            let cell_diagonal = cell_size.length();
+            let dir = direction * display_info.animation.remaining(curve);
+            let translate = PixelVec::splat(0.5) - dir / cell_diagonal * 4.;
+            let transform = Mat3::translate(translate.0)
+                * Mat3::rotate(-angle)
+                * Mat3::scale(Vec2::new(1.0, length / 2.))
+                * Mat3::rotate(angle)
+                * Mat3::translate(-translate.0);
+
+            self.fragment_push_constants.stretch = length / cell_diagonal;
+            (Motion::Animating, transform)
+        } else {
+            (Motion::Still, Mat3::IDENTITY)
+        };
 
         self.transform = Mat3::translate(current_position.0) * transform;
 
-        let (motion, show) = if let Some(blink_rate) = display_info.blink_rate {
-            let wait = Duration::from_millis(blink_rate.wait as u64);
-            if display_info.elapsed < wait {
-                let until_cycle = wait - display_info.elapsed;
-                (motion.soonest(Motion::Delay(until_cycle)), true)
-            } else {
-                let since_wait = (display_info.elapsed - wait).as_millis();
-                let on = blink_rate.on as u128;
-                let off = blink_rate.off as u128;
-                let cycle_duration = on + off;
-                let cycle_time = since_wait % cycle_duration;
-                if cycle_time < on {
-                    let until_off: u64 = (on - cycle_time).try_into().unwrap_or(0);
-                    let until_off = Duration::from_millis(until_off);
-                    (motion.soonest(Motion::Delay(until_off)), true)
-                } else {
-                    let off_time = cycle_time - on;
-                    let until_on: u64 = (off - off_time).try_into().unwrap_or(0);
-                    let until_on = Duration::from_millis(until_on);
-                    (motion.soonest(Motion::Delay(until_on)), false)
-                }
+        let (opacity, blink_motion) = match display_info.blink_rate {
+            Some(blink_rate) if self.is_focused => {
+                blink_rate.opacity(display_info.blink_start.elapsed(), blink_fade)
             }
-        } else {
-            (motion, true)
+            _ => (1., Motion::Still),
         };
-        self.show = show;
+        let motion = motion.soonest(blink_motion);
+        self.fragment_push_constants.opacity = opacity;
+        self.show = opacity > 0.;
 
         motion
     }
 
+    /// Restart the blink cycle with the cursor showing, such as after a
+    /// keystroke
+    pub fn reset_blink(&mut self) {
+        if let Some(display_info) = self.display_info.as_mut() {
+            display_info.blink_start = Instant::now();
+        }
+    }
+
+    /// Blinking pauses while the window is unfocused and restarts when it
+    /// regains focus
+    pub fn set_focused(&mut self, is_focused: bool) {
+        self.is_focused = is_focused;
+        self.reset_blink();
+    }
+
+    /// The corners of the cursor where it is displayed, in pixels, clockwise
+    /// from the top left
+    pub fn corners(&self) -> Option<[PixelVec<f32>; 4]> {
+        let display_info = self.display_info.as_ref()?;
+        let size = display_info.cursor_size * display_info.fill;
+        let top_left = self.position + PixelVec::new(0., display_info.cursor_size.y - size.y);
+        Some([
+            top_left,
+            top_left + PixelVec::new(size.x, 0.),
+            top_left + PixelVec(size),
+            top_left + PixelVec::new(0., size.y),
+        ])
+    }
+
+    /// The fill color of the cursor
+    pub fn color(&self) -> [f32; 4] {
+        self.fragment_push_constants.fg
+    }
+
     pub fn render(
         &mut self,
         encoder: &mut wgpu::CommandEncoder,
@@ -405,9 +446,11 @@ fn bind_group(
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 struct DisplayInfo {
-    start_position: PixelVec<f32>,
     target_position: PixelVec<f32>,
-    elapsed: Duration,
+    /// Offset of the displayed position from the target
+    animation: Animation<PixelVec<f32>>,
+    /// When the blink cycle started, which is reset when the cursor moves
+    blink_start: Instant,
     fill: Vec2<f32>,
     cursor_size: Vec2<f32>,
     blink_rate: Option<BlinkRate>,
@@ -433,7 +476,7 @@ struct FragmentPushConstants {
     bg: [f32; 4],
     size: Vec2<f32>,
     stretch: f32,
-    padding: f32,
+    opacity: f32,
 }
 
 impl VertexPushConstants {
@@ -445,15 +488,6 @@ pub enum CursorKind {
     Cmdline,
 }
 
-fn t(display_info: &DisplayInfo, speed: f32) -> f32 {
-    let length = (display_info.target_position - display_info.start_position).length();
-    if length < 0.25 {
-        1.0
-    } else {
-        nice_s_curve(display_info.elapsed.as_secs_f32() * speed, length)
-    }
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct BlinkRate {
     on: u32,
@@ -470,4 +504,79 @@ impl BlinkRate {
             _ => None,
         }
     }
+
+    /// The cursor opacity at the given time since the blink cycle started,
+    /// along with when it next changes. The cursor fades out over the end of
+    /// the on period and back in over the end of the off period, or toggles
+    /// if the fade is zero.
+    fn opacity(self, since_start: Duration, fade: Duration) -> (f32, Motion) {
+        let ms = |ms: u32| Duration::from_millis(ms.into());
+        let (wait, on, off) = (ms(self.wait), ms(self.on), ms(self.off));
+        if since_start < wait {
+            return (1., Motion::Delay(wait - since_start));
+        }
+
+        let cycle = on + off;
+        let t = (since_start - wait).as_nanos() % cycle.as_nanos();
+        let t = Duration::from_nanos(t.try_into().unwrap_or(0));
+        let fade_out_start = on - fade.min(on);
+        let fade_in_start = cycle - fade.min(off);
+        let progress = |start: Duration, end: Duration| {
+            (t - start).as_secs_f32() / (end - start).as_secs_f32()
+        };
+        if t < fade_out_start {
+            (1., Motion::Delay(fade_out_start - t))
+        } else if t < on {
+            (1. - progress(fade_out_start, on), Motion::Animating)
+        } else if t < fade_in_start {
+            (0., Motion::Delay(fade_in_start - t))
+        } else {
+            (progress(fade_in_start, cycle), Motion::Animating)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLINK: BlinkRate = BlinkRate {
+        wait: 700,
+        on: 400,
+        off: 250,
+    };
+
+    #[test]
+    fn blink_toggles_without_fade() {
+        let ms = Duration::from_millis;
+        assert_eq!(
+            BLINK.opacity(ms(100), Duration::ZERO),
+            (1., Motion::Delay(ms(600)))
+        );
+        assert_eq!(
+            BLINK.opacity(ms(1000), Duration::ZERO),
+            (1., Motion::Delay(ms(100)))
+        );
+        assert_eq!(
+            BLINK.opacity(ms(1200), Duration::ZERO),
+            (0., Motion::Delay(ms(150)))
+        );
+        assert_eq!(
+            BLINK.opacity(ms(1400), Duration::ZERO),
+            (1., Motion::Delay(ms(350)))
+        );
+    }
+
+    #[test]
+    fn blink_fades_at_the_end_of_each_phase() {
+        let ms = Duration::from_millis;
+        let fade = ms(100);
+        assert_eq!(BLINK.opacity(ms(950), fade), (1., Motion::Delay(ms(50))));
+        let (opacity, motion) = BLINK.opacity(ms(1050), fade);
+        assert!((opacity - 0.5).abs() < 1e-4);
+        assert_eq!(motion, Motion::Animating);
+        assert_eq!(BLINK.opacity(ms(1150), fade), (0., Motion::Delay(ms(100))));
+        let (opacity, _) = BLINK.opacity(ms(1325), fade);
+        assert!((opacity - 0.75).abs() < 1e-4);
+    }
 }
@@ -0,0 +1,43 @@
+struct Quad {
+    corners: array<vec2<f32>, 4>,
+    color: vec4<f32>,
+}
+
+struct Info {
+    target_size: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var<storage, read> quads: array<Quad>;
+var<push_constant> info: Info;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) in_vertex_index: u32,
+) -> VertexOutput {
+    let quad = quads[in_vertex_index / 6u];
+    // Two triangles, (0, 1, 2) and (0, 2, 3)
+    var corner_indices = array<u32, 6>(0u, 1u, 2u, 0u, 2u, 3u);
+    let corner = quad.corners[corner_indices[in_vertex_index % 6u]];
+
+    var out: VertexOutput;
+    out.color = quad.color;
+    out.clip_position = vec4<f32>(
+        corner / info.target_size
+        * vec2<f32>(2.0, -2.0)
+        + vec2<f32>(-1.0, 1.0),
+        0.0,
+        1.0
+    );
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
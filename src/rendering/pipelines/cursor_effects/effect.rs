@@ -0,0 +1,405 @@
+use bytemuck::{Pod, Zeroable};
+use neophyte_linalg::{PixelVec, Vec2};
+use neophyte_ui_event::{MaybeInto, Parse};
+use std::time::Duration;
+
+/// A visual effect drawn over the text and beneath the cursor while it moves
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CursorEffect {
+    #[default]
+    None,
+    /// Stretches the cursor between its old and new positions. The corners
+    /// leading the motion settle in `leading` seconds and the trailing ones in
+    /// `trailing` seconds.
+    Smear { leading: f32, trailing: f32 },
+    /// Leaves copies of the cursor along its path that start at the given
+    /// opacity and fade out over `duration` seconds
+    Trail { duration: f32, opacity: f32 },
+    /// Sheds `count` particles per cell traveled that drift at `speed` cells
+    /// per second and fade out over `duration` seconds
+    Particles {
+        duration: f32,
+        count: f32,
+        speed: f32,
+    },
+}
+
+impl Parse for CursorEffect {
+    fn parse(value: rmpv::Value) -> Option<Self> {
+        let rmpv::Value::Map(map) = value else {
+            return None;
+        };
+        let mut kind: Option<String> = None;
+        let mut leading: f32 = 0.05;
+        let mut trailing: f32 = 0.2;
+        let mut duration: Option<f32> = None;
+        let mut opacity: f32 = 0.5;
+        let mut count: f32 = 4.;
+        let mut speed: f32 = 2.;
+        for (k, v) in map {
+            match k.as_str()? {
+                "kind" => kind = Some(v.maybe_into()?),
+                "leading" => leading = v.maybe_into()?,
+                "trailing" => trailing = v.maybe_into()?,
+                "duration" => duration = Some(v.maybe_into()?),
+                "opacity" => opacity = v.maybe_into()?,
+                "count" => count = v.maybe_into()?,
+                "speed" => speed = v.maybe_into()?,
+                _ => {}
+            }
+        }
+        let effect = match kind?.as_str() {
+            "none" => Self::None,
+            "smear" => Self::Smear { leading, trailing },
+            "trail" => Self::Trail {
+                duration: duration.unwrap_or(0.25),
+                opacity,
+            },
+            "particles" => Self::Particles {
+                duration: duration.unwrap_or(0.5),
+                count,
+                speed,
+            },
+            _ => return None,
+        };
+        let is_valid = match effect {
+            Self::None => true,
+            Self::Smear { leading, trailing } => is_positive(leading) && is_positive(trailing),
+            Self::Trail { duration, opacity } => {
+                is_positive(duration) && (0. ..=1.).contains(&opacity)
+            }
+            Self::Particles {
+                duration,
+                count,
+                speed,
+            } => {
+                is_positive(duration)
+                    && count.is_finite()
+                    && count >= 0.
+                    && speed.is_finite()
+                    && speed >= 0.
+            }
+        };
+        is_valid.then_some(effect)
+    }
+}
+
+/// Whether the value is finite and greater than zero
+fn is_positive(n: f32) -> bool {
+    n.is_finite() && n > 0.
+}
+
+/// The corners of a quad in pixels, clockwise from the top left
+pub type Corners = [PixelVec<f32>; 4];
+
+/// A filled quad as it is laid out for the shader
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, Pod, Zeroable)]
+pub struct Quad {
+    pub corners: [Vec2<f32>; 4],
+    pub color: [f32; 4],
+}
+
+impl Quad {
+    fn new(corners: Corners, color: [f32; 4]) -> Self {
+        Self {
+            corners: corners.map(|corner| corner.0),
+            color,
+        }
+    }
+}
+
+/// Squared distance in pixels within which smear corners snap to the cursor
+const SMEAR_SNAP: f32 = 0.25;
+/// Particles are dropped beyond this many to bound the cost of a long jump
+const MAX_PARTICLES: usize = 512;
+
+/// Simulates the cursor effect from frame to frame
+#[derive(Debug, Clone, Default)]
+pub struct Effects {
+    effect: CursorEffect,
+    /// The cursor corners as of the previous frame
+    last: Option<Corners>,
+    /// The smeared corners, which trail the cursor corners
+    smear: Option<Corners>,
+    trail: Vec<Fading>,
+    particles: Vec<Particle>,
+    rng: Rng,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Fading {
+    corners: Corners,
+    age: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Particle {
+    position: PixelVec<f32>,
+    velocity: PixelVec<f32>,
+    age: f32,
+}
+
+impl Effects {
+    /// Advance the effect by the given time with the cursor at the given
+    /// corners. Returns whether the effect is still in progress.
+    pub fn advance(
+        &mut self,
+        delta_time: Duration,
+        effect: CursorEffect,
+        cursor: Option<Corners>,
+        cell_size: Vec2<f32>,
+    ) -> bool {
+        if effect != self.effect {
+            *self = Self {
+                effect,
+                ..Self::default()
+            };
+        }
+        let dt = delta_time.as_secs_f32();
+        let last = self.last;
+        self.last = cursor;
+        let Some(cursor) = cursor else {
+            self.smear = None;
+            self.trail.clear();
+            self.particles.clear();
+            return false;
+        };
+        let moved = last.filter(|&last| last != cursor);
+
+        match effect {
+            CursorEffect::None => false,
+
+            CursorEffect::Smear { leading, trailing } => {
+                let smear = self.smear.get_or_insert(cursor);
+                let direction = center(cursor) - center(*smear);
+                let mut is_settled = true;
+                for (corner, target) in smear.iter_mut().zip(cursor) {
+                    let duration = if is_leading(target, cursor, direction) {
+                        leading
+                    } else {
+                        trailing
+                    };
+                    // Settles to within 2% of the distance in the duration
+                    let k = 1. - (-4. * dt / duration).exp();
+                    *corner += (target - *corner) * k;
+                    if (target - *corner).length_squared() < SMEAR_SNAP {
+                        *corner = target;
+                    } else {
+                        is_settled = false;
+                    }
+                }
+                !is_settled
+            }
+
+            CursorEffect::Trail { duration, .. } => {
+                for fading in self.trail.iter_mut() {
+                    fading.age += dt;
+                }
+                self.trail.retain(|fading| fading.age < duration);
+                if let Some(last) = moved {
+                    self.trail.push(Fading {
+                        corners: sweep(last, cursor),
+                        age: 0.,
+                    });
+                }
+                !self.trail.is_empty()
+            }
+
+            CursorEffect::Particles {
+                duration,
+                count,
+                speed,
+            } => {
+                let drag = (-2. * dt).exp();
+                for particle in self.particles.iter_mut() {
+                    particle.age += dt;
+                    particle.position += particle.velocity * dt;
+                    particle.velocity *= drag;
+                }
+                self.particles.retain(|particle| particle.age < duration);
+
+                if let Some(last) = moved {
+                    let from = center(last);
+                    let to = center(cursor);
+                    let size = cursor[2] - cursor[0];
+                    let cells = (to - from).length() / cell_size.y;
+                    let spawn = ((cells * count).round() as usize)
+                        .min(MAX_PARTICLES.saturating_sub(self.particles.len()));
+                    for _ in 0..spawn {
+                        let along = from + (to - from) * self.rng.next();
+                        let jitter = PixelVec::new(self.rng.next() - 0.5, self.rng.next() - 0.5);
+                        let angle = self.rng.next() * std::f32::consts::TAU;
+                        let magnitude = speed * cell_size.y * (0.5 + self.rng.next() / 2.);
+                        self.particles.push(Particle {
+                            position: along + PixelVec(jitter.0 * size.0),
+                            velocity: PixelVec::new(angle.cos(), angle.sin()) * magnitude,
+                            age: 0.,
+                        });
+                    }
+                }
+                !self.particles.is_empty()
+            }
+        }
+    }
+
+    /// The quads to draw for the current state of the effect
+    pub fn quads(&self, color: [f32; 4], cell_size: Vec2<f32>) -> Vec<Quad> {
+        let with_alpha = |alpha: f32| [color[0], color[1], color[2], color[3] * alpha];
+        match self.effect {
+            CursorEffect::None => vec![],
+
+            CursorEffect::Smear { .. } => self
+                .smear
+                .filter(|&smear| Some(smear) != self.last)
+                .map(|smear| Quad::new(smear, color))
+                .into_iter()
+                .collect(),
+
+            CursorEffect::Trail { duration, opacity } => self
+                .trail
+                .iter()
+                .map(|fading| {
+                    let alpha = opacity * (1. - fading.age / duration);
+                    Quad::new(fading.corners, with_alpha(alpha))
+                })
+                .collect(),
+
+            CursorEffect::Particles { duration, .. } => self
+                .particles
+                .iter()
+                .map(|particle| {
+                    let life = 1. - particle.age / duration;
+                    let half = cell_size.y / 16. * (0.5 + life / 2.);
+                    let p = particle.position;
+                    let corners = [
+                        p + PixelVec::new(-half, -half),
+                        p + PixelVec::new(half, -half),
+                        p + PixelVec::new(half, half),
+                        p + PixelVec::new(-half, half),
+                    ];
+                    Quad::new(corners, with_alpha(life))
+                })
+                .collect(),
+        }
+    }
+}
+
+fn center(corners: Corners) -> PixelVec<f32> {
+    (corners[0] + corners[2]) * 0.5
+}
+
+/// Whether the corner is on the side of the quad facing the direction
+fn is_leading(corner: PixelVec<f32>, corners: Corners, direction: PixelVec<f32>) -> bool {
+    let toward = corner - center(corners);
+    toward.0.x * direction.0.x + toward.0.y * direction.0.y > 0.
+}
+
+/// A quad covering the area swept moving from one position to another, taking
+/// the leading corners from the destination and the rest from the origin
+fn sweep(from: Corners, to: Corners) -> Corners {
+    let direction = center(to) - center(from);
+    std::array::from_fn(|i| {
+        if is_leading(to[i], to, direction) {
+            to[i]
+        } else {
+            from[i]
+        }
+    })
+}
+
+/// A small xorshift generator for particle placement
+#[derive(Debug, Clone, Copy)]
+struct Rng(u32);
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self(0x9E37_79B9)
+    }
+}
+
+impl Rng {
+    /// A number in [0, 1)
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 8) as f32 / (1 << 24) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(x: f32) -> Corners {
+        [
+            PixelVec::new(x, 0.),
+            PixelVec::new(x + 10., 0.),
+            PixelVec::new(x + 10., 20.),
+            PixelVec::new(x, 20.),
+        ]
+    }
+
+    /// Effects must end once the cursor stops so that redrawing can stop
+    #[test]
+    fn effects_end_after_the_cursor_stops() {
+        let cell_size = Vec2::new(10., 20.);
+        let frame = Duration::from_millis(16);
+        for effect in [
+            CursorEffect::Smear {
+                leading: 0.05,
+                trailing: 0.2,
+            },
+            CursorEffect::Trail {
+                duration: 0.25,
+                opacity: 0.5,
+            },
+            CursorEffect::Particles {
+                duration: 0.5,
+                count: 4.,
+                speed: 2.,
+            },
+        ] {
+            let mut effects = Effects::default();
+            effects.advance(frame, effect, Some(cell(0.)), cell_size);
+            assert!(effects.advance(frame, effect, Some(cell(100.)), cell_size));
+            assert!(!effects.quads([1.; 4], cell_size).is_empty(), "{effect:?}");
+
+            let mut frames = 0;
+            while effects.advance(frame, effect, Some(cell(100.)), cell_size) {
+                frames += 1;
+                assert!(frames < 100, "{effect:?} did not end");
+            }
+            assert!(effects.quads([1.; 4], cell_size).is_empty(), "{effect:?}");
+        }
+    }
+
+    #[test]
+    fn sweep_covers_both_positions() {
+        let swept = sweep(cell(0.), cell(100.));
+        assert_eq!(swept[0], PixelVec::new(0., 0.));
+        assert_eq!(swept[1], PixelVec::new(110., 0.));
+        assert_eq!(swept[2], PixelVec::new(110., 20.));
+        assert_eq!(swept[3], PixelVec::new(0., 20.));
+    }
+
+    #[test]
+    fn rejects_non_finite_settings() {
+        let parse = |kind: &str, key: &str, n: f64| {
+            CursorEffect::parse(rmpv::Value::Map(vec![
+                ("kind".into(), kind.into()),
+                (key.into(), n.into()),
+            ]))
+        };
+        assert!(parse("smear", "leading", 0.1).is_some());
+        for n in [f64::INFINITY, f64::NAN] {
+            assert_eq!(parse("smear", "leading", n), None);
+            assert_eq!(parse("smear", "trailing", n), None);
+            assert_eq!(parse("trail", "duration", n), None);
+            assert_eq!(parse("particles", "duration", n), None);
+            assert_eq!(parse("particles", "count", n), None);
+            assert_eq!(parse("particles", "speed", n), None);
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Draws effects over the text and beneath the cursor while it moves, such as
+//! smears, trails, and particles. The effects are simulated on the CPU and
+//! drawn as a list of quads.
+
+mod effect;
+
+pub use effect::CursorEffect;
+
+use crate::rendering::{Motion, texture::Texture};
+use bytemuck::{Pod, Zeroable, cast_slice};
+use effect::{Corners, Effects, Quad};
+use neophyte_linalg::{PixelVec, Vec2};
+use std::{mem::size_of, num::NonZeroU64, time::Duration};
+use wgpu::include_wgsl;
+
+pub struct Pipeline {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    buffer: Option<wgpu::Buffer>,
+    buffer_capacity: u64,
+    bind_group: Option<wgpu::BindGroup>,
+    effects: Effects,
+    quads: Vec<Quad>,
+}
+
+impl Pipeline {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let shader = device.create_shader_module(include_wgsl!("cursor_effects.wgsl"));
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Cursor effects bind group layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Cursor effects pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[wgpu::PushConstantRange {
+                stages: wgpu::ShaderStages::VERTEX,
+                range: 0..PushConstants::SIZE,
+            }],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Cursor effects render pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: Texture::LINEAR_FORMAT,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+            buffer: None,
+            buffer_capacity: 0,
+            bind_group: None,
+            effects: Effects::default(),
+            quads: vec![],
+        }
+    }
+
+    /// Advance the effect with the cursor at the given corners, in pixels
+    pub fn advance(
+        &mut self,
+        delta_time: Duration,
+        effect: CursorEffect,
+        cursor: Option<Corners>,
+        color: [f32; 4],
+        cell_size: Vec2<f32>,
+    ) -> Motion {
+        let is_animating = self.effects.advance(delta_time, effect, cursor, cell_size);
+        self.quads = self.effects.quads(color, cell_size);
+        if is_animating {
+            Motion::Animating
+        } else {
+            Motion::Still
+        }
+    }
+
+    /// Upload the quads for the current frame
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let quads: &[u8] = cast_slice(&self.quads);
+        let len = quads.len() as u64;
+        self.bind_group = None;
+        let Some(size) = NonZeroU64::new(len) else {
+            return;
+        };
+
+        if len > self.buffer_capacity {
+            self.buffer_capacity = len * 2;
+            self.buffer = Some(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Cursor effects buffer"),
+                size: self.buffer_capacity,
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }));
+        }
+
+        let Some(buffer) = self.buffer.as_ref() else {
+            return;
+        };
+        queue.write_buffer(buffer, 0, quads);
+        self.bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Cursor effects bind group"),
+            layout: &self.bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer,
+                    offset: 0,
+                    size: Some(size),
+                }),
+            }],
+        }));
+    }
+
+    pub fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        color_target: &wgpu::TextureView,
+        target_size: PixelVec<f32>,
+    ) {
+        let Some(bind_group) = &self.bind_group else {
+            return;
+        };
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Cursor effects render pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: color_target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_push_constants(
+            wgpu::ShaderStages::VERTEX,
+            0,
+            cast_slice(&[PushConstants { target_size }]),
+        );
+        render_pass.set_bind_group(0, bind_group, &[]);
+        let count: u32 = self.quads.len().try_into().unwrap();
+        render_pass.draw(0..count * 6, 0..1);
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, Pod, Zeroable)]
+struct PushConstants {
+    target_size: PixelVec<f32>,
+}
+
+impl PushConstants {
+    pub const SIZE: u32 = size_of::<Self>() as u32;
+}
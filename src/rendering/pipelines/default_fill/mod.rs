@@ -87,26 +87,35 @@ impl Pipeline {
         target_size: PixelVec<u32>,
         cell_size: Vec2<u32>,
         clear_color: [f32; 4],
+        clear: bool,
     ) {
+        let (color_load_op, depth_load_op) = if clear {
+            (
+                wgpu::LoadOp::Clear(wgpu::Color {
+                    r: (clear_color[0] as f64).powf(2.2),
+                    g: (clear_color[1] as f64).powf(2.2),
+                    b: (clear_color[2] as f64).powf(2.2),
+                    a: (clear_color[3] as f64).powf(2.2),
+                }),
+                wgpu::LoadOp::Clear(1.0),
+            )
+        } else {
+            (wgpu::LoadOp::Load, wgpu::LoadOp::Load)
+        };
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Default fill render pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                 view: color_target,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color {
-                        r: (clear_color[0] as f64).powf(2.2),
-                        g: (clear_color[1] as f64).powf(2.2),
-                        b: (clear_color[2] as f64).powf(2.2),
-                        a: (clear_color[3] as f64).powf(2.2),
-                    }),
+                    load: color_load_op,
                     store: wgpu::StoreOp::Store,
                 },
             })],
             depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
                 view: depth_target,
                 depth_ops: Some(wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(1.0),
+                    load: depth_load_op,
                     store: wgpu::StoreOp::Store,
                 }),
                 stencil_ops: None,
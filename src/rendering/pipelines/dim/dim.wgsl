@@ -0,0 +1,42 @@
+struct PushConstants {
+    target_size: vec2<f32>,
+    rect_min: vec2<f32>,
+    rect_max: vec2<f32>,
+    amount: f32,
+    z: f32,
+    radius: f32,
+}
+
+var<push_constant> constants: PushConstants;
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) in_vertex_index: u32,
+) -> @builtin(position) vec4<f32> {
+    let corner = vec2<f32>(
+        f32(in_vertex_index % 2u),
+        f32(((in_vertex_index + 5u) % 6u) / 3u),
+    );
+    let pixel = mix(constants.rect_min, constants.rect_max, corner);
+    return vec4<f32>(
+        pixel / constants.target_size * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0),
+        constants.z,
+        1.0,
+    );
+}
+
+// Signed distance from the edge of the window, negative inside
+fn rounded_rect(p: vec2<f32>, lo: vec2<f32>, hi: vec2<f32>) -> f32 {
+    let half = (hi - lo) * 0.5;
+    let radius = min(constants.radius, min(half.x, half.y));
+    let q = abs(p - (lo + hi) * 0.5) - half + radius;
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    // Leave the corners of rounded floats alone, like the float decoration
+    let d = rounded_rect(position.xy, constants.rect_min, constants.rect_max);
+    let coverage = clamp(0.5 - d, 0.0, 1.0);
+    return vec4<f32>(0.0, 0.0, 0.0, constants.amount * coverage);
+}
@@ -0,0 +1,152 @@
+//! Darkens windows that do not have the cursor. Each window is covered by a
+//! translucent black quad at the depth of the window so that windows in front
+//! of it are not affected.
+
+use crate::rendering::texture::Texture;
+use bytemuck::{Pod, Zeroable, cast_slice};
+use neophyte_linalg::PixelVec;
+use std::mem::size_of;
+use wgpu::include_wgsl;
+
+pub struct Pipeline {
+    pipeline: wgpu::RenderPipeline,
+}
+
+/// A window to darken
+#[derive(Debug, Clone, Copy)]
+pub struct Dimmed {
+    pub offset: PixelVec<i32>,
+    pub size: PixelVec<u32>,
+    pub z: f32,
+    /// The opacity of the black drawn over the window
+    pub amount: f32,
+    /// The corner radius of the window, in pixels
+    pub radius: f32,
+}
+
+impl Pipeline {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let shader = device.create_shader_module(include_wgsl!("dim.wgsl"));
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Dim pipeline layout"),
+            bind_group_layouts: &[],
+            push_constant_ranges: &[wgpu::PushConstantRange {
+                stages: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                range: 0..PushConstants::SIZE,
+            }],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Dim render pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: Texture::LINEAR_FORMAT,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::COLOR,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        Self { pipeline }
+    }
+
+    pub fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        windows: impl Iterator<Item = Dimmed>,
+        color_target: &wgpu::TextureView,
+        depth_target: &wgpu::TextureView,
+        target_size: PixelVec<u32>,
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Dim render pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: color_target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth_target,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        for window in windows {
+            let rect_min = window.offset.cast_as::<f32>();
+            render_pass.set_push_constants(
+                wgpu::ShaderStages::VERTEX_FRAGMENT,
+                0,
+                cast_slice(&[PushConstants {
+                    target_size: target_size.cast_as(),
+                    rect_min,
+                    rect_max: rect_min + window.size.cast_as(),
+                    amount: window.amount,
+                    z: window.z,
+                    radius: window.radius,
+                    _padding: 0.,
+                }]),
+            );
+            render_pass.draw(0..6, 0..1);
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, Pod, Zeroable)]
+struct PushConstants {
+    target_size: PixelVec<f32>,
+    rect_min: PixelVec<f32>,
+    rect_max: PixelVec<f32>,
+    amount: f32,
+    z: f32,
+    radius: f32,
+    _padding: f32,
+}
+
+impl PushConstants {
+    pub const SIZE: u32 = size_of::<Self>() as u32;
+}
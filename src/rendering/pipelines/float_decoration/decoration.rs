@@ -0,0 +1,147 @@
+use crate::util::{IntoSrgb, parse_color};
+use neophyte_linalg::PixelVec;
+use neophyte_ui_event::{MaybeInto, Parse, rgb::Rgb};
+
+/// Decorations drawn around floating windows
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FloatDecoration {
+    /// Radius of the rounded corners in pixels
+    pub radius: f32,
+    pub shadow: Option<Shadow>,
+    pub outline: Option<Outline>,
+}
+
+impl FloatDecoration {
+    /// Whether any decoration is drawn
+    pub fn is_enabled(&self) -> bool {
+        self.radius > 0. || self.shadow.is_some() || self.outline.is_some()
+    }
+}
+
+impl Parse for FloatDecoration {
+    fn parse(value: rmpv::Value) -> Option<Self> {
+        let rmpv::Value::Map(map) = value else {
+            return None;
+        };
+        let mut out = Self::default();
+        for (k, v) in map {
+            match k.as_str()? {
+                "radius" => out.radius = v.maybe_into()?,
+                "shadow" => out.shadow = Some(v.maybe_into()?),
+                "outline" => out.outline = Some(v.maybe_into()?),
+                _ => {}
+            }
+        }
+        (out.radius >= 0.).then_some(out)
+    }
+}
+
+/// A soft shadow cast beneath a floating window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shadow {
+    /// Pixels over which the shadow fades out
+    pub blur: f32,
+    /// Pixels that the shadow is offset from the window
+    pub offset: PixelVec<f32>,
+    /// Color with straight alpha
+    pub color: [f32; 4],
+}
+
+impl Default for Shadow {
+    fn default() -> Self {
+        Self {
+            blur: 12.,
+            offset: PixelVec::new(0., 4.),
+            color: [0., 0., 0., 0.4],
+        }
+    }
+}
+
+impl Parse for Shadow {
+    fn parse(value: rmpv::Value) -> Option<Self> {
+        let rmpv::Value::Map(map) = value else {
+            return None;
+        };
+        let mut out = Self::default();
+        for (k, v) in map {
+            match k.as_str()? {
+                "blur" => out.blur = v.maybe_into()?,
+                "offset_x" => out.offset.0.x = v.maybe_into()?,
+                "offset_y" => out.offset.0.y = v.maybe_into()?,
+                "color" => out.color = parse_color(v)?,
+                _ => {}
+            }
+        }
+        (out.blur >= 0.).then_some(out)
+    }
+}
+
+/// A line drawn along the edge of a floating window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Outline {
+    /// Width of the line in pixels
+    pub width: f32,
+    /// Color with straight alpha
+    pub color: [f32; 4],
+}
+
+impl Default for Outline {
+    fn default() -> Self {
+        Self {
+            width: 1.,
+            color: Rgb::new(128, 128, 128).into_srgb(1.),
+        }
+    }
+}
+
+impl Parse for Outline {
+    fn parse(value: rmpv::Value) -> Option<Self> {
+        let rmpv::Value::Map(map) = value else {
+            return None;
+        };
+        let mut out = Self::default();
+        for (k, v) in map {
+            match k.as_str()? {
+                "width" => out.width = v.maybe_into()?,
+                "color" => out.color = parse_color(v)?,
+                _ => {}
+            }
+        }
+        (out.width > 0.).then_some(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmpv::Value;
+
+    fn map(entries: Vec<(&str, Value)>) -> Value {
+        Value::Map(entries.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+
+    #[test]
+    fn parses_partial_decoration_with_defaults() {
+        let value = map(vec![
+            ("radius", 6.into()),
+            ("shadow", map(vec![("offset_y", 8.into())])),
+        ]);
+        let decoration = FloatDecoration::parse(value).unwrap();
+        assert_eq!(decoration.radius, 6.);
+        assert_eq!(
+            decoration.shadow,
+            Some(Shadow {
+                offset: PixelVec::new(0., 8.),
+                ..Shadow::default()
+            })
+        );
+        assert_eq!(decoration.outline, None);
+        assert!(decoration.is_enabled());
+    }
+
+    #[test]
+    fn empty_decoration_is_disabled() {
+        let decoration = FloatDecoration::parse(map(vec![])).unwrap();
+        assert!(!decoration.is_enabled());
+    }
+}
@@ -0,0 +1,81 @@
+struct PushConstants {
+    target_size: vec2<f32>,
+    rect_min: vec2<f32>,
+    rect_max: vec2<f32>,
+    shadow_offset: vec2<f32>,
+    color: vec4<f32>,
+    radius: f32,
+    blur: f32,
+    outline_width: f32,
+    z: f32,
+}
+
+var<push_constant> constants: PushConstants;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) pixel: vec2<f32>,
+}
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) in_vertex_index: u32,
+) -> VertexOutput {
+    let corner = vec2<f32>(
+        f32(in_vertex_index % 2u),
+        f32(((in_vertex_index + 5u) % 6u) / 3u),
+    );
+    let margin = constants.blur
+        + abs(constants.shadow_offset.x)
+        + abs(constants.shadow_offset.y)
+        + constants.outline_width
+        + 1.0;
+    let lo = constants.rect_min - margin;
+    let hi = constants.rect_max + margin;
+    let pixel = lo + (hi - lo) * corner;
+
+    var out: VertexOutput;
+    out.pixel = pixel;
+    out.clip_position = vec4<f32>(
+        pixel / constants.target_size * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0),
+        constants.z,
+        1.0,
+    );
+    return out;
+}
+
+// Signed distance from the edge of the window, negative inside
+fn rounded_rect(p: vec2<f32>, lo: vec2<f32>, hi: vec2<f32>) -> f32 {
+    let half = (hi - lo) * 0.5;
+    let radius = min(constants.radius, min(half.x, half.y));
+    let q = abs(p - (lo + hi) * 0.5) - half + radius;
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+fn premultiply(alpha: f32) -> vec4<f32> {
+    let a = constants.color.a * alpha;
+    return vec4<f32>(constants.color.rgb * a, a);
+}
+
+@fragment
+fn fs_shadow(in: VertexOutput) -> @location(0) vec4<f32> {
+    let p = in.pixel - constants.shadow_offset;
+    let d = rounded_rect(p, constants.rect_min, constants.rect_max);
+    let blur = max(constants.blur, 1.0);
+    return premultiply(1.0 - smoothstep(-blur * 0.5, blur * 0.5, d));
+}
+
+// Multiplied with the window layer to cut away the corners
+@fragment
+fn fs_mask(in: VertexOutput) -> @location(0) vec4<f32> {
+    let d = rounded_rect(in.pixel, constants.rect_min, constants.rect_max);
+    return vec4<f32>(clamp(0.5 - d, 0.0, 1.0));
+}
+
+@fragment
+fn fs_outline(in: VertexOutput) -> @location(0) vec4<f32> {
+    let d = rounded_rect(in.pixel, constants.rect_min, constants.rect_max);
+    let inside = clamp(0.5 - d, 0.0, 1.0);
+    let within_width = clamp(0.5 + d + constants.outline_width, 0.0, 1.0);
+    return premultiply(inside * within_width);
+}
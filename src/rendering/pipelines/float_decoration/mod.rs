@@ -0,0 +1,248 @@
+//! Draws drop shadows, rounded corners, and outlines for floating windows. The
+//! window is drawn to its own layer first, then the corners are masked and the
+//! outline drawn over the layer, and the shadow is drawn beneath the window
+//! before the layer is composited.
+
+mod decoration;
+
+pub use decoration::{FloatDecoration, Outline, Shadow};
+
+use crate::rendering::texture::Texture;
+use bytemuck::{Pod, Zeroable, cast_slice};
+use neophyte_linalg::PixelVec;
+use std::mem::size_of;
+use wgpu::include_wgsl;
+
+pub struct Pipeline {
+    shadow: wgpu::RenderPipeline,
+    mask: wgpu::RenderPipeline,
+    outline: wgpu::RenderPipeline,
+}
+
+impl Pipeline {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let shader = device.create_shader_module(include_wgsl!("float_decoration.wgsl"));
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Float decoration pipeline layout"),
+            bind_group_layouts: &[],
+            push_constant_ranges: &[wgpu::PushConstantRange {
+                stages: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                range: 0..PushConstants::SIZE,
+            }],
+        });
+
+        let create = |label, entry_point, blend, depth_stencil| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some(entry_point),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: Texture::LINEAR_FORMAT,
+                        blend: Some(blend),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil,
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        // The shadow is hidden by windows in front of the float but not by
+        // the float itself, which shares its depth
+        let shadow = create(
+            "Float shadow render pipeline",
+            "fs_shadow",
+            wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+        );
+
+        // Multiplies the layer by the coverage of the rounded window
+        let mask = create(
+            "Float mask render pipeline",
+            "fs_mask",
+            wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Zero,
+                    dst_factor: wgpu::BlendFactor::Src,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Zero,
+                    dst_factor: wgpu::BlendFactor::SrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+            },
+            None,
+        );
+
+        let outline = create(
+            "Float outline render pipeline",
+            "fs_outline",
+            wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            None,
+        );
+
+        Self {
+            shadow,
+            mask,
+            outline,
+        }
+    }
+
+    /// Decorate a float that has been drawn to the layer target. The shadow
+    /// goes to the color target and should be drawn before the layer is
+    /// composited over it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        layer_target: &wgpu::TextureView,
+        color_target: &wgpu::TextureView,
+        depth_target: &wgpu::TextureView,
+        target_size: PixelVec<u32>,
+        (offset, size): (PixelVec<i32>, PixelVec<u32>),
+        z: f32,
+        opacity: f32,
+        decoration: &FloatDecoration,
+    ) {
+        let rect_min = offset.cast_as::<f32>();
+        let constants = PushConstants {
+            target_size: target_size.cast_as(),
+            rect_min,
+            rect_max: rect_min + size.cast_as(),
+            shadow_offset: PixelVec::default(),
+            color: [0.; 4],
+            radius: decoration.radius,
+            blur: 0.,
+            outline_width: 0.,
+            z,
+        };
+
+        if decoration.radius > 0. || decoration.outline.is_some() {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Float mask render pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: layer_target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            if decoration.radius > 0. {
+                render_pass.set_pipeline(&self.mask);
+                render_pass.set_push_constants(
+                    wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    0,
+                    cast_slice(&[constants]),
+                );
+                render_pass.draw(0..6, 0..1);
+            }
+
+            if let Some(outline) = decoration.outline {
+                render_pass.set_pipeline(&self.outline);
+                render_pass.set_push_constants(
+                    wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    0,
+                    cast_slice(&[PushConstants {
+                        color: outline.color,
+                        outline_width: outline.width,
+                        ..constants
+                    }]),
+                );
+                render_pass.draw(0..6, 0..1);
+            }
+        }
+
+        if let Some(shadow) = decoration.shadow {
+            let [r, g, b, a] = shadow.color;
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Float shadow render pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: color_target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: depth_target,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(&self.shadow);
+            render_pass.set_push_constants(
+                wgpu::ShaderStages::VERTEX_FRAGMENT,
+                0,
+                cast_slice(&[PushConstants {
+                    shadow_offset: shadow.offset,
+                    color: [r, g, b, a * opacity],
+                    blur: shadow.blur,
+                    ..constants
+                }]),
+            );
+            render_pass.draw(0..6, 0..1);
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, Pod, Zeroable)]
+struct PushConstants {
+    target_size: PixelVec<f32>,
+    rect_min: PixelVec<f32>,
+    rect_max: PixelVec<f32>,
+    shadow_offset: PixelVec<f32>,
+    color: [f32; 4],
+    radius: f32,
+    blur: f32,
+    outline_width: f32,
+    z: f32,
+}
+
+impl PushConstants {
+    pub const SIZE: u32 = size_of::<Self>() as u32;
+}
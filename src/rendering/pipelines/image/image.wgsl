@@ -0,0 +1,42 @@
+struct PushConstants {
+    target_size: vec2<f32>,
+    rect_min: vec2<f32>,
+    rect_max: vec2<f32>,
+    z: f32,
+    padding: f32,
+}
+
+@group(0) @binding(0)
+var tex: texture_2d<f32>;
+@group(0) @binding(1)
+var tex_sampler: sampler;
+var<push_constant> constants: PushConstants;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) in_vertex_index: u32,
+) -> VertexOutput {
+    let corner = vec2<f32>(
+        f32(in_vertex_index % 2u),
+        f32(((in_vertex_index + 5u) % 6u) / 3u),
+    );
+    let pixel = mix(constants.rect_min, constants.rect_max, corner);
+    var out: VertexOutput;
+    out.uv = corner;
+    out.clip_position = vec4<f32>(
+        pixel / constants.target_size * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0),
+        constants.z,
+        1.0,
+    );
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSampleLevel(tex, tex_sampler, in.uv, 0.0);
+}
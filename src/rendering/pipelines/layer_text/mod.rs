@@ -0,0 +1,178 @@
+//! Copies the text of a layered window into the monochrome target. The cursor
+//! samples the monochrome target to draw text over itself, so it needs the
+//! text of windows that were drawn to their own layer as well. The text
+//! replaces whatever was beneath the window at the depth of the window so
+//! that windows in front of it keep their own text.
+
+use crate::rendering::texture::Texture;
+use bytemuck::{Pod, Zeroable, cast_slice};
+use neophyte_linalg::PixelVec;
+use std::mem::size_of;
+use wgpu::include_wgsl;
+
+pub struct Pipeline {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+}
+
+impl Pipeline {
+    pub fn new(device: &wgpu::Device, layer_monochrome: &wgpu::TextureView) -> Self {
+        let shader = device.create_shader_module(include_wgsl!("layer_text.wgsl"));
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Layer text bind group layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Layer text pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[wgpu::PushConstantRange {
+                stages: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                range: 0..PushConstants::SIZE,
+            }],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Layer text render pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: Texture::LINEAR_FORMAT,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            bind_group: bind_group(device, &bind_group_layout, layer_monochrome),
+            bind_group_layout,
+        }
+    }
+
+    pub fn update(&mut self, device: &wgpu::Device, layer_monochrome: &wgpu::TextureView) {
+        self.bind_group = bind_group(device, &self.bind_group_layout, layer_monochrome);
+    }
+
+    /// Copy the layer text within the bounds of the window at the given depth
+    pub fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        monochrome_target: &wgpu::TextureView,
+        depth_target: &wgpu::TextureView,
+        target_size: PixelVec<u32>,
+        (offset, size): (PixelVec<i32>, PixelVec<u32>),
+        z: f32,
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Layer text render pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: monochrome_target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth_target,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        let rect_min = offset.cast_as::<f32>();
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_push_constants(
+            wgpu::ShaderStages::VERTEX_FRAGMENT,
+            0,
+            cast_slice(&[PushConstants {
+                target_size: target_size.cast_as(),
+                rect_min,
+                rect_max: rect_min + size.cast_as(),
+                z,
+                _padding: 0.,
+            }]),
+        );
+        render_pass.draw(0..6, 0..1);
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, Pod, Zeroable)]
+struct PushConstants {
+    target_size: PixelVec<f32>,
+    rect_min: PixelVec<f32>,
+    rect_max: PixelVec<f32>,
+    z: f32,
+    _padding: f32,
+}
+
+impl PushConstants {
+    pub const SIZE: u32 = size_of::<Self>() as u32;
+}
+
+fn bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    texture_view: &wgpu::TextureView,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Layer text bind group"),
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureView(texture_view),
+        }],
+    })
+}
@@ -1,10 +1,16 @@
 pub mod blend;
 pub mod cell_fill;
 pub mod cursor;
+pub mod cursor_effects;
 pub mod default_fill;
+pub mod dim;
+pub mod float_decoration;
 pub mod gamma_blit;
+pub mod image;
+pub mod layer_text;
 pub mod lines;
 pub mod png_blit;
+pub mod rects;
 pub mod text;
 
 use super::{targets::Targets, texture::Texture, wgpu_context::WgpuContext};
@@ -14,8 +20,19 @@ use neophyte_linalg::Vec2;
 pub struct Pipelines {
     pub cursor: cursor::Pipeline,
     pub cmdline_cursor: cursor::Pipeline,
+    pub cursor_effects: cursor_effects::Pipeline,
     pub blend: blend::Pipeline,
+    /// Blends text over translucent windows
+    pub layer_blend: blend::Pipeline,
+    /// Blends translucent windows over the grids beneath them
+    pub layer_composite: blend::Pipeline,
+    /// Copies the text of translucent windows to the monochrome target
+    pub layer_text: layer_text::Pipeline,
     pub default_fill: default_fill::Pipeline,
+    pub float_decoration: float_decoration::Pipeline,
+    pub dim: dim::Pipeline,
+    pub image: image::Pipeline,
+    pub rects: rects::Pipeline,
     pub cell_fill: cell_fill::Pipeline,
     pub monochrome: text::Pipeline,
     pub emoji: text::Pipeline,
@@ -34,8 +51,16 @@ impl Pipelines {
         Pipelines {
             cursor: cursor::Pipeline::new(device, &targets.monochrome.view),
             cmdline_cursor: cursor::Pipeline::new(device, &targets.monochrome.view),
+            cursor_effects: cursor_effects::Pipeline::new(device),
             blend: blend::Pipeline::new(device, &targets.monochrome.view),
+            layer_blend: blend::Pipeline::new(device, &targets.layer_monochrome.view),
+            layer_composite: blend::Pipeline::new(device, &targets.layer.view),
+            layer_text: layer_text::Pipeline::new(device, &targets.layer_monochrome.view),
             default_fill: default_fill::Pipeline::new(device, Texture::LINEAR_FORMAT),
+            float_decoration: float_decoration::Pipeline::new(device),
+            dim: dim::Pipeline::new(device),
+            image: image::Pipeline::new(device),
+            rects: rects::Pipeline::new(device),
             cell_fill: cell_fill::Pipeline::new(
                 device,
                 text_bind_group_layout,
@@ -88,5 +113,11 @@ impl Pipelines {
             .update(&wgpu_context.device, &wgpu_context.queue, &font_cache.emoji);
         self.blend
             .update(&wgpu_context.device, &targets.monochrome.view);
+        self.layer_blend
+            .update(&wgpu_context.device, &targets.layer_monochrome.view);
+        self.layer_composite
+            .update(&wgpu_context.device, &targets.layer.view);
+        self.layer_text
+            .update(&wgpu_context.device, &targets.layer_monochrome.view);
     }
 }
@@ -0,0 +1,46 @@
+struct PushConstants {
+    target_size: vec2<f32>,
+    min: vec2<f32>,
+    max: vec2<f32>,
+    z: f32,
+    padding: f32,
+    color: vec4<f32>,
+}
+
+var<push_constant> constants: PushConstants;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) pixel: vec2<f32>,
+}
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) in_vertex_index: u32,
+) -> VertexOutput {
+    let corner = vec2<f32>(
+        f32(in_vertex_index % 2u),
+        f32(((in_vertex_index + 5u) % 6u) / 3u),
+    );
+    let pixel = mix(constants.min, constants.max, corner);
+
+    var out: VertexOutput;
+    out.pixel = pixel;
+    out.clip_position = vec4<f32>(
+        pixel / constants.target_size * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0),
+        constants.z,
+        1.0,
+    );
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Round the short sides into semicircles
+    let half = (constants.max - constants.min) * 0.5;
+    let radius = min(half.x, half.y);
+    let q = abs(in.pixel - (constants.min + constants.max) * 0.5) - half + radius;
+    let d = length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+    let a = constants.color.a * clamp(0.5 - d, 0.0, 1.0);
+    return vec4<f32>(constants.color.rgb * a, a);
+}
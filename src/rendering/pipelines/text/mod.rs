@@ -160,10 +160,11 @@ impl Pipeline {
                     continue;
                 };
                 render_pass.set_bind_group(1, bind_group, &[]);
-                let size = grid.size().into_pixels(cell_size);
-                if let Some(offset) = grid.offset() {
+                if let Some(offset) = grid.offset()
+                    && let Some((clip_offset, clip_size)) = grid.clip_region(cell_size)
+                {
                     let offset = offset.round_to_pixels(cell_size);
-                    set_scissor(size, offset, target_size, &mut render_pass);
+                    set_scissor(clip_size, clip_offset, target_size, &mut render_pass);
                     GlyphPushConstants {
                         target_size: target_size.try_cast().unwrap(),
                         offset: offset + scroll_offset,
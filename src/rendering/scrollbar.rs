@@ -0,0 +1,296 @@
+//! Overlay scrollbars for windows, sized from the viewport that Neovim reports
+
+use super::Motion;
+use crate::ui::grid::Viewport;
+use crate::util::{IntoSrgb, parse_color};
+use neophyte_linalg::PixelVec;
+use neophyte_ui_event::{MaybeInto, Parse, rgb::Rgb};
+use std::time::Duration;
+
+/// How scrollbars are drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollbarSettings {
+    pub enabled: bool,
+    /// Width of the scrollbar in pixels
+    pub width: f32,
+    /// Seconds that the scrollbar stays visible after scrolling
+    pub timeout: f32,
+    /// Seconds over which the scrollbar fades out
+    pub fade: f32,
+    /// Color with straight alpha
+    pub color: [f32; 4],
+}
+
+impl Default for ScrollbarSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            width: 6.,
+            timeout: 1.,
+            fade: 0.3,
+            color: Rgb::new(128, 128, 128).into_srgb(0.6),
+        }
+    }
+}
+
+impl Parse for ScrollbarSettings {
+    fn parse(value: rmpv::Value) -> Option<Self> {
+        let rmpv::Value::Map(map) = value else {
+            return None;
+        };
+        let mut out = Self {
+            enabled: true,
+            ..Self::default()
+        };
+        for (k, v) in map {
+            match k.as_str()? {
+                "enabled" => out.enabled = v.maybe_into()?,
+                "width" => out.width = v.maybe_into()?,
+                "timeout" => out.timeout = v.maybe_into()?,
+                "fade" => out.fade = v.maybe_into()?,
+                "color" => out.color = parse_color(v)?,
+                _ => {}
+            }
+        }
+        // Timeouts become durations, which must be finite
+        (out.width > 0. && (0. ..f32::MAX).contains(&out.timeout) && out.fade >= 0.).then_some(out)
+    }
+}
+
+/// Pixels between the scrollbar and the right edge of the window
+const INSET: f32 = 2.;
+/// The smallest height of the thumb in pixels
+const MIN_THUMB: f32 = 16.;
+
+/// The placement of a scrollbar in pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geometry {
+    /// The top left of the track
+    pub track_offset: PixelVec<f32>,
+    pub track_size: PixelVec<f32>,
+    /// The distance from the top of the track to the top of the thumb
+    pub thumb_offset: f32,
+    pub thumb_height: f32,
+    /// The largest zero-based topline that keeps the window full
+    max_topline: u32,
+}
+
+impl Geometry {
+    /// The scrollbar for a window showing the given viewport whose scrolling
+    /// region has the given offset and size in pixels, or None if the whole
+    /// buffer is visible
+    pub fn new(
+        viewport: Viewport,
+        offset: PixelVec<f32>,
+        size: PixelVec<f32>,
+        width: f32,
+    ) -> Option<Self> {
+        let shown = viewport
+            .botline
+            .min(viewport.line_count)
+            .saturating_sub(viewport.topline);
+        if shown >= viewport.line_count || size.0.y <= 0. {
+            return None;
+        }
+        let max_topline = viewport.line_count - shown;
+        let height = size.0.y;
+        let thumb_height =
+            (height * shown as f32 / viewport.line_count as f32).max(MIN_THUMB.min(height));
+        let thumb_offset =
+            (height - thumb_height) * viewport.topline.min(max_topline) as f32 / max_topline as f32;
+        Some(Self {
+            track_offset: offset + PixelVec::new(size.0.x - width - INSET, 0.),
+            track_size: PixelVec::new(width, height),
+            thumb_offset,
+            thumb_height,
+            max_topline,
+        })
+    }
+
+    /// The top left and bottom right of the thumb
+    pub fn thumb(&self) -> (PixelVec<f32>, PixelVec<f32>) {
+        let min = self.track_offset + PixelVec::new(0., self.thumb_offset);
+        let max = min + PixelVec::new(self.track_size.0.x, self.thumb_height);
+        (min, max)
+    }
+
+    /// Whether the point is over the track, including the thumb
+    pub fn track_contains(&self, point: PixelVec<f32>) -> bool {
+        let max = self.track_offset + self.track_size;
+        point.0.x >= self.track_offset.0.x
+            && point.0.y >= self.track_offset.0.y
+            && point.0.x < max.0.x
+            && point.0.y < max.0.y
+    }
+
+    /// The distance of the point below the top of the thumb
+    pub fn below_thumb(&self, point: PixelVec<f32>) -> f32 {
+        point.0.y - self.track_offset.0.y - self.thumb_offset
+    }
+
+    /// The zero-based topline that puts the top of the thumb at the given
+    /// distance from the top of the track
+    pub fn topline_at(&self, thumb_offset: f32) -> u32 {
+        let range = self.track_size.0.y - self.thumb_height;
+        if range <= 0. {
+            return 0;
+        }
+        let fraction = (thumb_offset / range).clamp(0., 1.);
+        (fraction * self.max_topline as f32).round() as u32
+    }
+}
+
+/// Whether the scrollbar of a window is visible
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Scrollbar {
+    viewport: Option<Viewport>,
+    /// Seconds since the window last scrolled, or None if the scrollbar has
+    /// faded out
+    idle: Option<f32>,
+}
+
+impl Scrollbar {
+    /// Show the scrollbar if the window scrolled
+    pub fn update(&mut self, viewport: Option<Viewport>) {
+        if let (Some(old), Some(new)) = (self.viewport, viewport)
+            && old.topline != new.topline
+        {
+            self.show();
+        }
+        self.viewport = viewport;
+    }
+
+    /// Show the scrollbar and restart its timeout
+    pub fn show(&mut self) {
+        self.idle = Some(0.);
+    }
+
+    pub fn viewport(&self) -> Option<Viewport> {
+        self.viewport
+    }
+
+    pub fn advance(&mut self, delta_time: Duration, settings: ScrollbarSettings) -> Motion {
+        if !settings.enabled {
+            self.idle = None;
+        }
+        let Some(idle) = self.idle.as_mut() else {
+            return Motion::Still;
+        };
+        *idle += delta_time.as_secs_f32();
+        if *idle < settings.timeout {
+            Motion::delay(settings.timeout - *idle)
+        } else if *idle < settings.timeout + settings.fade {
+            Motion::Animating
+        } else {
+            self.idle = None;
+            Motion::Still
+        }
+    }
+
+    pub fn opacity(&self, settings: ScrollbarSettings) -> f32 {
+        match self.idle {
+            _ if !settings.enabled => 0.,
+            Some(idle) if idle < settings.timeout => 1.,
+            Some(idle) if settings.fade > 0. => {
+                (1. - (idle - settings.timeout) / settings.fade).clamp(0., 1.)
+            }
+            _ => 0.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn viewport(topline: u32) -> Viewport {
+        Viewport {
+            window: 1000,
+            topline,
+            botline: topline + 10,
+            line_count: 100,
+        }
+    }
+
+    fn geometry(topline: u32) -> Geometry {
+        Geometry::new(
+            viewport(topline),
+            PixelVec::new(0., 0.),
+            PixelVec::new(100., 200.),
+            6.,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn thumb_spans_the_shown_lines() {
+        let top = geometry(0);
+        assert_eq!(top.thumb_height, 20.);
+        assert_eq!(top.thumb_offset, 0.);
+        let bottom = geometry(90);
+        assert_eq!(bottom.thumb_offset, 180.);
+    }
+
+    #[test]
+    fn dragging_maps_back_to_the_topline() {
+        let geometry = geometry(45);
+        assert_eq!(geometry.topline_at(geometry.thumb_offset), 45);
+        assert_eq!(geometry.topline_at(-10.), 0);
+        assert_eq!(geometry.topline_at(1000.), 90);
+    }
+
+    #[test]
+    fn hidden_when_everything_is_shown() {
+        let viewport = Viewport {
+            window: 1000,
+            topline: 0,
+            botline: 101,
+            line_count: 100,
+        };
+        let size = PixelVec::new(100., 200.);
+        assert_eq!(Geometry::new(viewport, size, size, 6.), None);
+    }
+
+    #[test]
+    fn rejects_infinite_timeout() {
+        let value = rmpv::Value::Map(vec![("timeout".into(), f64::INFINITY.into())]);
+        assert_eq!(ScrollbarSettings::parse(value), None);
+
+        // Long timeouts wait for at most a day
+        let value = rmpv::Value::Map(vec![("timeout".into(), 1e30.into())]);
+        let settings = ScrollbarSettings::parse(value).unwrap();
+        let mut scrollbar = Scrollbar::default();
+        scrollbar.show();
+        assert_eq!(
+            scrollbar.advance(Duration::ZERO, settings),
+            Motion::Delay(Duration::from_secs(60 * 60 * 24))
+        );
+    }
+
+    #[test]
+    fn fades_after_scrolling() {
+        let settings = ScrollbarSettings {
+            enabled: true,
+            ..ScrollbarSettings::default()
+        };
+        let mut scrollbar = Scrollbar::default();
+        scrollbar.update(Some(viewport(0)));
+        assert_eq!(scrollbar.opacity(settings), 0.);
+        scrollbar.update(Some(viewport(5)));
+        assert_eq!(scrollbar.opacity(settings), 1.);
+        assert!(matches!(
+            scrollbar.advance(Duration::from_millis(500), settings),
+            Motion::Delay(_)
+        ));
+        assert_eq!(
+            scrollbar.advance(Duration::from_millis(600), settings),
+            Motion::Animating
+        );
+        assert!(scrollbar.opacity(settings) < 1.);
+        assert_eq!(
+            scrollbar.advance(Duration::from_millis(500), settings),
+            Motion::Still
+        );
+        assert_eq!(scrollbar.opacity(settings), 0.);
+    }
+}
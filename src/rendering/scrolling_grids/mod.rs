@@ -1,29 +1,38 @@
 mod range;
 
 use crate::{
-    rendering::Motion,
-    ui::grid::{CellContents, GridContents},
-    util::nice_s_curve,
+    rendering::{
+        Motion,
+        animation::{Animation, Curve},
+    },
+    ui::grid::{CellContents, GridContents, Margins, OnceOrChars},
 };
-use neophyte_linalg::CellVec;
+use neophyte_linalg::{CellVec, Vec2};
 use range::Range;
 use std::{collections::VecDeque, time::Duration};
 
 pub struct ScrollingGrids {
     scrolling: VecDeque<GridPart>,
-    t: Duration,
-    offset_start: f32,
+    /// Margins of the current grid, which are drawn separately from the
+    /// scrolling viewport
+    margins: Margins,
+    offset_y: Animation<f32>,
+    offset_x: Animation<f32>,
+    curve: Curve,
 }
 
 impl ScrollingGrids {
     #[allow(unused)]
     pub fn new(grid: GridContents) -> Self {
         let mut scrolling = VecDeque::new();
-        scrolling.push_back(GridPart::new(grid));
+        let margins = Margins::default();
+        scrolling.push_back(GridPart::new(grid, margins));
         Self {
             scrolling,
-            t: Duration::ZERO,
-            offset_start: 0.,
+            margins,
+            offset_y: Animation::default(),
+            offset_x: Animation::default(),
+            curve: Curve::default(),
         }
     }
 
@@ -32,28 +41,53 @@ impl ScrollingGrids {
         assert_eq!(self.scrolling.len(), 1);
     }
 
-    pub fn advance(&mut self, delta_time: Duration, speed: f32) -> Motion {
-        self.t +=
-            Duration::try_from_secs_f32(delta_time.as_secs_f32() * speed).unwrap_or(Duration::ZERO);
-        if self.offset_y() == 0. {
+    pub fn advance(
+        &mut self,
+        delta_time: Duration,
+        speed: f32,
+        curve: Curve,
+        cell_size: Vec2<f32>,
+    ) -> Motion {
+        self.curve = curve;
+        let animating_y = self
+            .offset_y
+            .advance(delta_time, speed * curve.rate(cell_size.y), curve);
+        let animating_x = self
+            .offset_x
+            .advance(delta_time, speed * curve.rate(cell_size.x), curve);
+        if !animating_y {
             self.finish_scroll();
-            Motion::Still
-        } else {
+        }
+        if animating_x || animating_y {
             Motion::Animating
+        } else {
+            Motion::Still
+        }
+    }
+
+    /// Update the margins of the grid. Scrolling in progress is finished if
+    /// they change since the viewport is no longer the same.
+    pub fn set_margins(&mut self, margins: Margins) {
+        if margins != self.margins {
+            self.margins = margins;
+            self.finish_scroll();
+            let front = self.scrolling.front_mut().unwrap();
+            *front = GridPart::new(front.grid.clone(), margins);
         }
     }
 
     pub fn push(&mut self, grid: GridContents, offset: i32) {
         // TODO: Add desired screen region
+        let inner = inner_range(&grid, self.margins);
         let sign = if offset.is_positive() { 1 } else { -1 };
-        let mag = offset.abs().min(grid.size.0.y.into());
+        let mag = offset.abs().min(inner.end - inner.start);
         let offset = mag * sign;
-        let mut coverage = Range::until(grid.size.0.y.into());
-        self.offset_start = self.offset_y() + offset as f32;
-        self.t = Duration::ZERO;
+        let mut coverage = inner;
+        self.offset_y.retarget(offset as f32, self.curve);
+        let margins = self.margins;
         self.scrolling.retain_mut(|part| {
             part.offset -= offset;
-            let grid_range = Range::until(part.grid.size.0.y.into()) + part.offset;
+            let grid_range = inner_range(&part.grid, margins) + part.offset;
             let uncovered = grid_range.cover(coverage);
             coverage = coverage.union(grid_range);
             if let Some(uncovered) = uncovered {
@@ -66,37 +100,75 @@ impl ScrollingGrids {
                 false
             }
         });
-        self.scrolling.push_front(GridPart::new(grid));
+        self.scrolling.push_front(GridPart::new(grid, self.margins));
+    }
+
+    /// Start a horizontal scroll by the given number of columns. Unlike
+    /// vertical scrolling, the previous contents are not kept around to fill
+    /// the columns that are scrolled into view.
+    pub fn push_horizontal(&mut self, cols: i32) {
+        let cols = cols.clamp(-i32::from(self.size().0.x), self.size().0.x.into());
+        self.offset_x.retarget(cols as f32, self.curve);
     }
 
     pub fn replace(&mut self, grid: GridContents) {
-        *self.scrolling.front_mut().unwrap() = GridPart::new(grid);
+        *self.scrolling.front_mut().unwrap() = GridPart::new(grid, self.margins);
     }
 
+    /// The rows of the scrolling viewport. Cells in the margins are left
+    /// empty so they don't scroll into view.
     pub fn rows<'a, 'b: 'a>(
         &'a self,
     ) -> impl Iterator<Item = (i32, impl Iterator<Item = CellContents<'a>> + 'a + Clone)> + 'a + Clone
     {
-        self.scrolling.iter().rev().flat_map(|part| {
+        let margins = self.margins;
+        self.scrolling.iter().rev().flat_map(move |part| {
+            let size = part.grid.size;
             part.grid
                 .rows()
                 .enumerate()
                 .skip(part.start)
                 .take(part.end - part.start)
-                .map(|(i, cells)| (i as i32 + part.offset, cells))
+                .map(move |(i, cells)| {
+                    (
+                        i as i32 + part.offset,
+                        mask(cells, move |col| margins.is_inner(size, i, col)),
+                    )
+                })
         })
     }
 
-    pub fn size(&self) -> CellVec<u16> {
-        self.scrolling.back().unwrap().grid.size
+    /// The rows of the current grid that have cells in the margins, with the
+    /// cells in the viewport left empty. These are drawn without scrolling.
+    pub fn margin_rows<'a, 'b: 'a>(
+        &'a self,
+    ) -> impl Iterator<Item = (i32, impl Iterator<Item = CellContents<'a>> + 'a + Clone)> + 'a + Clone
+    {
+        let margins = self.margins;
+        let grid = &self.scrolling.front().unwrap().grid;
+        let size = grid.size;
+        let has_side_margins = margins.left > 0 || margins.right > 0;
+        let inner_rows = margins.inner_rows(size.0.y);
+        grid.rows()
+            .enumerate()
+            .filter(move |(i, _)| has_side_margins || !inner_rows.contains(i))
+            .map(move |(i, cells)| {
+                (
+                    i as i32,
+                    mask(cells, move |col| !margins.is_inner(size, i, col)),
+                )
+            })
     }
 
-    fn offset_y(&self) -> f32 {
-        self.offset_start * (1. - nice_s_curve(self.t.as_secs_f32(), self.offset_start.abs()))
+    pub fn size(&self) -> CellVec<u16> {
+        self.scrolling.back().unwrap().grid.size
     }
 
     pub fn offset(&self) -> CellVec<f32> {
-        CellVec::new(0., self.offset_y())
+        CellVec::new(
+            self.offset_x.offset(self.curve),
+            self.offset_y.offset(self.curve),
+        )
     }
 }
 
@@ -108,12 +180,36 @@ struct GridPart {
 }
 
 impl GridPart {
-    pub fn new(grid: GridContents) -> Self {
+    pub fn new(grid: GridContents, margins: Margins) -> Self {
+        let rows = margins.inner_rows(grid.size.0.y);
         Self {
             offset: 0,
-            start: 0,
-            end: grid.size.0.y as usize,
+            start: rows.start,
+            end: rows.end,
             grid,
         }
     }
 }
+
+/// The rows of the grid's viewport
+fn inner_range(grid: &GridContents, margins: Margins) -> Range {
+    let rows = margins.inner_rows(grid.size.0.y);
+    Range::new(rows.start as i32, rows.end as i32)
+}
+
+/// Replace the cells in columns that are not kept with hidden ones
+fn mask<'a>(
+    cells: impl Iterator<Item = CellContents<'a>> + Clone,
+    keep: impl Fn(usize) -> bool + Clone,
+) -> impl Iterator<Item = CellContents<'a>> + Clone {
+    cells.enumerate().map(move |(col, cell)| {
+        if keep(col) {
+            cell
+        } else {
+            CellContents {
+                highlight: 0,
+                text: OnceOrChars::Hidden,
+            }
+        }
+    })
+}
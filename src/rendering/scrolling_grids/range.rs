@@ -15,10 +15,6 @@ impl Range {
         Self { start, end }
     }
 
-    pub fn until(end: i32) -> Self {
-        Self::new(0, end)
-    }
-
     pub fn union(self, other: Self) -> Self {
         Self {
             start: self.start.min(other.start),
@@ -1,12 +1,28 @@
 use super::{
-    Motion, cmdline_grid::CmdlineGrid, grids::Grids, message_grids::MessageGrids,
-    pipelines::Pipelines, targets::Targets, text::BindGroupLayout as TextBindGroup,
+    Motion,
+    cmdline_grid::CmdlineGrid,
+    grids::{Grid, Grids},
+    images::{Image, ImageData, ImageShow},
+    message_grids::MessageGrids,
+    overlays::{Overlay, OverlayKind, OverlaySet, Overlays},
+    pipelines::{
+        Pipelines, blend,
+        dim::Dimmed,
+        float_decoration::{FloatDecoration, Outline, Shadow},
+        image::ImageTexture,
+        rects::Rect,
+    },
+    scrollbar::Geometry,
+    targets::Targets,
+    text::{BindGroupLayout as TextBindGroup, Text},
+    toasts::{ToastSettings, Toasts},
+    transition::FloatTransition,
     wgpu_context::WgpuContext,
 };
 use crate::{
     event_handler::settings::Settings,
-    text::{cache::FontCache, fonts::Fonts},
-    ui::Ui,
+    text::{cache::FontCache, fonts::Fonts, ligatures::Ligatures},
+    ui::{self, Ui, grid::Viewport},
     util::IntoSrgb,
 };
 use bytemuck::cast_slice;
@@ -15,6 +31,7 @@ use neophyte_ui_event::rgb::Rgb;
 use std::{
     fs::File,
     io::{self, BufWriter},
+    ops::Range,
     sync::Arc,
     time::Duration,
 };
@@ -29,11 +46,27 @@ pub struct RenderState {
     shape_context: ShapeContext,
     pub fonts: Fonts,
     font_cache: FontCache,
+    pub ligatures: Ligatures,
     clear_color: [f32; 4],
     // TODO: Remove this if we no longer want to externalize the cmdline
     cmdline_grid: CmdlineGrid,
     message_grids: MessageGrids,
+    toasts: Toasts,
     text_bind_group_layout: TextBindGroup,
+    hovered_link: Option<HoveredLink>,
+    overlays: Overlays,
+}
+
+/// A hyperlink under the mouse, which is underlined
+#[derive(Debug, Clone, PartialEq)]
+pub struct HoveredLink {
+    pub grid: ui::grid::Id,
+    /// The row of the link in grid cells
+    pub row: u32,
+    /// The columns of the link in grid cells
+    pub cols: Range<u32>,
+    /// Color with straight alpha
+    pub color: [f32; 4],
 }
 
 impl RenderState {
@@ -56,22 +89,38 @@ impl RenderState {
             ),
             shape_context: ShapeContext::new(),
             font_cache: FontCache::new(),
+            ligatures: Ligatures::default(),
             grids: Grids::new(&wgpu_context.device),
             targets,
             wgpu_context,
             clear_color: [0.; 4],
             cmdline_grid: CmdlineGrid::new(),
             message_grids: MessageGrids::new(),
+            toasts: Toasts::default(),
+            hovered_link: None,
+            overlays: Overlays::default(),
         }
     }
 
-    pub fn update(&mut self, ui: &Ui, bg_override: Option<[f32; 4]>) {
+    pub fn update(
+        &mut self,
+        ui: &Ui,
+        bg_override: Option<[f32; 4]>,
+        float_transition: FloatTransition,
+        window_move_duration: f32,
+        toasts: ToastSettings,
+    ) {
         self.clear_color =
             bg_override.unwrap_or(ui.default_colors.rgb_bg.unwrap_or(Rgb::BLACK).into_srgb(1.));
 
         let fg = ui.default_colors.rgb_fg.unwrap_or(Rgb::WHITE);
         let bg = ui.default_colors.rgb_bg.unwrap_or(Rgb::BLACK);
 
+        if ui.did_highlights_change {
+            self.fonts.update_highlights(&ui.highlight_names);
+            self.ligatures.update_highlights(&ui.highlight_names);
+        }
+
         self.grids.update(
             &self.wgpu_context.device,
             &self.wgpu_context.queue,
@@ -79,6 +128,9 @@ impl RenderState {
             &self.fonts,
             &mut self.font_cache,
             &mut self.shape_context,
+            &self.ligatures,
+            float_transition,
+            window_move_duration,
         );
 
         let base_grid_size = ui.grids[0].contents().size.0;
@@ -86,6 +138,7 @@ impl RenderState {
             &self.wgpu_context.device,
             &self.wgpu_context.queue,
             &ui.cmdline,
+            ui.char_width,
             base_grid_size,
             &self.text_bind_group_layout.bind_group_layout,
             &ui.highlights,
@@ -94,12 +147,15 @@ impl RenderState {
             &self.fonts,
             &mut self.font_cache,
             &mut self.shape_context,
+            &self.ligatures,
         );
 
         self.message_grids.update(
             &self.wgpu_context.device,
             &self.wgpu_context.queue,
             &ui.messages,
+            toasts.enabled,
+            ui.char_width,
             base_grid_size,
             &self.text_bind_group_layout.bind_group_layout,
             &ui.highlights,
@@ -108,6 +164,21 @@ impl RenderState {
             &self.fonts,
             &mut self.font_cache,
             &mut self.shape_context,
+            &self.ligatures,
+        );
+
+        self.toasts.update(
+            &self.wgpu_context.device,
+            &self.wgpu_context.queue,
+            ui,
+            toasts,
+            &self.text_bind_group_layout.bind_group_layout,
+            fg,
+            bg,
+            &self.fonts,
+            &mut self.font_cache,
+            &mut self.shape_context,
+            &self.ligatures,
         );
 
         self.pipelines.update(
@@ -150,27 +221,41 @@ impl RenderState {
         cell_size: Vec2<f32>,
         settings: &Settings,
     ) -> Motion {
-        let mut motion = Motion::Still;
-
-        for grid in self.grids.iter_mut() {
-            motion = motion.soonest(
-                grid.scrolling
-                    .advance(delta_time, settings.scroll_speed * cell_size.y),
-            );
-        }
+        let mut motion = self.grids.advance(
+            delta_time,
+            settings.scroll_speed,
+            settings.scroll_curve,
+            settings.dim_inactive,
+            settings.scrollbar,
+            cell_size,
+        );
 
         const DEFAULT_CURSOR_SPEED: f32 = 100.;
-        let cursor_speed = settings.cursor_speed * DEFAULT_CURSOR_SPEED;
-        motion = motion.soonest(
-            self.pipelines
-                .cursor
-                .advance(delta_time, cursor_speed, cell_size),
-        );
+        let curve = settings.cursor_curve;
+        let cursor_speed = settings.cursor_speed * curve.rate(DEFAULT_CURSOR_SPEED);
+        let blink_fade = Duration::from_secs_f32(settings.cursor_blink_fade);
+        motion = motion.soonest(self.pipelines.cursor.advance(
+            delta_time,
+            cursor_speed,
+            curve,
+            blink_fade,
+            cell_size,
+        ));
         motion = motion.soonest(self.pipelines.cmdline_cursor.advance(
             delta_time,
             cursor_speed,
+            curve,
+            blink_fade,
+            cell_size,
+        ));
+        motion = motion.soonest(self.pipelines.cursor_effects.advance(
+            delta_time,
+            settings.cursor_effect,
+            self.pipelines.cursor.corners(),
+            self.pipelines.cursor.color(),
             cell_size,
         ));
+        motion = motion.soonest(self.toasts.advance(delta_time, settings.toasts));
 
         motion
     }
@@ -212,70 +297,121 @@ impl RenderState {
         let target_size = self.targets.color.texture.size().into();
 
         let grid_count = self.grids.grid_count() as f32;
-        let grids = || {
-            self.grids
-                .front_to_back()
-                .map(|(z, grid)| {
-                    (
-                        (z as f32 + 1.) / (grid_count + 1.),
-                        grid.scrolling.offset().round_to_pixels(cell_size),
-                        &grid.text,
-                    )
-                })
-                .chain(
-                    self.message_grids
-                        .texts()
-                        .map(|text| (f32::EPSILON, PixelVec::new(0, 0), text)),
-                )
-                .chain(std::iter::once((
-                    0.,
-                    PixelVec::new(0, 0),
-                    &self.cmdline_grid.text,
-                )))
-        };
-
-        self.pipelines.default_fill.render(
+        let z_of = |z: usize| (z as f32 + 1.) / (grid_count + 1.);
+        // Decorated floats are drawn to their own layer so that their corners
+        // can be masked
+        let decoration = &settings.float_decoration;
+        let is_layered =
+            |grid: &Grid| grid.is_translucent() || (grid.is_float() && decoration.is_enabled());
+        let opaque: Vec<_> = self
+            .grids
+            .front_to_back()
+            .filter(|(_, grid)| !is_layered(grid))
+            .flat_map(|(z, grid)| grid.texts(z_of(z), cell_size))
+            .chain(
+                self.message_grids
+                    .texts()
+                    .map(|text| (f32::EPSILON, PixelVec::new(0, 0), text)),
+            )
+            .chain(std::iter::once((
+                0.,
+                PixelVec::new(0, 0),
+                &self.cmdline_grid.text,
+            )))
+            .collect();
+        draw_grids(
             &mut encoder,
-            grids().map(|(z, _, grid)| (z, grid)),
-            &self.targets.color.view,
-            &self.targets.depth.view,
+            &self.pipelines,
+            &opaque,
+            GridTargets {
+                color: &self.targets.color.view,
+                monochrome: &self.targets.monochrome.view,
+                depth: &self.targets.depth.view,
+                text_blend: &self.pipelines.blend,
+                clear: true,
+            },
             target_size,
             cell_size,
             self.clear_color,
+            settings.underline_offset,
         );
 
-        self.pipelines.cell_fill.render(
+        self.pipelines.image.render(
             &mut encoder,
-            grids(),
+            self.grids
+                .front_to_back()
+                .filter(|(_, grid)| !is_layered(grid))
+                .flat_map(|(z, grid)| grid.placed_images(z_of(z), cell_size, &self.overlays)),
             &self.targets.color.view,
             &self.targets.depth.view,
             target_size,
-            cell_size,
         );
 
-        self.pipelines.monochrome.render(
-            &mut encoder,
-            grids(),
-            &self.targets.monochrome.view,
-            &self.targets.depth.view,
-            target_size,
-            cell_size,
-        );
+        // Layered grids are drawn back to front, each to its own layer that is
+        // blended over the grids beneath
+        let layered: Vec<_> = self
+            .grids
+            .front_to_back()
+            .filter(|(_, grid)| is_layered(grid))
+            .collect();
+        for (z, grid) in layered.into_iter().rev() {
+            self.draw_layer(
+                &mut encoder,
+                &grid.texts(z_of(z), cell_size),
+                target_size,
+                cell_size,
+                settings.underline_offset,
+            );
+            self.pipelines.image.render(
+                &mut encoder,
+                grid.placed_images(z_of(z), cell_size, &self.overlays),
+                &self.targets.layer.view,
+                &self.targets.depth.view,
+                target_size,
+            );
+            let bounds = grid.text.clip_region(cell_size);
+            if let Some(bounds) = bounds {
+                self.pipelines.layer_text.render(
+                    &mut encoder,
+                    &self.targets.monochrome.view,
+                    &self.targets.depth.view,
+                    target_size,
+                    bounds,
+                    z_of(z),
+                );
+            }
+            if grid.is_float()
+                && decoration.is_enabled()
+                && let Some(bounds) = bounds
+            {
+                self.pipelines.float_decoration.render(
+                    &mut encoder,
+                    &self.targets.layer.view,
+                    &self.targets.color.view,
+                    &self.targets.depth.view,
+                    target_size,
+                    bounds,
+                    z_of(z),
+                    grid.transition.opacity(),
+                    decoration,
+                );
+            }
+            self.pipelines.layer_composite.render(
+                &mut encoder,
+                &self.targets.color.view,
+                grid.transition.opacity(),
+            );
+        }
 
-        self.pipelines.lines.render(
+        self.pipelines
+            .cursor_effects
+            .prepare(&self.wgpu_context.device, &self.wgpu_context.queue);
+        self.pipelines.cursor_effects.render(
             &mut encoder,
-            grids(),
-            &self.targets.monochrome.view,
-            &self.targets.depth.view,
-            target_size,
-            cell_size,
-            settings.underline_offset,
+            &self.targets.color.view,
+            target_size.cast_as(),
         );
 
-        self.pipelines
-            .blend
-            .render(&mut encoder, &self.targets.color.view);
-
         self.pipelines.cursor.render(
             &mut encoder,
             &self.targets.color.view,
@@ -290,13 +426,116 @@ impl RenderState {
 
         self.pipelines.emoji.render(
             &mut encoder,
-            grids(),
+            opaque.iter().copied(),
             &self.targets.color.view,
             &self.targets.depth.view,
             target_size,
             cell_size,
         );
 
+        // Toasts go over everything but the dimming and rects of the grids
+        // beneath, which they occlude through the depth buffer
+        for card in self.toasts.cards() {
+            let Some(bounds) = card.text.clip_region(cell_size) else {
+                continue;
+            };
+            self.draw_layer(
+                &mut encoder,
+                &[(0., PixelVec::new(0, 0), card.text)],
+                target_size,
+                cell_size,
+                settings.underline_offset,
+            );
+            self.pipelines.float_decoration.render(
+                &mut encoder,
+                &self.targets.layer.view,
+                &self.targets.color.view,
+                &self.targets.depth.view,
+                target_size,
+                bounds,
+                0.,
+                card.opacity,
+                &FloatDecoration {
+                    radius: settings.toasts.radius,
+                    shadow: Some(Shadow::default()),
+                    outline: Some(Outline {
+                        width: 1.5,
+                        color: card.accent,
+                    }),
+                },
+            );
+            self.pipelines.layer_composite.render(
+                &mut encoder,
+                &self.targets.color.view,
+                card.opacity,
+            );
+        }
+
+        self.pipelines.dim.render(
+            &mut encoder,
+            self.grids.front_to_back().filter_map(|(z, grid)| {
+                let amount = grid.dim.amount() * grid.transition.opacity();
+                // Decorated floats are dimmed within the same rounded
+                // rectangle that their corners are masked to
+                let (bounds, radius) = if grid.is_float() && decoration.is_enabled() {
+                    (grid.text.clip_region(cell_size), decoration.radius)
+                } else {
+                    (grid.bounds(cell_size), 0.)
+                };
+                let (offset, size) = bounds?;
+                (amount > 0.).then_some(Dimmed {
+                    offset,
+                    size,
+                    z: z_of(z),
+                    amount,
+                    radius,
+                })
+            }),
+            &self.targets.color.view,
+            &self.targets.depth.view,
+            target_size,
+        );
+
+        let scrollbar = settings.scrollbar;
+        let underline_y = self.underline_y(settings.underline_offset);
+        let link_underline = self.link_underline(cell_size, underline_y);
+        let cell_size_f32 = cell_size.cast_as::<f32>();
+        self.pipelines.rects.render(
+            &mut encoder,
+            self.grids
+                .front_to_back()
+                .filter_map(|(z, grid)| {
+                    let opacity = grid.scrollbar.opacity(scrollbar) * grid.transition.opacity();
+                    let geometry = grid.scrollbar_geometry(cell_size, scrollbar.width)?;
+                    let (min, max) = geometry.thumb();
+                    let [r, g, b, a] = scrollbar.color;
+                    (opacity > 0.).then_some(Rect {
+                        min,
+                        max,
+                        z: z_of(z),
+                        color: [r, g, b, a * opacity],
+                    })
+                })
+                .chain(self.grids.front_to_back().flat_map(|(z, grid)| {
+                    let opacity = grid.transition.opacity();
+                    grid.placed_shapes(cell_size, &self.overlays)
+                        .map(move |(shape, cell)| {
+                            let (min, max) = shape.rect(cell, cell_size_f32, underline_y);
+                            let [r, g, b, a] = shape.color();
+                            Rect {
+                                min,
+                                max,
+                                z: z_of(z),
+                                color: [r, g, b, a * opacity],
+                            }
+                        })
+                }))
+                .chain(link_underline.map(|(z, rect)| Rect { z: z_of(z), ..rect })),
+            &self.targets.color.view,
+            &self.targets.depth.view,
+            target_size,
+        );
+
         self.pipelines.gamma_blit_final.render(
             &mut encoder,
             &output_view,
@@ -380,12 +619,196 @@ impl RenderState {
         output.present();
     }
 
+    /// Clear the layer and draw the given grids to it
+    fn draw_layer(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        texts: &[(f32, PixelVec<i32>, &Text)],
+        target_size: PixelVec<u32>,
+        cell_size: Vec2<u32>,
+        underline_offset: i32,
+    ) {
+        clear(encoder, &self.targets.layer.view);
+        draw_grids(
+            encoder,
+            &self.pipelines,
+            texts,
+            GridTargets {
+                color: &self.targets.layer.view,
+                monochrome: &self.targets.layer_monochrome.view,
+                depth: &self.targets.depth.view,
+                text_blend: &self.pipelines.layer_blend,
+                clear: false,
+            },
+            target_size,
+            cell_size,
+            self.clear_color,
+            underline_offset,
+        );
+        self.pipelines.emoji.render(
+            encoder,
+            texts.iter().copied(),
+            &self.targets.layer.view,
+            &self.targets.depth.view,
+            target_size,
+            cell_size,
+        );
+    }
+
+    /// Show the cursors and restart their blink cycles
+    pub fn reset_cursor_blink(&mut self) {
+        self.pipelines.cursor.reset_blink();
+        self.pipelines.cmdline_cursor.reset_blink();
+    }
+
+    pub fn set_focused(&mut self, is_focused: bool) {
+        self.pipelines.cursor.set_focused(is_focused);
+        self.pipelines.cmdline_cursor.set_focused(is_focused);
+    }
+
+    /// The scrollbar of the given grid if it is visible
+    pub fn scrollbar(
+        &self,
+        grid: ui::grid::Id,
+        cell_size: Vec2<u32>,
+        settings: &Settings,
+    ) -> Option<(Viewport, Geometry)> {
+        let grid = self.grids.get(grid)?;
+        if grid.scrollbar.opacity(settings.scrollbar) <= 0. {
+            return None;
+        }
+        let geometry = grid.scrollbar_geometry(cell_size, settings.scrollbar.width)?;
+        Some((grid.scrollbar.viewport()?, geometry))
+    }
+
+    /// Show an image over the given grid
+    pub fn show_image(&mut self, grid: ui::grid::Id, image: ImageShow) {
+        let Some(texture) = self.upload_image(&image.data) else {
+            return;
+        };
+        let image_id = image.id;
+        let image = Image {
+            texture,
+            position: image.position.cast_as(),
+            size: image.size,
+        };
+        if !self.grids.show_image(image_id, grid, image) {
+            log::error!("No grid {grid} to show an image over");
+        }
+    }
+
+    fn upload_image(&self, data: &ImageData) -> Option<ImageTexture> {
+        let max_size = self.wgpu_context.device.limits().max_texture_dimension_2d;
+        if data.size.x > max_size || data.size.y > max_size {
+            log::error!("Images can be at most {max_size} pixels wide and tall");
+            return None;
+        }
+        Some(
+            self.pipelines
+                .image
+                .upload(&self.wgpu_context.device, &self.wgpu_context.queue, data),
+        )
+    }
+
+    /// Returns whether there was an image with the given ID
+    pub fn hide_image(&mut self, id: u64) -> bool {
+        self.grids.hide_image(id)
+    }
+
+    pub fn hovered_link(&self) -> Option<&HoveredLink> {
+        self.hovered_link.as_ref()
+    }
+
+    pub fn set_hovered_link(&mut self, link: Option<HoveredLink>) {
+        self.hovered_link = link;
+    }
+
+    /// The distance from the top of a cell to the top of underlines from
+    /// highlights
+    fn underline_y(&self, underline_offset: i32) -> f32 {
+        let metrics = self.fonts.metrics().into_pixels();
+        let y = self.fonts.glyph_offset().y + metrics.ascent + metrics.underline_offset;
+        (y as i32 + underline_offset + 2) as f32
+    }
+
+    /// Attach an overlay to an extmark, replacing any that it had
+    pub fn set_overlay(&mut self, overlay: OverlaySet) {
+        let OverlaySet {
+            buffer,
+            extmark,
+            kind,
+        } = overlay;
+        let overlay = match kind {
+            OverlayKind::Image { data, size } => {
+                let Some(texture) = self.upload_image(&data) else {
+                    return;
+                };
+                Overlay::Image { texture, size }
+            }
+            OverlayKind::Shape(shape) => Overlay::Shape(shape),
+        };
+        self.overlays.set(buffer, extmark, overlay);
+    }
+
+    /// Returns whether the extmark had an overlay
+    pub fn remove_overlay(&mut self, buffer: i64, extmark: ui::grid::ExtmarkId) -> bool {
+        self.overlays.remove(buffer, extmark)
+    }
+
+    /// Returns whether the buffer had any overlays
+    pub fn clear_overlays(&mut self, buffer: i64) -> bool {
+        self.overlays.remove_buffer(buffer)
+    }
+
+    /// The underline of the hovered link and the front to back index of its
+    /// grid, in the same place as underlines from highlights
+    fn link_underline(&self, cell_size: Vec2<u32>, underline_y: f32) -> Option<(usize, Rect)> {
+        let link = self.hovered_link.as_ref()?;
+        let z = self.grids.front_to_back_index(link.grid)?;
+        let (offset, _) = self.grids.get(link.grid)?.placement(cell_size)?;
+        let metrics = self.fonts.metrics().into_pixels();
+        let min = offset.cast_as::<f32>()
+            + PixelVec::new(link.cols.start * cell_size.x, link.row * cell_size.y).cast_as()
+            + PixelVec::new(0., underline_y);
+        let max = min
+            + PixelVec::new(
+                link.cols.len() as u32 * cell_size.x,
+                metrics.stroke_size.max(1),
+            )
+            .cast_as();
+        Some((
+            z,
+            Rect {
+                min,
+                max,
+                z: 0.,
+                color: link.color,
+            },
+        ))
+    }
+
+    /// Show the scrollbar of the given grid and restart its timeout
+    pub fn show_scrollbar(&mut self, grid: ui::grid::Id) {
+        if let Some(grid) = self.grids.get_mut(grid) {
+            grid.scrollbar.show();
+        }
+    }
+
     pub fn clear_glyph_cache(&mut self) {
         self.font_cache.clear();
         self.pipelines.emoji.clear();
         self.pipelines.monochrome.clear();
     }
 
+    /// Whether box-drawing and similar characters are drawn procedurally
+    pub fn box_drawing(&self) -> bool {
+        self.font_cache.box_drawing
+    }
+
+    pub fn set_box_drawing(&mut self, enabled: bool) {
+        self.font_cache.box_drawing = enabled;
+    }
+
     pub fn surface_size(&self) -> PixelVec<u32> {
         PixelVec::new(
             self.wgpu_context.surface_config.width,
@@ -394,6 +817,89 @@ impl RenderState {
     }
 }
 
+/// The targets that grids are drawn to
+struct GridTargets<'a> {
+    color: &'a wgpu::TextureView,
+    monochrome: &'a wgpu::TextureView,
+    depth: &'a wgpu::TextureView,
+    /// Blends the monochrome target over the color target
+    text_blend: &'a blend::Pipeline,
+    /// Whether to clear the color and depth targets before drawing
+    clear: bool,
+}
+
+/// Draw the backgrounds, text, and decorations of the given grids. Emoji are
+/// drawn separately so that they go over the cursor.
+#[allow(clippy::too_many_arguments)]
+fn draw_grids(
+    encoder: &mut wgpu::CommandEncoder,
+    pipelines: &Pipelines,
+    grids: &[(f32, PixelVec<i32>, &Text)],
+    targets: GridTargets,
+    target_size: PixelVec<u32>,
+    cell_size: Vec2<u32>,
+    clear_color: [f32; 4],
+    underline_offset: i32,
+) {
+    pipelines.default_fill.render(
+        encoder,
+        grids.iter().map(|&(z, _, grid)| (z, grid)),
+        targets.color,
+        targets.depth,
+        target_size,
+        cell_size,
+        clear_color,
+        targets.clear,
+    );
+
+    pipelines.cell_fill.render(
+        encoder,
+        grids.iter().copied(),
+        targets.color,
+        targets.depth,
+        target_size,
+        cell_size,
+    );
+
+    pipelines.monochrome.render(
+        encoder,
+        grids.iter().copied(),
+        targets.monochrome,
+        targets.depth,
+        target_size,
+        cell_size,
+    );
+
+    pipelines.lines.render(
+        encoder,
+        grids.iter().copied(),
+        targets.monochrome,
+        targets.depth,
+        target_size,
+        cell_size,
+        underline_offset,
+    );
+
+    targets.text_blend.render(encoder, targets.color, 1.);
+}
+
+fn clear(encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Clear render pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: target,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+}
+
 #[derive(Debug, thiserror::Error)]
 enum SavePngError {
     #[error("{0}")]
@@ -4,6 +4,11 @@ use neophyte_linalg::PixelVec;
 pub struct Targets {
     pub monochrome: Texture,
     pub color: Texture,
+    /// Translucent windows are drawn here before being blended over the color
+    /// target
+    pub layer: Texture,
+    /// The monochrome target for translucent windows
+    pub layer_monochrome: Texture,
     pub depth: Texture,
     pub png: Texture,
     pub png_staging: wgpu::Buffer,
@@ -12,7 +17,7 @@ pub struct Targets {
 
 impl Targets {
     pub fn new(device: &wgpu::Device, size: PixelVec<u32>) -> Self {
-        let png_size = PixelVec::new(((size.0.x + 63) / 64) * 64, size.0.y);
+        let png_size = PixelVec::new(size.0.x.div_ceil(64) * 64, size.0.y);
         Self {
             monochrome: Texture::target(
                 device,
@@ -32,6 +37,24 @@ impl Targets {
                     Texture::ATTACHMENT_AND_BINDING,
                 ),
             ),
+            layer: Texture::target(
+                device,
+                &Texture::descriptor(
+                    "Layer texture",
+                    size.into(),
+                    Texture::LINEAR_FORMAT,
+                    Texture::ATTACHMENT_AND_BINDING,
+                ),
+            ),
+            layer_monochrome: Texture::target(
+                device,
+                &Texture::descriptor(
+                    "Layer monochrome texture",
+                    size.into(),
+                    Texture::LINEAR_FORMAT,
+                    Texture::ATTACHMENT_AND_BINDING,
+                ),
+            ),
             depth: Texture::target(
                 device,
                 &Texture::descriptor(
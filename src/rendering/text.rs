@@ -1,9 +1,12 @@
 use crate::{
     text::{
         cache::{CacheValue, FontCache, GlyphKind},
-        fonts::{FontStyle, Fonts},
+        emoji,
+        fonts::{FontFamily, FontStyle, Fonts},
+        ligatures::Ligatures,
+        script::{break_runs, direction, language, script_runs, shaping_script},
     },
-    ui::grid::CellContents,
+    ui::grid::{CellContents, Margins, OnceOrChars},
     util::IntoSrgb,
 };
 use bytemuck::{Pod, Zeroable, cast_slice};
@@ -11,11 +14,8 @@ use neophyte_linalg::{CellVec, PixelVec, Vec2};
 use neophyte_ui_event::{hl_attr_define::Attributes, rgb::Rgb};
 use std::num::NonZeroU64;
 use swash::{
-    shape::ShapeContext,
-    text::{
-        Script,
-        cluster::{CharCluster, Parser, Status, Token},
-    },
+    shape::{ShapeContext, cluster::Glyph},
+    text::cluster::{CharCluster, Parser, Status, Token},
 };
 
 pub struct Text {
@@ -31,6 +31,10 @@ pub struct Text {
     lines_bind_group: Option<wgpu::BindGroup>,
     window_position: Option<CellVec<f32>>,
     size: CellVec<u32>,
+    /// Cells around the edges of the window that are not drawn
+    clip: Margins,
+    /// Whether any cell backgrounds are blended with the grids beneath
+    is_translucent: bool,
 }
 
 impl Text {
@@ -50,6 +54,8 @@ impl Text {
             // causing the initial Telescope scroll.
             window_position: None,
             size,
+            clip: Margins::default(),
+            is_translucent: false,
         }
     }
 
@@ -67,10 +73,13 @@ impl Text {
         fonts: &Fonts,
         font_cache: &mut FontCache,
         shape_context: &mut ShapeContext,
+        ligatures: &Ligatures,
+        cursor: Option<CellVec<i32>>,
     ) {
         let metrics = fonts.metrics();
         let metrics_px = metrics.into_pixels();
-        let cell_size = metrics_px.cell_size();
+        let cell_size = fonts.cell_size();
+        let glyph_offset = fonts.glyph_offset();
 
         let default_fg = default_fg.into_srgb(1.);
         let default_bg = default_bg.into_srgb(1.);
@@ -80,16 +89,21 @@ impl Text {
         self.cell_fill.clear();
         self.decoration.clear();
 
-        let mut cluster = CharCluster::new();
         self.size = CellVec(Vec2::new(0, 0));
         let mut line_length = 0;
         for (cell_line_i, cell_line) in lines {
             self.size.0.x = self.size.0.x.max(line_length);
             self.size.0.y += 1;
             line_length = 0;
-            let mut parser = Parser::new(
-                Script::Latin,
-                cell_line.enumerate().flat_map(|(cell_i, cell)| {
+            let line = cell_chars(cell_line.clone());
+            let emoji_cells: Vec<_> = cell_line
+                .clone()
+                .enumerate()
+                .map(|(i, cell)| emoji::is_emoji(cell.text, is_wide(&line, i)))
+                .collect();
+            let tokens: Vec<_> = cell_line
+                .enumerate()
+                .flat_map(|(cell_i, cell)| {
                     cell.text.map(move |c| Token {
                         ch: c,
                         offset: cell_i as u32,
@@ -97,174 +111,121 @@ impl Text {
                         info: c.into(),
                         data: cell.highlight,
                     })
-                }),
-            );
-
-            let mut next_font: Option<BestFont> = None;
-            let mut is_parser_empty = false;
-            while !is_parser_empty {
-                if let Some(current_font_unwrapped) = next_font {
-                    let font_info = fonts.families().nth(current_font_unwrapped.index).unwrap();
-                    let font = font_info.style(current_font_unwrapped.style).unwrap();
-                    let mut shaper = shape_context
-                        .builder(font.as_ref())
-                        .features(font_info.setting.features.iter().cloned().map(|s| s.0))
-                        .variations(font_info.setting.variations.iter().cloned().map(|s| s.0))
-                        .script(Script::Arabic)
-                        .build();
-                    shaper.add_cluster(&cluster);
-
-                    loop {
-                        if !parser.next(&mut cluster) {
-                            is_parser_empty = true;
-                            break;
-                        }
-
-                        let best_font = best_font(&mut cluster, fonts, highlights);
-                        match best_font {
-                            Some(best_font) => {
-                                if current_font_unwrapped == best_font {
-                                    shaper.add_cluster(&cluster);
-                                } else {
-                                    next_font = Some(best_font);
-                                    break;
-                                }
-                            }
-
-                            None => {
-                                next_font = None;
+                })
+                .collect();
+            // Shape emoji on their own so that the parts of a sequence stay
+            // together in their cell
+            let mut breaks: Vec<_> = emoji_cells
+                .iter()
+                .enumerate()
+                .filter(|(_, is_emoji)| **is_emoji)
+                .flat_map(|(i, _)| [i, i + 1])
+                .collect();
+            if let Some(cursor) = cursor
+                && cursor.0.y == cell_line_i
+                && let Ok(x) = usize::try_from(cursor.0.x)
+            {
+                // Keep ligatures from forming across the cursor
+                breaks.extend([x, x + 1]);
+            }
+            breaks.sort_unstable();
+            breaks.dedup();
+
+            for run in break_runs(script_runs(&line), &breaks) {
+                let mut cluster = CharCluster::new();
+                let script = shaping_script(run.script, fonts.arabicshape);
+                let mut parser = Parser::new(
+                    script,
+                    tokens
+                        .iter()
+                        .copied()
+                        .filter(|token| (run.start..run.end).contains(&(token.offset as usize))),
+                );
+
+                let mut next_font: Option<BestFont> = None;
+                let mut is_parser_empty = false;
+                while !is_parser_empty {
+                    if let Some(current_font_unwrapped) = next_font {
+                        let font_info = fonts.family(current_font_unwrapped.index).unwrap();
+                        let font = font_info.style(current_font_unwrapped.style).unwrap();
+                        let is_wide = fonts.is_wide_family(current_font_unwrapped.index);
+                        // Wide fonts are sized separately to fit in two cells
+                        let font_metrics = if is_wide { font.metrics() } else { metrics };
+                        let feature_index = ligatures.feature_index(cluster.user_data());
+                        let mut shaper = shape_context
+                            .builder(font.as_ref())
+                            .features(
+                                font_info
+                                    .setting
+                                    .features
+                                    .iter()
+                                    .chain(ligatures.features(feature_index))
+                                    .map(|s| s.0),
+                            )
+                            .variations(font_info.setting.variations.iter().map(|s| s.0))
+                            .script(script)
+                            .language(language(run.script))
+                            .direction(direction(run.script))
+                            .build();
+                        shaper.add_cluster(&cluster);
+
+                        loop {
+                            if !parser.next(&mut cluster) {
+                                is_parser_empty = true;
                                 break;
                             }
-                        }
-                    }
 
-                    shaper.shape_with(|cluster| {
-                        line_length += 1;
-                        let (fg, is_underlined) = if let Some(hl) = highlights
-                            .get(cluster.data as usize)
-                            .and_then(|hl| (*hl).as_ref())
-                        {
-                            let blend = hl.blend();
-                            let fg = hl.foreground;
-                            let bg = hl.background;
-
-                            if let Some(bg) = if hl.reverse() {
-                                Some(fg.map(|fg| fg.into_srgb(blend)).unwrap_or(default_fg))
-                            } else {
-                                bg.map(|bg| bg.into_srgb(blend))
-                            } {
-                                // Although some programming fonts are said to
-                                // contain ligatures, in practice these are more
-                                // commonly implemented as multi-character alternates.
-                                // In contrast to genuine OpenType ligatures,
-                                // multi-character alternates still get a glyph cluster
-                                // per input char where some of those clusters may
-                                // contain an empty glyph. That means we can produce the
-                                // cell fill characters during shaping without worrying
-                                // too much about whether a glyph cluster spans multiple
-                                // cells. This is something to improve on in the future
-                                // in case some fonts contain actual ligatures.
-                                let bg_cell = BgCell {
-                                    x: cluster.source.start.try_into().unwrap(),
-                                    y: cell_line_i,
-                                    r: bg[0],
-                                    g: bg[1],
-                                    b: bg[2],
-                                    a: bg[3],
-                                };
-                                self.cell_fill.push(bg_cell);
-                            }
+                            let best_font =
+                                best_font(&mut cluster, fonts, font_cache, highlights, &line);
+                            match best_font {
+                                Some(best_font) => {
+                                    if current_font_unwrapped == best_font
+                                        && feature_index
+                                            == ligatures.feature_index(cluster.user_data())
+                                    {
+                                        shaper.add_cluster(&cluster);
+                                    } else {
+                                        next_font = Some(best_font);
+                                        break;
+                                    }
+                                }
 
-                            (
-                                if hl.reverse() {
-                                    bg.map(|bg| bg.into_srgb(blend)).unwrap_or(default_bg)
-                                } else {
-                                    fg.map(|fg| fg.into_srgb(blend)).unwrap_or(default_fg)
-                                },
-                                hl.underline(),
-                            )
-                        } else {
-                            (default_fg, false)
-                        };
-
-                        let x = cluster.source.start * cell_size.x;
-                        let mut advanced = 0.0f32;
-                        for glyph in cluster.glyphs {
-                            let CacheValue { index, kind } = match font_cache.get(
-                                font.as_ref(),
-                                font_info.setting.variations.as_slice(),
-                                metrics.em,
-                                glyph.id,
-                                current_font_unwrapped.style,
-                                current_font_unwrapped.index,
-                            ) {
-                                Some(glyph) => glyph,
                                 None => {
-                                    continue;
+                                    next_font = None;
+                                    break;
                                 }
-                            };
-                            let glyph_index = index as u32;
-
-                            let position = Vec2::new(glyph.x, glyph.y) * metrics.scale_factor;
-                            let position = Vec2::new(
-                                position.x.round() as i32 + x as i32 + advanced.round() as i32,
-                                position.y.round() as i32 + (cell_line_i * cell_size.y as i32),
-                            );
-
-                            if is_underlined {
-                                let line_position = position
-                                    + Vec2::new(
-                                        0,
-                                        (metrics_px.ascent + metrics_px.underline_offset) as i32,
-                                    );
-                                let line_size =
-                                    Vec2::new(metrics_px.width, metrics_px.stroke_size.max(1));
-                                self.decoration.push(Decoration {
-                                    x: line_position.x,
-                                    y: line_position.y,
-                                    w: line_size.x,
-                                    h: line_size.y,
-                                    r: fg[0],
-                                    g: fg[1],
-                                    b: fg[2],
-                                })
-                            }
-
-                            let position = position + Vec2::new(0, metrics_px.em as i32);
-                            match kind {
-                                GlyphKind::Monochrome => self.monochrome.push(MonochromeCell {
-                                    x: position.x,
-                                    y: position.y,
-                                    r: fg[0],
-                                    g: fg[1],
-                                    b: fg[2],
-                                    glyph_index: glyph_index.try_into().unwrap(),
-                                }),
-                                GlyphKind::Emoji => self.emoji.push(EmojiCell {
-                                    x: position.x,
-                                    y: position.y,
-                                    glyph_index: glyph_index.try_into().unwrap(),
-                                }),
                             }
-                            advanced += glyph.advance * metrics.scale_factor;
                         }
-                    });
-                } else {
-                    loop {
-                        let range = cluster.range();
-                        line_length += range.end - range.start;
-                        if let Some(hl) = highlights[cluster.user_data() as usize] {
-                            let bg = if hl.reverse() {
-                                hl.foreground
-                            } else {
-                                hl.background
-                            };
 
-                            if let Some(bg) = bg {
-                                let bg = bg.into_srgb(hl.blend());
-                                for i in range.start..range.end {
+                        shaper.shape_with(|cluster| {
+                            line_length += 1;
+                            let (fg, is_underlined) = if let Some(hl) = highlights
+                                .get(cluster.data as usize)
+                                .and_then(|hl| (*hl).as_ref())
+                            {
+                                let blend = hl.blend();
+                                let fg = hl.foreground;
+                                let bg = hl.background;
+
+                                if let Some(bg) = if hl.reverse() {
+                                    Some(fg.map(|fg| fg.into_srgb(blend)).unwrap_or(default_fg))
+                                } else {
+                                    bg.map(|bg| bg.into_srgb(blend))
+                                        .or_else(|| blended_default(default_bg, blend))
+                                } {
+                                    // Although some programming fonts are said to
+                                    // contain ligatures, in practice these are more
+                                    // commonly implemented as multi-character alternates.
+                                    // In contrast to genuine OpenType ligatures,
+                                    // multi-character alternates still get a glyph cluster
+                                    // per input char where some of those clusters may
+                                    // contain an empty glyph. That means we can produce the
+                                    // cell fill characters during shaping without worrying
+                                    // too much about whether a glyph cluster spans multiple
+                                    // cells. This is something to improve on in the future
+                                    // in case some fonts contain actual ligatures.
                                     let bg_cell = BgCell {
-                                        x: (i * cell_size.x).try_into().unwrap(),
+                                        x: cluster.source.start.try_into().unwrap(),
                                         y: cell_line_i,
                                         r: bg[0],
                                         g: bg[1],
@@ -273,23 +234,177 @@ impl Text {
                                     };
                                     self.cell_fill.push(bg_cell);
                                 }
+
+                                (
+                                    if hl.reverse() {
+                                        bg.map(|bg| bg.into_srgb(blend)).unwrap_or(default_bg)
+                                    } else {
+                                        fg.map(|fg| fg.into_srgb(blend)).unwrap_or(default_fg)
+                                    },
+                                    hl.underline(),
+                                )
+                            } else {
+                                (default_fg, false)
+                            };
+
+                            let x = cluster.source.start * cell_size.x;
+                            let cell_i = cluster.source.start as usize;
+                            let fit = if emoji_cells.get(cell_i).copied().unwrap_or_default() {
+                                emoji_fit(&line, cell_i, cluster.glyphs, cell_size)
+                            } else {
+                                symbol_fit(fonts, &line, cell_i, cluster.glyphs.len(), cell_size)
+                            };
+                            // For fitted glyphs, the number of boxes to the left
+                            let mut fit_slot = 0;
+                            let mut advanced = if is_wide {
+                                // Center the glyph in its two cells
+                                let advance: f32 = cluster.glyphs.iter().map(|g| g.advance).sum();
+                                let advance = advance * font_metrics.scale_factor;
+                                ((cell_size.x * 2) as f32 - advance).max(0.) / 2.
+                            } else {
+                                glyph_offset.x as f32
+                            };
+                            for glyph in cluster.glyphs {
+                                let CacheValue { index, kind } = match font_cache.get(
+                                    font.as_ref(),
+                                    &font_info.setting,
+                                    font_metrics.em,
+                                    glyph.id,
+                                    current_font_unwrapped.style,
+                                    current_font_unwrapped.index,
+                                    fit,
+                                ) {
+                                    Some(glyph) => glyph,
+                                    None => {
+                                        continue;
+                                    }
+                                };
+                                let glyph_index = index as u32;
+
+                                let position =
+                                    Vec2::new(glyph.x, glyph.y) * font_metrics.scale_factor;
+                                let position = Vec2::new(
+                                    position.x.round() as i32 + x as i32 + advanced.round() as i32,
+                                    position.y.round() as i32
+                                        + (cell_line_i * cell_size.y as i32)
+                                        + glyph_offset.y as i32,
+                                );
+
+                                if is_underlined {
+                                    let line_position = position
+                                        + Vec2::new(
+                                            0,
+                                            (metrics_px.ascent + metrics_px.underline_offset)
+                                                as i32,
+                                        );
+                                    // Span the whole cell so that underlines stay
+                                    // continuous with letter spacing
+                                    let line_size =
+                                        Vec2::new(cell_size.x, metrics_px.stroke_size.max(1));
+                                    self.decoration.push(Decoration {
+                                        x: x as i32,
+                                        y: line_position.y,
+                                        w: line_size.x,
+                                        h: line_size.y,
+                                        r: fg[0],
+                                        g: fg[1],
+                                        b: fg[2],
+                                    })
+                                }
+
+                                let position = if let Some(fit) = fit {
+                                    // Fitted glyphs are offset from the corner of
+                                    // their box
+                                    let box_x = x + fit_slot * fit.x;
+                                    if glyph.advance > 0. {
+                                        fit_slot += 1;
+                                    }
+                                    Vec2::new(box_x as i32, cell_line_i * cell_size.y as i32)
+                                } else {
+                                    position + Vec2::new(0, metrics_px.em as i32)
+                                };
+                                match kind {
+                                    GlyphKind::Monochrome => self.monochrome.push(MonochromeCell {
+                                        x: position.x,
+                                        y: position.y,
+                                        r: fg[0],
+                                        g: fg[1],
+                                        b: fg[2],
+                                        glyph_index: glyph_index.try_into().unwrap(),
+                                    }),
+                                    GlyphKind::Emoji => self.emoji.push(EmojiCell {
+                                        x: position.x,
+                                        y: position.y,
+                                        glyph_index: glyph_index.try_into().unwrap(),
+                                    }),
+                                }
+                                advanced += glyph.advance * font_metrics.scale_factor;
                             }
-                        }
+                        });
+                    } else {
+                        loop {
+                            let range = cluster.range();
+                            line_length += range.end - range.start;
+                            let hl = highlights[cluster.user_data() as usize];
+                            if let Some(hl) = hl {
+                                let bg = if hl.reverse() {
+                                    hl.foreground
+                                } else {
+                                    hl.background
+                                };
 
-                        if !parser.next(&mut cluster) {
-                            is_parser_empty = true;
-                            break;
-                        }
+                                let blend = hl.blend();
+                                if let Some(bg) = bg
+                                    .map(|bg| bg.into_srgb(blend))
+                                    .or_else(|| blended_default(default_bg, blend))
+                                {
+                                    for i in range.start..range.end {
+                                        let bg_cell = BgCell {
+                                            x: i.try_into().unwrap(),
+                                            y: cell_line_i,
+                                            r: bg[0],
+                                            g: bg[1],
+                                            b: bg[2],
+                                            a: bg[3],
+                                        };
+                                        self.cell_fill.push(bg_cell);
+                                    }
+                                }
+                            }
+
+                            if let Some(c) = box_drawing_char(&cluster, font_cache)
+                                && let Some(CacheValue { index, kind: _ }) =
+                                    font_cache.get_box_drawing(c, cell_size)
+                            {
+                                let fg = foreground(hl.as_ref(), default_fg, default_bg);
+                                self.monochrome.push(MonochromeCell {
+                                    x: (range.start * cell_size.x).try_into().unwrap(),
+                                    y: cell_line_i * cell_size.y as i32,
+                                    r: fg[0],
+                                    g: fg[1],
+                                    b: fg[2],
+                                    glyph_index: index.try_into().unwrap(),
+                                });
+                            }
+
+                            if !parser.next(&mut cluster) {
+                                is_parser_empty = true;
+                                break;
+                            }
 
-                        if let Some(best_font) = best_font(&mut cluster, fonts, highlights) {
-                            next_font = Some(best_font);
-                            break;
+                            if let Some(best_font) =
+                                best_font(&mut cluster, fonts, font_cache, highlights, &line)
+                            {
+                                next_font = Some(best_font);
+                                break;
+                            }
                         }
                     }
                 }
             }
         }
 
+        self.is_translucent = self.cell_fill.iter().any(|cell| cell.a < 1.);
         self.size.0.x = self.size.0.x.max(line_length);
         if let Some(size) = size {
             self.size = size;
@@ -406,6 +521,11 @@ impl Text {
         self.window_position = window_position;
     }
 
+    /// Set the cells around the edges of the window that are not drawn
+    pub fn set_clip(&mut self, clip: Margins) {
+        self.clip = clip;
+    }
+
     pub fn cell_fill_bind_group(&self) -> Option<&wgpu::BindGroup> {
         self.cell_fill_bind_group.as_ref()
     }
@@ -430,6 +550,33 @@ impl Text {
         self.window_position
     }
 
+    /// The position and size of the region of the window that is drawn, in
+    /// pixels
+    pub fn clip_region(&self, cell_size: Vec2<u32>) -> Option<(PixelVec<i32>, PixelVec<u32>)> {
+        let offset =
+            self.window_position? + CellVec::new(self.clip.left as f32, self.clip.top as f32);
+        let size = CellVec::new(
+            self.size
+                .0
+                .x
+                .saturating_sub(self.clip.left + self.clip.right),
+            self.size
+                .0
+                .y
+                .saturating_sub(self.clip.top + self.clip.bottom),
+        );
+        Some((
+            offset.round_to_pixels(cell_size),
+            size.into_pixels(cell_size),
+        ))
+    }
+
+    /// Whether the grid must be drawn over the grids beneath it rather than
+    /// occluding them
+    pub fn is_translucent(&self) -> bool {
+        self.is_translucent
+    }
+
     pub fn cell_fill_count(&self) -> u32 {
         self.cell_fill.len().try_into().unwrap()
     }
@@ -472,18 +619,139 @@ pub fn set_scissor(
     render_pass.set_scissor_rect(offset.x, offset.y, size.x, size.y);
 }
 
+/// The first char of each cell in the line, where empty continuation cells
+/// following a double-width character are '\0' and hidden cells are HIDDEN
+fn cell_chars<'a>(line: impl Iterator<Item = CellContents<'a>>) -> Vec<char> {
+    line.map(|mut cell| match cell.text {
+        OnceOrChars::Hidden => HIDDEN,
+        _ => cell.text.next().unwrap_or('\0'),
+    })
+    .collect()
+}
+
+/// Stands in for hidden cells, which are neither spaces nor continuation cells
+/// so that the glyphs beside them are not stretched over them
+const HIDDEN: char = '\u{FFFC}';
+
+/// Whether the cell contains a double-width character, indicated by the
+/// following cell being an empty continuation cell
+fn is_wide(line: &[char], i: usize) -> bool {
+    line.get(i).is_some_and(|&c| c != '\0') && line.get(i + 1) == Some(&'\0')
+}
+
+/// If the cell contains a symbol that should be scaled to fit the cell, the
+/// size of the box to fit it in. Symbols followed by a space or a continuation
+/// cell get two cells.
+fn symbol_fit(
+    fonts: &Fonts,
+    line: &[char],
+    i: usize,
+    glyph_count: usize,
+    cell_size: Vec2<u32>,
+) -> Option<Vec2<u32>> {
+    let c = *line.get(i)?;
+    if glyph_count != 1 || !fonts.is_symbol(c) {
+        return None;
+    }
+    let cells = if matches!(line.get(i + 1), Some(' ' | '\0')) {
+        2
+    } else {
+        1
+    };
+    Some(Vec2::new(cell_size.x * cells, cell_size.y))
+}
+
+/// The size of the box to fit each glyph of an emoji cell in. The emoji fills
+/// its cell, or both cells if it is double-width. If the font could not combine
+/// a sequence into one glyph, the parts share the space side by side.
+fn emoji_fit(line: &[char], i: usize, glyphs: &[Glyph], cell_size: Vec2<u32>) -> Option<Vec2<u32>> {
+    let cells = if is_wide(line, i) { 2 } else { 1 };
+    let parts = glyphs
+        .iter()
+        .filter(|glyph| glyph.advance > 0.)
+        .count()
+        .max(1);
+    Some(Vec2::new(cell_size.x * cells / parts as u32, cell_size.y))
+}
+
+/// The character in the cluster if it should be drawn procedurally rather than
+/// with a font
+fn box_drawing_char(cluster: &CharCluster, font_cache: &FontCache) -> Option<char> {
+    match cluster.chars() {
+        [c] if font_cache.is_box_drawing(c.ch) => Some(c.ch),
+        _ => None,
+    }
+}
+
+/// Blended cells without a background of their own still blend the default
+/// background with the grids beneath
+fn blended_default(default_bg: [f32; 4], blend: f32) -> Option<[f32; 4]> {
+    (blend < 1.).then_some([default_bg[0], default_bg[1], default_bg[2], blend])
+}
+
+/// The text color for the given highlight
+fn foreground(hl: Option<&Attributes>, default_fg: [f32; 4], default_bg: [f32; 4]) -> [f32; 4] {
+    match hl {
+        Some(hl) => {
+            let blend = hl.blend();
+            if hl.reverse() {
+                hl.background
+                    .map(|bg| bg.into_srgb(blend))
+                    .unwrap_or(default_bg)
+            } else {
+                hl.foreground
+                    .map(|fg| fg.into_srgb(blend))
+                    .unwrap_or(default_fg)
+            }
+        }
+        None => default_fg,
+    }
+}
+
 fn best_font(
     cluster: &mut CharCluster,
     fonts: &Fonts,
+    font_cache: &FontCache,
     highlights: &[Option<Attributes>],
+    line: &[char],
 ) -> Option<BestFont> {
+    // Procedural glyphs take priority over fonts
+    if box_drawing_char(cluster, font_cache).is_some() {
+        return None;
+    }
+
     let style = highlights
         .get(cluster.user_data() as usize)
         .and_then(|hl| (*hl).as_ref())
         .map(|highlight| FontStyle::new(highlight.bold(), highlight.italic()))
         .unwrap_or_default();
+
+    if let Some(family) = fonts.highlight_family(cluster.user_data())
+        && let Some(best_font) = best_font_in(cluster, std::iter::once(family), style)
+    {
+        return Some(best_font);
+    }
+
+    if is_wide(line, cluster.range().start as usize) {
+        let wide_families = fonts
+            .wide_families()
+            .enumerate()
+            .map(|(i, family)| (i + fonts.wide_start(), family));
+        if let Some(best_font) = best_font_in(cluster, wide_families, style) {
+            return Some(best_font);
+        }
+    }
+
+    best_font_in(cluster, fonts.families().enumerate(), style)
+}
+
+fn best_font_in<'a>(
+    cluster: &mut CharCluster,
+    families: impl Iterator<Item = (usize, &'a FontFamily)>,
+    style: FontStyle,
+) -> Option<BestFont> {
     let mut best_font = None;
-    for (i, font_info) in fonts.families().enumerate() {
+    for (i, font_info) in families {
         if let Some(font) = &font_info.style(style) {
             match cluster.map(|c| font.charmap().map(c)) {
                 Status::Discard => {}
@@ -498,15 +766,15 @@ fn best_font(
             }
         }
 
-        if style != FontStyle::Regular {
-            if let Some(font) = &font_info.regular {
-                match cluster.map(|c| font.charmap().map(c)) {
-                    Status::Discard => {}
-                    Status::Keep => best_font = Some(BestFont::new(i, FontStyle::Regular)),
-                    Status::Complete => {
-                        best_font = Some(BestFont::new(i, FontStyle::Regular));
-                        break;
-                    }
+        if style != FontStyle::Regular
+            && let Some(font) = &font_info.regular
+        {
+            match cluster.map(|c| font.charmap().map(c)) {
+                Status::Discard => {}
+                Status::Keep => best_font = Some(BestFont::new(i, FontStyle::Regular)),
+                Status::Complete => {
+                    best_font = Some(BestFont::new(i, FontStyle::Regular));
+                    break;
                 }
             }
         }
@@ -591,3 +859,27 @@ impl BindGroupLayout {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(text: &str) -> CellContents<'_> {
+        CellContents {
+            highlight: 0,
+            text: text.chars().into(),
+        }
+    }
+
+    #[test]
+    fn hidden_cells_do_not_widen_the_cell_before() {
+        let hidden = CellContents {
+            highlight: 0,
+            text: OnceOrChars::Hidden,
+        };
+        let line = cell_chars([cell("a"), hidden, cell("好"), cell("")].into_iter());
+        assert_eq!(line, ['a', HIDDEN, '好', '\0']);
+        assert!(!is_wide(&line, 0));
+        assert!(is_wide(&line, 2));
+    }
+}
@@ -0,0 +1,647 @@
+//! Messages from msg_show drawn as cards that stack in a corner of the window
+//! and dismiss themselves after a timeout
+
+use super::{
+    Motion,
+    message_grids::lines,
+    text::Text,
+    transition::{FloatTransition, Transition},
+};
+use crate::{
+    text::{cache::FontCache, fonts::Fonts, ligatures::Ligatures},
+    ui::{
+        Ui,
+        grid::CellContents,
+        messages::{Messages, ShownMessage},
+        width::CharWidth,
+    },
+    util::IntoSrgb,
+};
+use neophyte_linalg::{CellVec, Vec2};
+use neophyte_ui_event::{Content, MaybeInto, Parse, msg_show::Kind, rgb::Rgb};
+use std::{collections::HashMap, time::Duration};
+use swash::shape::ShapeContext;
+
+/// How toasts are drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToastSettings {
+    pub enabled: bool,
+    /// Seconds before a toast dismisses itself, or zero to keep toasts until
+    /// Neovim clears the messages
+    pub timeout: f32,
+    /// The corner of the window that toasts stack from
+    pub corner: Corner,
+    /// Seconds for toasts to fade in and out and to move as the stack changes
+    pub duration: f32,
+    /// Radius of the rounded corners in pixels
+    pub radius: f32,
+}
+
+impl ToastSettings {
+    fn transition(self) -> FloatTransition {
+        FloatTransition {
+            duration: self.duration,
+            fade: true,
+            // Slide in from the edge of the window
+            slide: if self.corner.is_top() { 1. } else { -1. },
+            moves: true,
+        }
+    }
+}
+
+impl Default for ToastSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout: 4.,
+            corner: Corner::default(),
+            duration: 0.2,
+            radius: 6.,
+        }
+    }
+}
+
+impl Parse for ToastSettings {
+    fn parse(value: rmpv::Value) -> Option<Self> {
+        let rmpv::Value::Map(map) = value else {
+            return None;
+        };
+        let mut out = Self {
+            enabled: true,
+            ..Self::default()
+        };
+        for (k, v) in map {
+            match k.as_str()? {
+                "enabled" => out.enabled = v.maybe_into()?,
+                "timeout" => out.timeout = v.maybe_into()?,
+                "corner" => out.corner = v.maybe_into()?,
+                "duration" => out.duration = v.maybe_into()?,
+                "radius" => out.radius = v.maybe_into()?,
+                _ => {}
+            }
+        }
+        let is_valid = |n: f32| n.is_finite() && n >= 0.;
+        (is_valid(out.timeout) && is_valid(out.duration) && is_valid(out.radius)).then_some(out)
+    }
+}
+
+/// A corner of the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Corner {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    fn is_top(self) -> bool {
+        matches!(self, Self::TopLeft | Self::TopRight)
+    }
+
+    fn is_left(self) -> bool {
+        matches!(self, Self::TopLeft | Self::BottomLeft)
+    }
+}
+
+impl Parse for Corner {
+    fn parse(value: rmpv::Value) -> Option<Self> {
+        match String::parse(value)?.as_str() {
+            "top_left" => Some(Self::TopLeft),
+            "top_right" => Some(Self::TopRight),
+            "bottom_left" => Some(Self::BottomLeft),
+            "bottom_right" => Some(Self::BottomRight),
+            _ => None,
+        }
+    }
+}
+
+/// Cells between the toasts and the edges of the window
+const MARGIN: Vec2<f32> = Vec2::new(1., 1.);
+/// Rows between stacked toasts
+const GAP: f32 = 0.5;
+
+struct Toast {
+    id: u64,
+    kind: Kind,
+    content: Content,
+    text: Text,
+    /// The size of the card in cells, including padding
+    size: CellVec<u32>,
+    /// Seconds since the toast was shown or its message replaced
+    age: f32,
+    /// The row of the top of the card in the stack, or None before the first
+    /// layout
+    row: Option<f32>,
+    transition: Transition,
+    is_closing: bool,
+}
+
+impl Toast {
+    fn close(&mut self, settings: ToastSettings) {
+        if !self.is_closing {
+            self.is_closing = true;
+            self.transition.close(settings.transition());
+        }
+    }
+}
+
+/// A toast to draw
+pub struct Card<'a> {
+    pub text: &'a Text,
+    /// Color with straight alpha for the outline, from the kind of message
+    pub accent: [f32; 4],
+    pub opacity: f32,
+}
+
+/// Toasts for the messages shown with msg_show, ordered from the corner
+/// outward
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+    /// The contents of messages that timed out but that Neovim has not
+    /// cleared. A replaced message is shown again.
+    dismissed: HashMap<u64, Content>,
+    accents: HashMap<Kind, [f32; 4]>,
+    base_grid_size: Vec2<u16>,
+    previous_settings: Option<ToastSettings>,
+}
+
+impl Toasts {
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        ui: &Ui,
+        settings: ToastSettings,
+        grid_bind_group_layout: &wgpu::BindGroupLayout,
+        default_fg: Rgb,
+        default_bg: Rgb,
+        fonts: &Fonts,
+        font_cache: &mut FontCache,
+        shape_context: &mut ShapeContext,
+        ligatures: &Ligatures,
+    ) {
+        let base_grid_size = ui.grids[0].contents().size.0;
+        let did_size_change = self.base_grid_size != base_grid_size;
+        self.base_grid_size = base_grid_size;
+        let did_settings_change = self.previous_settings != Some(settings);
+        self.previous_settings = Some(settings);
+        if !settings.enabled {
+            self.toasts.clear();
+            self.dismissed.clear();
+            return;
+        }
+        if !(ui.messages.dirty
+            || did_size_change
+            || did_settings_change
+            || ui.did_highlights_change)
+        {
+            return;
+        }
+
+        self.sync(&ui.messages, ui.char_width, settings);
+        self.accents.clear();
+        let max_size = self.max_size();
+        for toast in self.toasts.iter_mut() {
+            let lines = padded_lines(&toast.content, ui.char_width, max_size);
+            toast.text.update_contents(
+                device,
+                queue,
+                Some(toast.size),
+                lines
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, c)| (i as i32, c.into_iter())),
+                grid_bind_group_layout,
+                &ui.highlights,
+                default_fg,
+                default_bg,
+                fonts,
+                font_cache,
+                shape_context,
+                ligatures,
+                None,
+            );
+            self.accents
+                .entry(toast.kind)
+                .or_insert_with(|| accent(toast.kind, ui));
+        }
+        self.place(settings);
+    }
+
+    /// Match the toasts to the messages that are shown
+    fn sync(&mut self, messages: &Messages, char_width: CharWidth, settings: ToastSettings) {
+        let is_shown = |id| messages.show.iter().any(|message| message.id == id);
+        self.dismissed.retain(|&id, _| is_shown(id));
+        for toast in self.toasts.iter_mut() {
+            if !is_shown(toast.id) {
+                toast.close(settings);
+            }
+        }
+
+        for message in messages.show.iter() {
+            let ShownMessage { id, kind, content } = message;
+            if self
+                .dismissed
+                .get(id)
+                .is_some_and(|dismissed| dismissed == content)
+            {
+                continue;
+            }
+            self.dismissed.remove(id);
+            let lines = padded_lines(content, char_width, self.max_size());
+            let size = CellVec::new(
+                lines
+                    .iter()
+                    .map(|line| line.len() as u32)
+                    .max()
+                    .unwrap_or(0),
+                lines.len() as u32,
+            );
+            let existing = self
+                .toasts
+                .iter_mut()
+                .find(|toast| toast.id == *id && !toast.is_closing);
+            match existing {
+                _ if lines.is_empty() => {
+                    if let Some(toast) = existing {
+                        toast.close(settings);
+                    }
+                }
+                Some(toast) => {
+                    if toast.content != *content || toast.kind != *kind {
+                        toast.age = 0.;
+                    }
+                    toast.kind = *kind;
+                    toast.content = content.clone();
+                    toast.size = size;
+                }
+                None => {
+                    let mut transition = Transition::default();
+                    transition.open(settings.transition());
+                    self.toasts.push(Toast {
+                        id: *id,
+                        kind: *kind,
+                        content: content.clone(),
+                        text: Text::new(size),
+                        size,
+                        age: 0.,
+                        row: None,
+                        transition,
+                        is_closing: false,
+                    });
+                }
+            }
+        }
+        self.layout(settings);
+    }
+
+    /// The largest card that fits in the window, in cells
+    fn max_size(&self) -> Vec2<u32> {
+        let fit = |size: u16, margin: f32| (size as f32 - margin * 2.).max(1.) as u32;
+        Vec2::new(
+            fit(self.base_grid_size.x, MARGIN.x),
+            fit(self.base_grid_size.y, MARGIN.y),
+        )
+    }
+
+    /// Stack the open toasts from the corner. Toasts glide to their new rows.
+    fn layout(&mut self, settings: ToastSettings) {
+        let window_height = self.base_grid_size.y as f32;
+        let mut distance = MARGIN.y;
+        for toast in self.toasts.iter_mut().filter(|toast| !toast.is_closing) {
+            let height = toast.size.0.y as f32;
+            let row = if settings.corner.is_top() {
+                distance
+            } else {
+                window_height - distance - height
+            };
+            if let Some(previous) = toast.row {
+                toast
+                    .transition
+                    .move_by(CellVec::new(0., row - previous), settings.duration);
+            }
+            toast.row = Some(row);
+            distance += height + GAP;
+        }
+    }
+
+    /// Move the text of each toast to where it is drawn
+    fn place(&mut self, settings: ToastSettings) {
+        let window_width = self.base_grid_size.x as f32;
+        for toast in self.toasts.iter_mut() {
+            let Some(row) = toast.row else {
+                continue;
+            };
+            let col = if settings.corner.is_left() {
+                MARGIN.x
+            } else {
+                window_width - MARGIN.x - toast.size.0.x as f32
+            };
+            let position = CellVec::new(col, row) + toast.transition.offset();
+            toast.text.update_window(Some(position));
+        }
+    }
+
+    pub fn advance(&mut self, delta_time: Duration, settings: ToastSettings) -> Motion {
+        let mut motion = Motion::Still;
+        self.toasts.retain_mut(|toast| {
+            let is_animating = toast.transition.advance(delta_time);
+            if is_animating {
+                motion = Motion::Animating;
+            }
+            is_animating || !toast.is_closing
+        });
+
+        let mut did_dismiss = false;
+        for toast in self.toasts.iter_mut().filter(|toast| !toast.is_closing) {
+            toast.age += delta_time.as_secs_f32();
+            // Prompts stay until Neovim clears them since it is still waiting
+            // for an answer
+            if settings.timeout == 0. || toast.kind.is_prompt() {
+                continue;
+            }
+            if toast.age >= settings.timeout {
+                toast.close(settings);
+                self.dismissed.insert(toast.id, toast.content.clone());
+                did_dismiss = true;
+            } else {
+                motion = motion.soonest(Motion::delay(settings.timeout - toast.age));
+            }
+        }
+        if did_dismiss {
+            // Fade out the dismissed toasts and close the gaps they leave
+            self.layout(settings);
+            motion = Motion::Animating;
+        }
+
+        self.place(settings);
+        motion
+    }
+
+    /// The toasts to draw, from the corner outward
+    pub fn cards(&self) -> impl Iterator<Item = Card<'_>> {
+        self.toasts.iter().map(|toast| Card {
+            text: &toast.text,
+            accent: self
+                .accents
+                .get(&toast.kind)
+                .copied()
+                .unwrap_or(FALLBACK_ACCENT.into_srgb(1.)),
+            opacity: toast.transition.opacity(),
+        })
+    }
+}
+
+const FALLBACK_ACCENT: Rgb = Rgb::new(128, 128, 128);
+
+/// The outline color for a kind of message, taken from the foreground of the
+/// matching highlight group
+fn accent(kind: Kind, ui: &Ui) -> [f32; 4] {
+    let group = match kind {
+        _ if kind.is_error() => "ErrorMsg",
+        Kind::Wmsg => "WarningMsg",
+        _ if kind.is_prompt() => "Question",
+        Kind::Echo | Kind::Echomsg | Kind::LuaPrint => "MoreMsg",
+        _ => "FloatBorder",
+    };
+    ui.highlight_groups
+        .get(group)
+        .and_then(|&id| ui.highlights.get(id as usize)?.as_ref()?.foreground)
+        .unwrap_or(FALLBACK_ACCENT)
+        .into_srgb(1.)
+}
+
+/// The lines of a message with a cell of padding on either side, wrapped to
+/// the given width and cut off at the given height
+fn padded_lines(
+    content: &Content,
+    char_width: CharWidth,
+    max_size: Vec2<u32>,
+) -> Vec<Vec<CellContents<'_>>> {
+    let width = (max_size.x as usize).saturating_sub(2).max(2);
+    let mut wrapped = vec![];
+    for mut line in lines(content, char_width) {
+        while line.len() > width {
+            // Keep wide characters together with their continuation cells
+            let is_continuation = line[width].text.clone().next().is_none();
+            let rest = line.split_off(width - is_continuation as usize);
+            wrapped.push(line);
+            line = rest;
+        }
+        wrapped.push(line);
+    }
+    wrapped.truncate(max_size.y.max(1) as usize);
+    for line in wrapped.iter_mut() {
+        line.insert(0, char_width.cells(" ", 0).next().unwrap());
+        line.extend(char_width.cells(" ", 0));
+    }
+    wrapped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neophyte_ui_event::{MsgShow, msg_show::ReplaceLast};
+    use rmpv::Value;
+
+    fn settings() -> ToastSettings {
+        ToastSettings {
+            enabled: true,
+            ..ToastSettings::default()
+        }
+    }
+
+    fn show(messages: &mut Messages, text: &str, replace_last: ReplaceLast) {
+        show_kind(messages, Kind::Echo, text, replace_last);
+    }
+
+    fn show_kind(messages: &mut Messages, kind: Kind, text: &str, replace_last: ReplaceLast) {
+        messages.show(MsgShow {
+            kind,
+            content: Content::parse(Value::Array(vec![Value::Array(vec![
+                0.into(),
+                text.into(),
+            ])]))
+            .unwrap(),
+            replace_last,
+        });
+    }
+
+    fn toasts() -> Toasts {
+        Toasts {
+            base_grid_size: Vec2::new(80, 24),
+            ..Toasts::default()
+        }
+    }
+
+    fn rows(toasts: &Toasts) -> Vec<Option<f32>> {
+        toasts.toasts.iter().map(|toast| toast.row).collect()
+    }
+
+    #[test]
+    fn stacks_from_the_corner() {
+        let mut messages = Messages::default();
+        show(&mut messages, "one", ReplaceLast::Keep);
+        show(&mut messages, "two\nlines", ReplaceLast::Keep);
+        show(&mut messages, "three", ReplaceLast::Keep);
+        let mut toasts = toasts();
+        toasts.sync(&messages, CharWidth::default(), settings());
+        assert_eq!(rows(&toasts), [Some(1.), Some(2.5), Some(5.)]);
+        assert_eq!(toasts.toasts[1].size, CellVec::new(7, 2));
+
+        let settings = ToastSettings {
+            corner: Corner::BottomLeft,
+            ..settings()
+        };
+        toasts.sync(&messages, CharWidth::default(), settings);
+        assert_eq!(rows(&toasts), [Some(22.), Some(19.5), Some(18.)]);
+    }
+
+    #[test]
+    fn wraps_to_the_window() {
+        let mut messages = Messages::default();
+        show(&mut messages, &"a".repeat(100), ReplaceLast::Keep);
+        show(&mut messages, &"b\n".repeat(40), ReplaceLast::Keep);
+        let mut toasts = toasts();
+        toasts.sync(&messages, CharWidth::default(), settings());
+        assert_eq!(toasts.toasts[0].size, CellVec::new(78, 2));
+        assert_eq!(toasts.toasts[1].size, CellVec::new(3, 22));
+    }
+
+    #[test]
+    fn wrapping_keeps_wide_characters_whole() {
+        let mut messages = Messages::default();
+        show(
+            &mut messages,
+            &format!("a{}", "字".repeat(40)),
+            ReplaceLast::Keep,
+        );
+        let content = &messages.show[0].content;
+        let lines = padded_lines(content, CharWidth::default(), Vec2::new(10, 10));
+        let widths: Vec<_> = lines.iter().map(|line| line.len()).collect();
+        assert_eq!(widths, [9, 10, 10, 10, 10, 10, 10, 10, 10, 10]);
+    }
+
+    #[test]
+    fn replacing_updates_in_place() {
+        let mut messages = Messages::default();
+        show(&mut messages, "one", ReplaceLast::Keep);
+        show(&mut messages, "two", ReplaceLast::Keep);
+        let mut toasts = toasts();
+        toasts.sync(&messages, CharWidth::default(), settings());
+        toasts.advance(Duration::from_secs(1), settings());
+        show(&mut messages, "three", ReplaceLast::Replace);
+        toasts.sync(&messages, CharWidth::default(), settings());
+        assert_eq!(toasts.toasts.len(), 2);
+        assert_eq!(toasts.toasts[1].content, messages.show[1].content);
+        assert_eq!(toasts.toasts[0].age, 1.);
+        assert_eq!(toasts.toasts[1].age, 0.);
+    }
+
+    #[test]
+    fn dismisses_after_timeout() {
+        let mut messages = Messages::default();
+        show(&mut messages, "one", ReplaceLast::Keep);
+        show(&mut messages, "two", ReplaceLast::Keep);
+        let mut toasts = toasts();
+        toasts.sync(&messages, CharWidth::default(), settings());
+        toasts.advance(Duration::from_secs(1), settings());
+        show(&mut messages, "three", ReplaceLast::Keep);
+        toasts.sync(&messages, CharWidth::default(), settings());
+        assert!(matches!(
+            toasts.advance(Duration::from_secs(2), settings()),
+            Motion::Delay(_)
+        ));
+
+        // The first two time out and the third moves up to the corner
+        assert_eq!(
+            toasts.advance(Duration::from_millis(1500), settings()),
+            Motion::Animating
+        );
+        assert_eq!(toasts.toasts.iter().filter(|t| t.is_closing).count(), 2);
+        assert_eq!(toasts.toasts[2].row, Some(1.));
+        toasts.advance(Duration::from_millis(300), settings());
+        assert_eq!(toasts.toasts.len(), 1);
+
+        // Timed out messages stay hidden
+        toasts.sync(&messages, CharWidth::default(), settings());
+        assert_eq!(toasts.toasts.len(), 1);
+    }
+
+    #[test]
+    fn prompts_do_not_time_out() {
+        let mut messages = Messages::default();
+        show_kind(
+            &mut messages,
+            Kind::Confirm,
+            "Save changes?",
+            ReplaceLast::Keep,
+        );
+        show_kind(
+            &mut messages,
+            Kind::ReturnPrompt,
+            "Press ENTER",
+            ReplaceLast::Keep,
+        );
+        let mut toasts = toasts();
+        toasts.sync(&messages, CharWidth::default(), settings());
+        assert_eq!(
+            toasts.advance(Duration::from_secs(60), settings()),
+            Motion::Still
+        );
+        assert!(toasts.toasts.iter().all(|toast| !toast.is_closing));
+    }
+
+    #[test]
+    fn replacing_shows_a_dismissed_message_again() {
+        let mut messages = Messages::default();
+        show(&mut messages, "one", ReplaceLast::Keep);
+        let mut toasts = toasts();
+        toasts.sync(&messages, CharWidth::default(), settings());
+        toasts.advance(Duration::from_secs(5), settings());
+        toasts.advance(Duration::from_secs(1), settings());
+        toasts.sync(&messages, CharWidth::default(), settings());
+        assert!(toasts.toasts.is_empty());
+        show(&mut messages, "two", ReplaceLast::Replace);
+        toasts.sync(&messages, CharWidth::default(), settings());
+        assert_eq!(toasts.toasts.len(), 1);
+    }
+
+    #[test]
+    fn clearing_messages_closes_toasts() {
+        let mut messages = Messages::default();
+        show(&mut messages, "one", ReplaceLast::Keep);
+        let mut toasts = toasts();
+        toasts.sync(&messages, CharWidth::default(), settings());
+        messages.show.clear();
+        toasts.sync(&messages, CharWidth::default(), settings());
+        assert!(toasts.toasts[0].is_closing);
+        toasts.advance(Duration::from_secs(1), settings());
+        assert!(toasts.toasts.is_empty());
+        assert!(toasts.dismissed.is_empty());
+    }
+
+    #[test]
+    fn parses_settings() {
+        let value = Value::Map(vec![
+            ("timeout".into(), 2.into()),
+            ("corner".into(), "bottom_left".into()),
+        ]);
+        let settings = ToastSettings::parse(value).unwrap();
+        assert!(settings.enabled);
+        assert_eq!(settings.timeout, 2.);
+        assert_eq!(settings.corner, Corner::BottomLeft);
+        let value = Value::Map(vec![("corner".into(), "middle".into())]);
+        assert_eq!(ToastSettings::parse(value), None);
+        for key in ["timeout", "duration", "radius"] {
+            for n in [f64::INFINITY, f64::NAN] {
+                let value = Value::Map(vec![(key.into(), n.into())]);
+                assert_eq!(ToastSettings::parse(value), None);
+            }
+        }
+    }
+}
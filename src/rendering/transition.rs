@@ -0,0 +1,381 @@
+//! Transitions for windows as they open, close, move, and lose focus
+
+use super::animation::{Animatable, Animation, Curve};
+use neophyte_linalg::CellVec;
+use neophyte_ui_event::{MaybeInto, Parse};
+use std::time::Duration;
+
+/// How floating windows animate as they open, close, and move
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FloatTransition {
+    /// Seconds that each transition takes
+    pub duration: f32,
+    /// Whether floats fade in as they open and out as they close
+    pub fade: bool,
+    /// The number of cells above its position that a float slides down from
+    /// as it opens and back up to as it closes
+    pub slide: f32,
+    /// Whether floats glide to new positions rather than jumping
+    pub moves: bool,
+}
+
+impl FloatTransition {
+    fn curve(self) -> Curve {
+        Curve::EaseOutCubic {
+            duration: self.duration,
+        }
+    }
+
+    /// Seconds for floats to move to a new position, or zero if they jump
+    pub fn move_duration(self) -> f32 {
+        if self.moves { self.duration } else { 0. }
+    }
+
+    fn animates_open_and_close(self) -> bool {
+        self.fade || self.slide != 0.
+    }
+
+    /// The opacity and offset of a float before it opens or after it closes
+    fn hidden(self) -> (f32, CellVec<f32>) {
+        let opacity = if self.fade { 0. } else { 1. };
+        (opacity, CellVec::new(0., -self.slide))
+    }
+}
+
+impl Default for FloatTransition {
+    fn default() -> Self {
+        Self {
+            duration: 0.15,
+            fade: false,
+            slide: 0.,
+            moves: false,
+        }
+    }
+}
+
+impl Parse for FloatTransition {
+    fn parse(value: rmpv::Value) -> Option<Self> {
+        let rmpv::Value::Map(map) = value else {
+            return None;
+        };
+        let mut out = Self::default();
+        for (k, v) in map {
+            match k.as_str()? {
+                "duration" => out.duration = v.maybe_into()?,
+                "fade" => out.fade = v.maybe_into()?,
+                "slide" => out.slide = v.maybe_into()?,
+                "move" => out.moves = v.maybe_into()?,
+                _ => {}
+            }
+        }
+        (out.duration.is_finite() && out.duration > 0. && out.slide.is_finite()).then_some(out)
+    }
+}
+
+impl Animatable for CellVec<f32> {
+    fn magnitude(self) -> f32 {
+        self.length()
+    }
+}
+
+/// The transition state of a window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+    /// The opacity being animated toward
+    target_opacity: f32,
+    /// The offset from the window position being animated toward, in cells
+    target_offset: CellVec<f32>,
+    opacity: Animation<f32>,
+    offset: Animation<CellVec<f32>>,
+    curve: Curve,
+    is_closing: bool,
+}
+
+impl Default for Transition {
+    fn default() -> Self {
+        Self {
+            target_opacity: 1.,
+            target_offset: CellVec::default(),
+            opacity: Animation::default(),
+            offset: Animation::default(),
+            curve: Curve::default(),
+            is_closing: false,
+        }
+    }
+}
+
+impl Transition {
+    /// Start opening the window, or reverse its closing transition
+    pub fn open(&mut self, settings: FloatTransition) {
+        if !self.is_closing {
+            if !settings.animates_open_and_close() {
+                return;
+            }
+            let (opacity, offset) = settings.hidden();
+            *self = Self {
+                target_opacity: opacity,
+                target_offset: offset,
+                ..Self::default()
+            };
+        }
+        self.is_closing = false;
+        self.retarget(1., CellVec::default(), settings);
+    }
+
+    /// Start closing the window. Returns whether the window should continue to
+    /// be drawn until the transition finishes.
+    pub fn close(&mut self, settings: FloatTransition) -> bool {
+        if !settings.animates_open_and_close() {
+            return false;
+        }
+        self.is_closing = true;
+        let (opacity, offset) = settings.hidden();
+        self.retarget(opacity, offset, settings);
+        true
+    }
+
+    /// The window position moved by the given amount. The window glides to
+    /// the new position over the given number of seconds, or jumps if zero.
+    pub fn move_by(&mut self, delta: CellVec<f32>, duration: f32) {
+        if duration > 0. && !self.is_closing {
+            self.curve = Curve::EaseOutCubic { duration };
+            self.offset.retarget(-delta, self.curve);
+        }
+    }
+
+    /// Advance the transition by the given time. Returns whether the
+    /// transition is still in progress.
+    pub fn advance(&mut self, delta_time: Duration) -> bool {
+        let is_fading = self.opacity.advance(delta_time, 1., self.curve);
+        let is_moving = self.offset.advance(delta_time, 1., self.curve);
+        let is_animating = is_fading || is_moving;
+        if self.is_closing && !is_animating {
+            *self = Self::default();
+        }
+        is_animating
+    }
+
+    pub fn opacity(&self) -> f32 {
+        (self.target_opacity + self.opacity.offset(self.curve)).clamp(0., 1.)
+    }
+
+    /// The offset from the window position, in cells
+    pub fn offset(&self) -> CellVec<f32> {
+        self.target_offset + self.offset.offset(self.curve)
+    }
+
+    /// Whether the window is closed but still transitioning out
+    pub fn is_closing(&self) -> bool {
+        self.is_closing
+    }
+
+    fn retarget(&mut self, opacity: f32, offset: CellVec<f32>, settings: FloatTransition) {
+        self.curve = settings.curve();
+        self.opacity
+            .retarget(self.target_opacity - opacity, self.curve);
+        self.offset
+            .retarget(self.target_offset - offset, self.curve);
+        self.target_opacity = opacity;
+        self.target_offset = offset;
+    }
+}
+
+/// How focusable windows other than the one with the cursor are dimmed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DimInactive {
+    pub enabled: bool,
+    /// The opacity of the black drawn over inactive windows
+    pub amount: f32,
+    /// Seconds for windows to dim or brighten as focus moves
+    pub duration: f32,
+}
+
+impl DimInactive {
+    /// The dimming for inactive windows, or zero if disabled
+    pub fn amount(self) -> f32 {
+        if self.enabled { self.amount } else { 0. }
+    }
+
+    fn curve(self) -> Curve {
+        if self.duration > 0. {
+            Curve::EaseOutCubic {
+                duration: self.duration,
+            }
+        } else {
+            Curve::Instant
+        }
+    }
+}
+
+impl Default for DimInactive {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            amount: 0.3,
+            duration: 0.15,
+        }
+    }
+}
+
+impl Parse for DimInactive {
+    fn parse(value: rmpv::Value) -> Option<Self> {
+        let rmpv::Value::Map(map) = value else {
+            return None;
+        };
+        let mut out = Self {
+            enabled: true,
+            ..Self::default()
+        };
+        for (k, v) in map {
+            match k.as_str()? {
+                "enabled" => out.enabled = v.maybe_into()?,
+                "amount" => out.amount = v.maybe_into()?,
+                "duration" => out.duration = v.maybe_into()?,
+                _ => {}
+            }
+        }
+        ((0. ..=1.).contains(&out.amount) && out.duration.is_finite() && out.duration >= 0.)
+            .then_some(out)
+    }
+}
+
+/// The dimming of a window, which fades in and out as focus moves
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Dim {
+    target: f32,
+    animation: Animation<f32>,
+    curve: Curve,
+}
+
+impl Dim {
+    /// Start dimming toward the given amount
+    pub fn set(&mut self, amount: f32, settings: DimInactive) {
+        if amount != self.target {
+            self.curve = settings.curve();
+            self.animation.retarget(self.target - amount, self.curve);
+            self.target = amount;
+        }
+    }
+
+    /// Advance the transition by the given time. Returns whether the
+    /// transition is still in progress.
+    pub fn advance(&mut self, delta_time: Duration) -> bool {
+        self.animation.advance(delta_time, 1., self.curve)
+    }
+
+    /// The opacity of the black drawn over the window
+    pub fn amount(&self) -> f32 {
+        (self.target + self.animation.offset(self.curve)).clamp(0., 1.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETTINGS: FloatTransition = FloatTransition {
+        duration: 0.1,
+        fade: true,
+        slide: 1.,
+        moves: true,
+    };
+
+    fn settle(transition: &mut Transition) {
+        let mut frames = 0;
+        while transition.advance(Duration::from_millis(16)) {
+            frames += 1;
+            assert!(frames < 100, "transition did not finish");
+        }
+    }
+
+    #[test]
+    fn opens_from_hidden() {
+        let mut transition = Transition::default();
+        transition.open(SETTINGS);
+        assert_eq!(transition.opacity(), 0.);
+        assert_eq!(transition.offset(), CellVec::new(0., -1.));
+        settle(&mut transition);
+        assert_eq!(transition.opacity(), 1.);
+        assert_eq!(transition.offset(), CellVec::default());
+    }
+
+    #[test]
+    fn closes_to_hidden() {
+        let mut transition = Transition::default();
+        assert!(transition.close(SETTINGS));
+        assert!(transition.is_closing());
+        assert_eq!(transition.opacity(), 1.);
+        transition.advance(Duration::from_millis(50));
+        assert!(transition.opacity() < 1.);
+        settle(&mut transition);
+        assert!(!transition.is_closing());
+    }
+
+    #[test]
+    fn reopening_reverses_from_the_current_state() {
+        let mut transition = Transition::default();
+        transition.close(SETTINGS);
+        transition.advance(Duration::from_millis(50));
+        let opacity = transition.opacity();
+        transition.open(SETTINGS);
+        assert!(!transition.is_closing());
+        assert!((transition.opacity() - opacity).abs() < 1e-4);
+    }
+
+    #[test]
+    fn moves_from_the_old_position() {
+        let mut transition = Transition::default();
+        transition.move_by(CellVec::new(4., 0.), 0.1);
+        assert_eq!(transition.offset(), CellVec::new(-4., 0.));
+        settle(&mut transition);
+        assert_eq!(transition.offset(), CellVec::default());
+    }
+
+    #[test]
+    fn disabled_does_not_animate() {
+        let settings = FloatTransition::default();
+        let mut transition = Transition::default();
+        transition.open(settings);
+        assert!(!transition.close(settings));
+        transition.move_by(CellVec::new(4., 2.), settings.move_duration());
+        assert_eq!(transition, Transition::default());
+    }
+
+    #[test]
+    fn rejects_non_finite_float_transitions() {
+        use rmpv::Value;
+        let parse =
+            |key: &str, n: f64| FloatTransition::parse(Value::Map(vec![(key.into(), n.into())]));
+        assert!(parse("slide", 2.).is_some());
+        for n in [f64::INFINITY, f64::NAN] {
+            assert_eq!(parse("duration", n), None);
+            assert_eq!(parse("slide", n), None);
+        }
+    }
+
+    #[test]
+    fn dims_when_focus_leaves() {
+        let settings = DimInactive {
+            enabled: true,
+            ..DimInactive::default()
+        };
+        let mut dim = Dim::default();
+        dim.set(settings.amount(), settings);
+        assert_eq!(dim.amount(), 0.);
+        while dim.advance(Duration::from_millis(16)) {}
+        assert_eq!(dim.amount(), 0.3);
+        dim.set(0., settings);
+        assert_eq!(dim.amount(), 0.3);
+        while dim.advance(Duration::from_millis(16)) {}
+        assert_eq!(dim.amount(), 0.);
+    }
+
+    #[test]
+    fn rejects_non_finite_dim_durations() {
+        use rmpv::Value;
+        let parse = |n: f64| DimInactive::parse(Value::Map(vec![("duration".into(), n.into())]));
+        assert!(parse(0.2).is_some());
+        assert_eq!(parse(f64::INFINITY), None);
+        assert_eq!(parse(f64::NAN), None);
+    }
+}
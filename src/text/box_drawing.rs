@@ -0,0 +1,661 @@
+//! Procedural rendering for box-drawing, block element, braille, and powerline
+//! characters. Font outlines for these rarely line up exactly with the cell
+//! grid, leaving gaps or overlaps between neighboring cells, so we draw them
+//! ourselves at exactly the size of a cell.
+
+use neophyte_linalg::Vec2;
+
+/// Whether the character is one that we draw procedurally
+pub fn is_box_drawing(c: char) -> bool {
+    matches!(
+        c,
+        '\u{2500}'..='\u{259F}' | '\u{2800}'..='\u{28FF}' | '\u{E0B0}'..='\u{E0BF}'
+    )
+}
+
+/// Draw the character as a coverage mask with the dimensions of a cell, in
+/// rows then columns. Returns None if the character is not one that we draw
+/// procedurally.
+pub fn rasterize(c: char, cell_size: Vec2<u32>) -> Option<Vec<u8>> {
+    if cell_size.x == 0 || cell_size.y == 0 {
+        return None;
+    }
+
+    let mut canvas = Canvas::new(cell_size);
+    match c {
+        '\u{2500}'..='\u{257F}' => canvas.box_line(c),
+        '\u{2580}'..='\u{259F}' => canvas.block(c),
+        '\u{2800}'..='\u{28FF}' => canvas.braille(c),
+        '\u{E0B0}'..='\u{E0BF}' => canvas.powerline(c),
+        _ => return None,
+    }
+    Some(canvas.data)
+}
+
+/// The weight of one arm of a box-drawing line, reaching from the center of
+/// the cell to one of its edges
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Weight {
+    None,
+    Light,
+    Heavy,
+    Double,
+}
+
+impl Weight {
+    fn from_char(c: u8) -> Self {
+        match c {
+            b'l' => Self::Light,
+            b'h' => Self::Heavy,
+            b'd' => Self::Double,
+            _ => Self::None,
+        }
+    }
+
+    fn is_single(self) -> bool {
+        matches!(self, Self::Light | Self::Heavy)
+    }
+}
+
+/// Arms for U+2500 through U+257F in up, right, down, left order, using l for
+/// light, h for heavy, and d for double. Characters drawn some other way, such
+/// as dashes, arcs, and diagonals, are empty.
+#[rustfmt::skip]
+const BOX_ARMS: [&str; 128] = [
+    // 2500
+    ".l.l", ".h.h", "l.l.", "h.h.", "", "", "", "",
+    "", "", "", "", ".ll.", ".hl.", ".lh.", ".hh.",
+    // 2510
+    "..ll", "..lh", "..hl", "..hh", "ll..", "lh..", "hl..", "hh..",
+    "l..l", "l..h", "h..l", "h..h", "lll.", "lhl.", "hll.", "llh.",
+    // 2520
+    "hlh.", "hhl.", "lhh.", "hhh.", "l.ll", "l.lh", "h.ll", "l.hl",
+    "h.hl", "h.lh", "l.hh", "h.hh", ".lll", ".llh", ".hll", ".hlh",
+    // 2530
+    ".lhl", ".lhh", ".hhl", ".hhh", "ll.l", "ll.h", "lh.l", "lh.h",
+    "hl.l", "hl.h", "hh.l", "hh.h", "llll", "lllh", "lhll", "lhlh",
+    // 2540
+    "hlll", "llhl", "hlhl", "hllh", "hhll", "llhh", "lhhl", "hhlh",
+    "lhhh", "hlhh", "hhhl", "hhhh", "", "", "", "",
+    // 2550
+    ".d.d", "d.d.", ".dl.", ".ld.", ".dd.", "..ld", "..dl", "..dd",
+    "ld..", "dl..", "dd..", "l..d", "d..l", "d..d", "ldl.", "dld.",
+    // 2560
+    "ddd.", "l.ld", "d.dl", "d.dd", ".dld", ".ldl", ".ddd", "ld.d",
+    "dl.l", "dd.d", "ldld", "dldl", "dddd", "", "", "",
+    // 2570
+    "", "", "", "", "...l", "l...", ".l..", "..l.",
+    "...h", "h...", ".h..", "..h.", ".h.l", "l.h.", ".l.h", "h.l.",
+];
+
+/// Pixel rows or columns covered by a line across the cell
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    start: u32,
+    end: u32,
+}
+
+impl Span {
+    /// A span of the given thickness centered in the given length
+    fn centered(length: u32, thickness: u32) -> Self {
+        let start = length.saturating_sub(thickness) / 2;
+        Self {
+            start,
+            end: (start + thickness).min(length),
+        }
+    }
+}
+
+/// Positions of the lines for an axis of a box-drawing character
+#[derive(Debug, Clone, Copy)]
+struct Lines {
+    light: Span,
+    heavy: Span,
+    /// The first line of a double line
+    double_a: Span,
+    /// The second line of a double line
+    double_b: Span,
+    center: u32,
+}
+
+impl Lines {
+    fn new(length: u32, light: u32, heavy: u32) -> Self {
+        let total = light * 3;
+        let outer = Span::centered(length, total);
+        Self {
+            light: Span::centered(length, light),
+            heavy: Span::centered(length, heavy),
+            double_a: Span {
+                start: outer.start,
+                end: outer.start + light,
+            },
+            double_b: Span {
+                start: outer.end.saturating_sub(light),
+                end: outer.end,
+            },
+            center: length / 2,
+        }
+    }
+
+    fn single(&self, weight: Weight) -> Span {
+        match weight {
+            Weight::Heavy => self.heavy,
+            _ => self.light,
+        }
+    }
+}
+
+struct Canvas {
+    size: Vec2<u32>,
+    data: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(size: Vec2<u32>) -> Self {
+        Self {
+            size,
+            data: vec![0; size.area() as usize],
+        }
+    }
+
+    fn light_thickness(&self) -> u32 {
+        (self.size.x as f32 / 8.).round().max(1.) as u32
+    }
+
+    /// Fill the rectangle from start inclusive to end exclusive
+    fn fill_rect(&mut self, start: Vec2<u32>, end: Vec2<u32>, alpha: u8) {
+        let end = Vec2::combine(end, self.size, u32::min);
+        for y in start.y..end.y {
+            let row = (y * self.size.x) as usize;
+            for x in start.x..end.x {
+                let dst = &mut self.data[row + x as usize];
+                *dst = (*dst).max(alpha);
+            }
+        }
+    }
+
+    /// Antialiased fill of the region for which inside returns true, using
+    /// coordinates in pixels
+    fn fill_shape(&mut self, inside: impl Fn(f32, f32) -> bool) {
+        const SAMPLES: u32 = 4;
+        for y in 0..self.size.y {
+            for x in 0..self.size.x {
+                let mut covered = 0;
+                for sy in 0..SAMPLES {
+                    for sx in 0..SAMPLES {
+                        let px = x as f32 + (sx as f32 + 0.5) / SAMPLES as f32;
+                        let py = y as f32 + (sy as f32 + 0.5) / SAMPLES as f32;
+                        if inside(px, py) {
+                            covered += 1;
+                        }
+                    }
+                }
+                let alpha = (covered * 255 / (SAMPLES * SAMPLES)) as u8;
+                let dst = &mut self.data[(y * self.size.x + x) as usize];
+                *dst = (*dst).max(alpha);
+            }
+        }
+    }
+
+    /// Antialiased line between two points in pixels
+    fn fill_segment(&mut self, a: Vec2<f32>, b: Vec2<f32>, thickness: f32) {
+        let half = thickness / 2.;
+        self.fill_shape(|x, y| distance_to_segment(Vec2::new(x, y), a, b) <= half);
+    }
+
+    fn box_line(&mut self, c: char) {
+        let light = self.light_thickness();
+        let heavy = light * 2;
+        match c {
+            '\u{2504}'..='\u{250B}' | '\u{254C}'..='\u{254F}' => {
+                let (count, weight, is_vertical) = match c {
+                    '\u{2504}' => (3, Weight::Light, false),
+                    '\u{2505}' => (3, Weight::Heavy, false),
+                    '\u{2506}' => (3, Weight::Light, true),
+                    '\u{2507}' => (3, Weight::Heavy, true),
+                    '\u{2508}' => (4, Weight::Light, false),
+                    '\u{2509}' => (4, Weight::Heavy, false),
+                    '\u{250A}' => (4, Weight::Light, true),
+                    '\u{250B}' => (4, Weight::Heavy, true),
+                    '\u{254C}' => (2, Weight::Light, false),
+                    '\u{254D}' => (2, Weight::Heavy, false),
+                    '\u{254E}' => (2, Weight::Light, true),
+                    _ => (2, Weight::Heavy, true),
+                };
+                self.dashes(count, weight, is_vertical, light, heavy);
+            }
+            '\u{256D}'..='\u{2570}' => self.arc(c, light),
+            '\u{2571}'..='\u{2573}' => {
+                let size = self.size.cast_as::<f32>();
+                let thickness = light as f32;
+                if c != '\u{2572}' {
+                    self.fill_segment(Vec2::new(size.x, 0.), Vec2::new(0., size.y), thickness);
+                }
+                if c != '\u{2571}' {
+                    self.fill_segment(Vec2::new(0., 0.), size, thickness);
+                }
+            }
+            _ => {
+                let arms = BOX_ARMS[c as usize - 0x2500].as_bytes();
+                if arms.len() == 4 {
+                    let arms = [0, 1, 2, 3].map(|i| Weight::from_char(arms[i]));
+                    self.arms(arms, light, heavy);
+                }
+            }
+        }
+    }
+
+    /// Draw the arms of a box-drawing character in up, right, down, left order
+    fn arms(&mut self, [up, right, down, left]: [Weight; 4], light: u32, heavy: u32) {
+        let size = self.size;
+        let h = Lines::new(size.y, light, heavy);
+        let v = Lines::new(size.x, light, heavy);
+
+        // The extent of the vertical lines that horizontal arms join to,
+        // from the left edge to the right edge
+        let vertical_join = thickest(up, down).map(|weight| v.single(weight));
+        let horizontal_join = thickest(left, right).map(|weight| h.single(weight));
+        let is_up_double = up == Weight::Double;
+        let is_down_double = down == Weight::Double;
+        let is_left_double = left == Weight::Double;
+        let is_right_double = right == Weight::Double;
+        let is_vertical_double = is_up_double || is_down_double;
+        let is_horizontal_double = is_left_double || is_right_double;
+
+        // Horizontal arms
+        for (weight, is_right) in [(left, false), (right, true)] {
+            match weight {
+                Weight::None => {}
+                Weight::Double => {
+                    for (span, is_first) in [(h.double_a, true), (h.double_b, false)] {
+                        // The first line is nearer the up arm
+                        let (near, far) = if is_first {
+                            (is_up_double, is_down_double)
+                        } else {
+                            (is_down_double, is_up_double)
+                        };
+                        let (start, end) = if near {
+                            (v.double_b.start, v.double_a.end)
+                        } else if far {
+                            (v.double_a.start, v.double_b.end)
+                        } else if let Some(join) = vertical_join {
+                            (join.start, join.end)
+                        } else {
+                            (v.center, v.center)
+                        };
+                        let (x0, x1) = if is_right { (start, size.x) } else { (0, end) };
+                        self.fill_rect(Vec2::new(x0, span.start), Vec2::new(x1, span.end), 255);
+                    }
+                }
+                Weight::Light | Weight::Heavy => {
+                    let span = h.single(weight);
+                    let opposite = if is_right { left } else { right };
+                    let (start, end) = if opposite != Weight::None {
+                        (
+                            v.center.min(span_start(vertical_join, v.center)),
+                            v.center.max(span_end(vertical_join, v.center)),
+                        )
+                    } else if is_vertical_double {
+                        if is_up_double && is_down_double {
+                            (v.double_b.start, v.double_a.end)
+                        } else {
+                            (v.double_a.start, v.double_b.end)
+                        }
+                    } else if let Some(join) = vertical_join {
+                        (join.start, join.end)
+                    } else {
+                        (v.center, v.center)
+                    };
+                    let (x0, x1) = if is_right { (start, size.x) } else { (0, end) };
+                    self.fill_rect(Vec2::new(x0, span.start), Vec2::new(x1, span.end), 255);
+                }
+            }
+        }
+
+        // Vertical arms
+        for (weight, is_down) in [(up, false), (down, true)] {
+            match weight {
+                Weight::None => {}
+                Weight::Double => {
+                    for (span, is_first) in [(v.double_a, true), (v.double_b, false)] {
+                        // The first line is nearer the left arm
+                        let (near, far) = if is_first {
+                            (is_left_double, is_right_double)
+                        } else {
+                            (is_right_double, is_left_double)
+                        };
+                        let (start, end) = if near {
+                            (h.double_b.start, h.double_a.end)
+                        } else if far {
+                            (h.double_a.start, h.double_b.end)
+                        } else if let Some(join) = horizontal_join {
+                            (join.start, join.end)
+                        } else {
+                            (h.center, h.center)
+                        };
+                        let (y0, y1) = if is_down { (start, size.y) } else { (0, end) };
+                        self.fill_rect(Vec2::new(span.start, y0), Vec2::new(span.end, y1), 255);
+                    }
+                }
+                Weight::Light | Weight::Heavy => {
+                    let span = v.single(weight);
+                    let opposite = if is_down { up } else { down };
+                    let (start, end) = if opposite != Weight::None {
+                        (
+                            h.center.min(span_start(horizontal_join, h.center)),
+                            h.center.max(span_end(horizontal_join, h.center)),
+                        )
+                    } else if is_horizontal_double {
+                        if is_left_double && is_right_double {
+                            (h.double_b.start, h.double_a.end)
+                        } else {
+                            (h.double_a.start, h.double_b.end)
+                        }
+                    } else if let Some(join) = horizontal_join {
+                        (join.start, join.end)
+                    } else {
+                        (h.center, h.center)
+                    };
+                    let (y0, y1) = if is_down { (start, size.y) } else { (0, end) };
+                    self.fill_rect(Vec2::new(span.start, y0), Vec2::new(span.end, y1), 255);
+                }
+            }
+        }
+    }
+
+    fn dashes(&mut self, count: u32, weight: Weight, is_vertical: bool, light: u32, heavy: u32) {
+        let size = if is_vertical {
+            self.size.transpose()
+        } else {
+            self.size
+        };
+        let span = Lines::new(size.y, light, heavy).single(weight);
+        let dash = size.x as f32 / count as f32;
+        let gap = (dash / 3.).round().max(1.);
+        for i in 0..count {
+            let start = (i as f32 * dash + gap / 2.).round() as u32;
+            let end = ((i + 1) as f32 * dash - gap / 2.).round() as u32;
+            let (start, end) = (
+                Vec2::new(start, span.start),
+                Vec2::new(end.max(start + 1), span.end),
+            );
+            if is_vertical {
+                self.fill_rect(start.transpose(), end.transpose(), 255);
+            } else {
+                self.fill_rect(start, end, 255);
+            }
+        }
+    }
+
+    fn arc(&mut self, c: char, light: u32) {
+        let size = self.size.cast_as::<f32>();
+        let h = Span::centered(self.size.y, light);
+        let v = Span::centered(self.size.x, light);
+        // Centers of the straight lines that the arc connects
+        let center = Vec2::new((v.start + v.end) as f32 / 2., (h.start + h.end) as f32 / 2.);
+        let radius = center
+            .x
+            .min(center.y)
+            .min(size.x - center.x)
+            .min(size.y - center.y);
+        // Direction from the center of the cell toward the arms
+        let direction = match c {
+            '\u{256D}' => Vec2::new(1., 1.),
+            '\u{256E}' => Vec2::new(-1., 1.),
+            '\u{256F}' => Vec2::new(-1., -1.),
+            _ => Vec2::new(1., -1.),
+        };
+        let arc_center = center + direction * radius;
+        let half = light as f32 / 2.;
+        self.fill_shape(|x, y| {
+            let d = Vec2::new(x, y) - arc_center;
+            // Only the quadrant facing the center of the cell
+            if d.x * direction.x > 0. || d.y * direction.y > 0. {
+                return false;
+            }
+            let distance = (d.x * d.x + d.y * d.y).sqrt();
+            (distance - radius).abs() <= half
+        });
+
+        // Straight segments from the ends of the arc to the cell edges
+        let horizontal = if direction.x > 0. {
+            (arc_center.x.round() as u32, self.size.x)
+        } else {
+            (0, arc_center.x.round() as u32)
+        };
+        let vertical = if direction.y > 0. {
+            (arc_center.y.round() as u32, self.size.y)
+        } else {
+            (0, arc_center.y.round() as u32)
+        };
+        self.fill_rect(
+            Vec2::new(horizontal.0, h.start),
+            Vec2::new(horizontal.1, h.end),
+            255,
+        );
+        self.fill_rect(
+            Vec2::new(v.start, vertical.0),
+            Vec2::new(v.end, vertical.1),
+            255,
+        );
+    }
+
+    fn block(&mut self, c: char) {
+        let size = self.size;
+        let eighth_x = |n: u32| (size.x as f32 * n as f32 / 8.).round() as u32;
+        let eighth_y = |n: u32| (size.y as f32 * n as f32 / 8.).round() as u32;
+        let half = Vec2::new(eighth_x(4), eighth_y(4));
+        let full = |alpha| (Vec2::new(0, 0), size, alpha);
+        let lower = |n| (Vec2::new(0, size.y - eighth_y(n)), size, 255);
+        let left = |n| (Vec2::new(0, 0), Vec2::new(eighth_x(n), size.y), 255);
+        let upper_left = (Vec2::new(0, 0), half, 255);
+        let upper_right = (Vec2::new(half.x, 0), Vec2::new(size.x, half.y), 255);
+        let lower_left = (Vec2::new(0, half.y), Vec2::new(half.x, size.y), 255);
+        let lower_right = (half, size, 255);
+        let rects: &[(Vec2<u32>, Vec2<u32>, u8)] = match c {
+            '\u{2580}' => &[(Vec2::new(0, 0), Vec2::new(size.x, half.y), 255)],
+            '\u{2581}'..='\u{2587}' => &[lower(c as u32 - 0x2580)],
+            '\u{2588}' => &[full(255)],
+            '\u{2589}'..='\u{258F}' => &[left(0x2590 - c as u32)],
+            '\u{2590}' => &[(Vec2::new(half.x, 0), size, 255)],
+            '\u{2591}' => &[full(64)],
+            '\u{2592}' => &[full(128)],
+            '\u{2593}' => &[full(191)],
+            '\u{2594}' => &[(Vec2::new(0, 0), Vec2::new(size.x, eighth_y(1)), 255)],
+            '\u{2595}' => &[(Vec2::new(size.x - eighth_x(1), 0), size, 255)],
+            '\u{2596}' => &[lower_left],
+            '\u{2597}' => &[lower_right],
+            '\u{2598}' => &[upper_left],
+            '\u{2599}' => &[upper_left, lower_left, lower_right],
+            '\u{259A}' => &[upper_left, lower_right],
+            '\u{259B}' => &[upper_left, upper_right, lower_left],
+            '\u{259C}' => &[upper_left, upper_right, lower_right],
+            '\u{259D}' => &[upper_right],
+            '\u{259E}' => &[upper_right, lower_left],
+            _ => &[upper_right, lower_left, lower_right],
+        };
+        for &(start, end, alpha) in rects {
+            self.fill_rect(start, end, alpha);
+        }
+    }
+
+    fn braille(&mut self, c: char) {
+        // Dots are numbered down the left column then down the right, except
+        // that the bottom row was added later as dots 7 and 8
+        const DOTS: [(u32, u32); 8] = [
+            (0, 0),
+            (0, 1),
+            (0, 2),
+            (1, 0),
+            (1, 1),
+            (1, 2),
+            (0, 3),
+            (1, 3),
+        ];
+        let bits = c as u32 - 0x2800;
+        let size = self.size.cast_as::<f32>();
+        let spacing = Vec2::new(size.x / 2., size.y / 4.);
+        let radius = (spacing.x.min(spacing.y) * 0.35).max(0.5);
+        let centers: Vec<_> = DOTS
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| bits & (1 << i) != 0)
+            .map(|(_, &(column, row))| {
+                Vec2::new(
+                    (column as f32 + 0.5) * spacing.x,
+                    (row as f32 + 0.5) * spacing.y,
+                )
+            })
+            .collect();
+        self.fill_shape(|x, y| {
+            centers.iter().any(|center| {
+                let d = Vec2::new(x, y) - *center;
+                d.x * d.x + d.y * d.y <= radius * radius
+            })
+        });
+    }
+
+    fn powerline(&mut self, c: char) {
+        let size = self.size.cast_as::<f32>();
+        let thickness = self.light_thickness() as f32;
+        let top_left = Vec2::new(0., 0.);
+        let top_right = Vec2::new(size.x, 0.);
+        let bottom_left = Vec2::new(0., size.y);
+        let bottom_right = size;
+        let middle_left = Vec2::new(0., size.y / 2.);
+        let middle_right = Vec2::new(size.x, size.y / 2.);
+        match c {
+            '\u{E0B0}' => self.fill_shape(|x, y| x / size.x <= 1. - (2. * y / size.y - 1.).abs()),
+            '\u{E0B2}' => self.fill_shape(|x, y| x / size.x >= (2. * y / size.y - 1.).abs()),
+            '\u{E0B1}' => {
+                self.fill_segment(top_left, middle_right, thickness);
+                self.fill_segment(middle_right, bottom_left, thickness);
+            }
+            '\u{E0B3}' => {
+                self.fill_segment(top_right, middle_left, thickness);
+                self.fill_segment(middle_left, bottom_right, thickness);
+            }
+            '\u{E0B4}'..='\u{E0B7}' => {
+                let is_right = c <= '\u{E0B5}';
+                let is_outline = c == '\u{E0B5}' || c == '\u{E0B7}';
+                let center_x = if is_right { 0. } else { size.x };
+                let radii = Vec2::new(size.x, size.y / 2.);
+                let inner = radii - thickness;
+                self.fill_shape(|x, y| {
+                    let d = Vec2::new(x - center_x, y - size.y / 2.);
+                    let outer = ellipse(d, radii) <= 1.;
+                    outer && !(is_outline && ellipse(d, inner) < 1.)
+                });
+            }
+            '\u{E0B8}' => self.fill_shape(|x, y| x / size.x <= y / size.y),
+            '\u{E0BA}' => self.fill_shape(|x, y| x / size.x >= 1. - y / size.y),
+            '\u{E0BC}' => self.fill_shape(|x, y| x / size.x <= 1. - y / size.y),
+            '\u{E0BE}' => self.fill_shape(|x, y| x / size.x >= y / size.y),
+            '\u{E0B9}' | '\u{E0BF}' => self.fill_segment(top_left, bottom_right, thickness),
+            _ => self.fill_segment(top_right, bottom_left, thickness),
+        }
+    }
+}
+
+/// The heavier of two single line weights, or None if neither is a single line
+fn thickest(a: Weight, b: Weight) -> Option<Weight> {
+    match (a.is_single(), b.is_single()) {
+        (true, true) if a == Weight::Heavy || b == Weight::Heavy => Some(Weight::Heavy),
+        (true, _) => Some(a),
+        (_, true) => Some(b),
+        _ => None,
+    }
+}
+
+fn span_start(span: Option<Span>, default: u32) -> u32 {
+    span.map(|span| span.start).unwrap_or(default)
+}
+
+fn span_end(span: Option<Span>, default: u32) -> u32 {
+    span.map(|span| span.end).unwrap_or(default)
+}
+
+/// Normalized distance from the center of an ellipse, where 1 is on the edge
+fn ellipse(d: Vec2<f32>, radii: Vec2<f32>) -> f32 {
+    let d = d / radii;
+    d.x * d.x + d.y * d.y
+}
+
+fn distance_to_segment(p: Vec2<f32>, a: Vec2<f32>, b: Vec2<f32>) -> f32 {
+    let ab = b - a;
+    let ap = p - a;
+    let length_squared = ab.x * ab.x + ab.y * ab.y;
+    let t = if length_squared > 0. {
+        ((ap.x * ab.x + ap.y * ab.y) / length_squared).clamp(0., 1.)
+    } else {
+        0.
+    };
+    let d = ap - ab * t;
+    (d.x * d.x + d.y * d.y).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: Vec2<u32> = Vec2::new(10, 20);
+
+    fn mask(c: char) -> impl Fn(u32, u32) -> u8 {
+        let mask = rasterize(c, SIZE).unwrap();
+        move |x, y| mask[(y * SIZE.x + x) as usize]
+    }
+
+    #[test]
+    fn cross_reaches_every_edge() {
+        let at = mask('┼');
+        assert!((0..SIZE.x).any(|x| at(x, 0) > 0));
+        assert!((0..SIZE.x).any(|x| at(x, SIZE.y - 1) > 0));
+        assert!((0..SIZE.y).any(|y| at(0, y) > 0));
+        assert!((0..SIZE.y).any(|y| at(SIZE.x - 1, y) > 0));
+        for (x, y) in [
+            (0, 0),
+            (SIZE.x - 1, 0),
+            (0, SIZE.y - 1),
+            (SIZE.x - 1, SIZE.y - 1),
+        ] {
+            assert_eq!(at(x, y), 0);
+        }
+    }
+
+    #[test]
+    fn lower_half_block_fills_exactly_the_lower_half() {
+        let at = mask('▄');
+        for y in 0..SIZE.y {
+            let expected = if y < SIZE.y / 2 { 0 } else { 255 };
+            for x in 0..SIZE.x {
+                assert_eq!(at(x, y), expected, "at ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn powerline_triangles_span_the_cell() {
+        let right = mask('\u{E0B0}');
+        let left = mask('\u{E0B2}');
+        let middle = SIZE.y / 2;
+        for y in 0..SIZE.y {
+            // The base runs along the whole flat edge
+            assert!(right(0, y) > 0);
+            assert!(left(SIZE.x - 1, y) > 0);
+        }
+        // The tip reaches the opposite edge at the middle
+        assert!(right(SIZE.x - 1, middle) > 0);
+        assert!(left(0, middle) > 0);
+        // And narrows away from it
+        for y in [0, SIZE.y - 1] {
+            assert_eq!(right(SIZE.x - 1, y), 0);
+            assert_eq!(left(0, y), 0);
+        }
+    }
+
+    #[test]
+    fn only_procedural_glyphs_rasterize() {
+        assert!(rasterize('a', SIZE).is_none());
+        assert!(rasterize('┼', Vec2::new(0, 0)).is_none());
+    }
+}
@@ -1,13 +1,19 @@
 use super::{
     atlas::FontAtlas,
-    fonts::{FontStyle, SwashSetting},
+    box_drawing, emoji,
+    fonts::{FontSetting, FontStyle},
 };
+use crate::ui::options::Edging;
 use bytemuck::{Pod, Zeroable};
 use neophyte_linalg::Vec2;
 use std::collections::{HashMap, hash_map::Entry};
 use swash::{
     FontRef, GlyphId,
-    scale::{Render, ScaleContext, Source, StrikeWith, image::Content},
+    scale::{
+        Render, ScaleContext, Source, StrikeWith,
+        image::{Content, Image},
+    },
+    zeno::Placement,
 };
 
 /// A cache of font glyphs
@@ -18,7 +24,13 @@ pub struct FontCache {
     /// entry. A value of None indicates that we already tried to convert the
     /// given cache key and failed so we should not try again.
     lut: HashMap<CacheKey, Option<CacheValue>>,
+    /// Cache entries for procedurally drawn characters and the cell size they
+    /// were drawn for
+    box_drawing_lut: HashMap<(char, Vec2<u32>), Option<CacheValue>>,
     scale_context: ScaleContext,
+    /// Whether box-drawing and similar characters are drawn procedurally
+    /// rather than taken from fonts
+    pub box_drawing: bool,
 }
 
 impl Default for FontCache {
@@ -33,7 +45,9 @@ impl FontCache {
             monochrome: Cached::new(1),
             emoji: Cached::new(4),
             lut: HashMap::new(),
+            box_drawing_lut: HashMap::new(),
             scale_context: ScaleContext::default(),
+            box_drawing: true,
         }
     }
 
@@ -42,34 +56,110 @@ impl FontCache {
         self.monochrome.clear();
         self.emoji.clear();
         self.lut.clear();
+        self.box_drawing_lut.clear();
+    }
+
+    /// Whether the character should be drawn procedurally instead of being
+    /// looked up in a font
+    pub fn is_box_drawing(&self, c: char) -> bool {
+        self.box_drawing && box_drawing::is_box_drawing(c)
+    }
+
+    /// Get an existing cache entry for a procedurally drawn character or
+    /// create it if it does not exist. The glyph covers exactly one cell.
+    pub fn get_box_drawing(&mut self, c: char, cell_size: Vec2<u32>) -> Option<CacheValue> {
+        match self.box_drawing_lut.entry((c, cell_size)) {
+            Entry::Occupied(entry) => *entry.get(),
+            Entry::Vacant(entry) => {
+                let out = box_drawing::rasterize(c, cell_size).map(|data| {
+                    let image = Image {
+                        source: Source::Outline,
+                        content: Content::Mask,
+                        placement: Placement {
+                            left: 0,
+                            top: 0,
+                            width: cell_size.x,
+                            height: cell_size.y,
+                        },
+                        data,
+                    };
+                    let cached = &mut self.monochrome;
+                    let index = cached.info.len();
+                    cached.revision += 1;
+                    let origin = cached.atlas.pack(&image);
+                    cached.info.push(GlyphInfo {
+                        size: cell_size.try_cast().unwrap(),
+                        offset: Vec2::new(0, 0),
+                        origin: origin.try_cast().unwrap(),
+                    });
+                    CacheValue {
+                        index,
+                        kind: GlyphKind::Monochrome,
+                    }
+                });
+                entry.insert(out);
+                out
+            }
+        }
     }
 
     /// Get an existing cache entry or attempt to create it if it does not
-    /// exist.
+    /// exist. If fit is given, the glyph is scaled to fit within a box of that
+    /// size in pixels and offset so that it is centered in the box when drawn
+    /// at the box's top-left corner.
+    #[allow(clippy::too_many_arguments)]
     pub fn get(
         &mut self,
         font: FontRef,
-        variations: &[SwashSetting<f32>],
+        setting: &FontSetting,
         size: f32,
         glyph_id: GlyphId,
         style: FontStyle,
         font_index: usize,
+        fit: Option<Vec2<u32>>,
     ) -> Option<CacheValue> {
         let key = CacheKey {
             glyph_id,
             style,
             font_index,
+            fit,
         };
         match self.lut.entry(key) {
             Entry::Occupied(entry) => *entry.get(),
             Entry::Vacant(entry) => {
+                let size = match fit {
+                    Some(fit) => {
+                        let mut scaler = self
+                            .scale_context
+                            .builder(font)
+                            .size(size)
+                            .variations(setting.variations.iter().map(|s| s.0))
+                            .build();
+                        let bounds = scaler
+                            .scale_outline(glyph_id)
+                            .map(|outline| outline.bounds())
+                            .filter(|bounds| !bounds.is_empty());
+                        match bounds {
+                            Some(bounds) => {
+                                let scale = (fit.x as f32 / bounds.width())
+                                    .min(fit.y as f32 / bounds.height());
+                                size * scale
+                            }
+                            None => size,
+                        }
+                    }
+                    None => size,
+                };
+
                 let mut scaler = self
                     .scale_context
                     .builder(font)
                     .size(size)
-                    .hint(true)
-                    .variations(variations.iter().cloned().map(|s| s.0))
+                    .hint(setting.hinting.is_enabled())
+                    .variations(setting.variations.iter().map(|s| s.0))
                     .build();
+                // Swash renders COLRv0 color outlines. Fonts with only COLRv1
+                // paint graphs fall back to their bitmaps or plain outlines.
                 match Render::new(&[
                     Source::ColorOutline(0),
                     Source::ColorBitmap(StrikeWith::BestFit),
@@ -77,7 +167,26 @@ impl FontCache {
                 ])
                 .render(&mut scaler, glyph_id)
                 {
-                    Some(image) => {
+                    Some(mut image) => {
+                        if setting.edging == Edging::Alias && image.content == Content::Mask {
+                            for coverage in image.data.iter_mut() {
+                                *coverage = if *coverage < 128 { 0 } else { 255 };
+                            }
+                        }
+
+                        // Bitmap strikes only come in the sizes the font
+                        // provides, so scale color glyphs to the box here
+                        if let Some(fit) = fit
+                            && image.content == Content::Color
+                        {
+                            let placement = &mut image.placement;
+                            let size = Vec2::new(placement.width, placement.height);
+                            let new_size = emoji::fit_size(size, fit);
+                            image.data = emoji::resize(&image.data, size, new_size, 4);
+                            placement.width = new_size.x;
+                            placement.height = new_size.y;
+                        }
+
                         let placement = image.placement;
                         let size = Vec2::new(placement.width, placement.height);
                         if size.area() > 0 {
@@ -93,9 +202,15 @@ impl FontCache {
                             entry.insert(out);
                             cached.revision += 1;
                             let origin = cached.atlas.pack(&image);
+                            let offset = match fit {
+                                Some(fit) => {
+                                    (fit.try_cast::<i32>().unwrap() - size.try_cast().unwrap()) / 2
+                                }
+                                None => Vec2::new(placement.left, placement.top) * Vec2::new(1, -1),
+                            };
                             cached.info.push(GlyphInfo {
                                 size: size.try_cast().unwrap(),
-                                offset: Vec2::new(placement.left, placement.top) * Vec2::new(1, -1),
+                                offset,
                                 origin: origin.try_cast().unwrap(),
                             });
                             out
@@ -152,6 +267,8 @@ struct CacheKey {
     glyph_id: GlyphId,
     style: FontStyle,
     font_index: usize,
+    /// The box the glyph was scaled to fit, if any
+    fit: Option<Vec2<u32>>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -0,0 +1,236 @@
+use neophyte_linalg::Vec2;
+use swash::text::Codepoint;
+
+const ZWJ: char = '\u{200D}';
+const TEXT_PRESENTATION: char = '\u{FE0E}';
+const EMOJI_PRESENTATION: char = '\u{FE0F}';
+const KEYCAP: char = '\u{20E3}';
+
+/// Whether the contents of a cell should be drawn as a color emoji. Neovim
+/// puts a whole grapheme cluster in one cell, so this considers ZWJ sequences,
+/// skin tone modifiers, flag pairs, and keycaps together. Pictographs without
+/// a variation selector only count if Neovim made the cell double-width, which
+/// it does for characters with emoji presentation by default.
+pub fn is_emoji(mut chars: impl Iterator<Item = char>, is_wide: bool) -> bool {
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    if is_regional_indicator(first) {
+        return true;
+    }
+
+    let mut is_sequence = false;
+    for c in chars {
+        match c {
+            TEXT_PRESENTATION => return false,
+            EMOJI_PRESENTATION | ZWJ | KEYCAP => is_sequence = true,
+            c if is_skin_tone(c) => is_sequence = true,
+            _ => {}
+        }
+    }
+    is_sequence || (is_wide && first.is_extended_pictographic())
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+fn is_skin_tone(c: char) -> bool {
+    ('\u{1F3FB}'..='\u{1F3FF}').contains(&c)
+}
+
+/// The largest size with the aspect ratio of the given size that fits in the
+/// box, touching it on at least one side
+pub fn fit_size(size: Vec2<u32>, fit: Vec2<u32>) -> Vec2<u32> {
+    if size.x == 0 || size.y == 0 {
+        return size;
+    }
+    let scale = (fit.x as f32 / size.x as f32).min(fit.y as f32 / size.y as f32);
+    Vec2::new(
+        ((size.x as f32 * scale).round() as u32).clamp(1, fit.x.max(1)),
+        ((size.y as f32 * scale).round() as u32).clamp(1, fit.y.max(1)),
+    )
+}
+
+/// Resample an image with the given number of interleaved channels to a new
+/// size. Each output pixel is the area-weighted average of the input pixels
+/// it covers, which keeps large emoji bitmap strikes from aliasing when they
+/// are shrunk to the cell size.
+pub fn resize(data: &[u8], size: Vec2<u32>, new_size: Vec2<u32>, channels: usize) -> Vec<u8> {
+    if size == new_size {
+        return data.to_vec();
+    }
+    let rows = resize_axis(data, size, new_size.x, channels, false);
+    resize_axis(
+        &rows,
+        Vec2::new(new_size.x, size.y),
+        new_size.y,
+        channels,
+        true,
+    )
+}
+
+/// Resample the image along one axis
+fn resize_axis(
+    data: &[u8],
+    size: Vec2<u32>,
+    new_len: u32,
+    channels: usize,
+    vertical: bool,
+) -> Vec<u8> {
+    let (len, lines) = if vertical {
+        (size.y, size.x)
+    } else {
+        (size.x, size.y)
+    };
+    let out_size = if vertical {
+        Vec2::new(size.x, new_len)
+    } else {
+        Vec2::new(new_len, size.y)
+    };
+    let index = |size: Vec2<u32>, line: u32, i: u32| {
+        let (x, y) = if vertical { (line, i) } else { (i, line) };
+        (y * size.x + x) as usize * channels
+    };
+
+    let scale = len as f32 / new_len as f32;
+    let mut out = vec![0; out_size.area() as usize * channels];
+    let mut sums = vec![0f32; channels];
+    for line in 0..lines {
+        for i in 0..new_len {
+            let start = i as f32 * scale;
+            let end = start + scale;
+            sums.iter_mut().for_each(|sum| *sum = 0.);
+            let mut total = 0.;
+            let mut j = start.floor() as u32;
+            while (j as f32) < end && j < len {
+                let weight = (end.min(j as f32 + 1.) - start.max(j as f32)).max(0.);
+                let src = index(size, line, j);
+                for (sum, &value) in sums.iter_mut().zip(&data[src..src + channels]) {
+                    *sum += value as f32 * weight;
+                }
+                total += weight;
+                j += 1;
+            }
+            let dst = index(out_size, line, i);
+            for (out, sum) in out[dst..dst + channels].iter_mut().zip(&sums) {
+                *out = (sum / total).round() as u8;
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swash::text::{
+        Script,
+        cluster::{CharCluster, Parser, Token},
+    };
+
+    const FIXTURE: &str = include_str!("emoji_fixture.txt");
+
+    fn fixture() -> impl Iterator<Item = (&'static str, &'static str)> {
+        FIXTURE
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.is_empty())
+            .map(|line| line.split_once('\t').unwrap())
+    }
+
+    #[test]
+    fn fixture_sequences_are_emoji() {
+        for (description, sequence) in fixture() {
+            assert!(
+                is_emoji(sequence.chars(), true),
+                "{description}: {sequence:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn text_is_not_emoji() {
+        assert!(!is_emoji("a".chars(), false));
+        assert!(!is_emoji("1".chars(), false));
+        assert!(!is_emoji("漢".chars(), true));
+        assert!(!is_emoji("©".chars(), false));
+        assert!(!is_emoji("❤\u{FE0E}".chars(), false));
+        assert!(!is_emoji("".chars(), true));
+    }
+
+    /// Each fixture sequence occupies one cell followed by a continuation
+    /// cell, so the parser must keep its characters together for it to be
+    /// shaped as one glyph. The exception is flags, which the parser splits
+    /// into a cluster per regional indicator. Both land in the same cell and
+    /// the font joins them while shaping.
+    #[test]
+    fn fixture_sequences_are_one_cluster() {
+        for (description, sequence) in fixture() {
+            let tokens = sequence.char_indices().map(|(offset, c)| Token {
+                ch: c,
+                offset: offset as u32,
+                len: c.len_utf8() as u8,
+                info: c.into(),
+                data: 0,
+            });
+            let mut parser = Parser::new(Script::Latin, tokens);
+            let mut cluster = CharCluster::new();
+            let mut clusters = vec![];
+            let mut clustered = String::new();
+            while parser.next(&mut cluster) {
+                clusters.push(cluster.range().to_range());
+                clustered.extend(cluster.chars().iter().map(|c| c.ch));
+            }
+
+            let expected = if sequence.chars().all(is_regional_indicator) {
+                2
+            } else {
+                1
+            };
+            assert_eq!(clusters.len(), expected, "{description}: {clusters:?}");
+            assert_eq!(clusters[0].start, 0, "{description}");
+            assert_eq!(clusters[expected - 1].end, sequence.len(), "{description}");
+            // A presentation selector after an emoji is recorded on the
+            // cluster rather than kept as a character
+            let without_selectors: String = sequence
+                .chars()
+                .filter(|c| !matches!(c, '\u{FE0E}' | '\u{FE0F}'))
+                .collect();
+            assert!(
+                clustered == sequence || clustered == without_selectors,
+                "{description}: {clustered:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn fit_size_touches_the_box() {
+        let fit = Vec2::new(20, 18);
+        assert_eq!(fit_size(Vec2::new(136, 128), fit), Vec2::new(19, 18));
+        assert_eq!(fit_size(Vec2::new(160, 80), fit), Vec2::new(20, 10));
+        assert_eq!(fit_size(Vec2::new(9, 9), fit), Vec2::new(18, 18));
+        assert_eq!(fit_size(Vec2::new(0, 9), fit), Vec2::new(0, 9));
+    }
+
+    #[test]
+    fn resize_keeps_solid_colors() {
+        let color = [200, 100, 50, 255];
+        let size = Vec2::new(136, 128);
+        let data: Vec<u8> = color.repeat(size.area() as usize);
+        for new_size in [Vec2::new(19, 18), Vec2::new(300, 7), Vec2::new(1, 1)] {
+            let resized = resize(&data, size, new_size, 4);
+            assert_eq!(resized.len(), new_size.area() as usize * 4);
+            assert!(resized.chunks(4).all(|pixel| pixel == color));
+        }
+    }
+
+    #[test]
+    fn resize_averages_covered_pixels() {
+        let data = [0, 255, 255, 0];
+        assert_eq!(
+            resize(&data, Vec2::new(4, 1), Vec2::new(2, 1), 1),
+            [128, 128]
+        );
+        assert_eq!(resize(&data, Vec2::new(2, 2), Vec2::new(1, 1), 1), [128]);
+    }
+}
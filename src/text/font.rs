@@ -73,12 +73,12 @@ impl Font {
     }
 
     /// Cached Swash charmap
-    pub fn charmap(&self) -> Charmap {
+    pub fn charmap(&self) -> Charmap<'_> {
         self.charmap.materialize(&self.as_ref())
     }
 
     /// Gets the underlying Swash font
-    pub fn as_ref(&self) -> FontRef {
+    pub fn as_ref(&self) -> FontRef<'_> {
         // Unlike the FontRef constructors, this does not construct a new key,
         // enabling performance optimizations and caching mechanisms
         FontRef {
@@ -1,14 +1,36 @@
-use super::font::{Font, FontFromFileError, Metrics};
-use crate::{assets, ui::options::FontSize};
+use super::{
+    font::{Font, FontFromFileError, Metrics},
+    highlight_lookup::HighlightLookup,
+};
+use crate::{
+    assets,
+    ui::HlId,
+    ui::options::{Edging, FontFace, FontSize, Hinting},
+};
 use font_kit::{error::SelectionError, handle::Handle, source::SystemSource};
 use neophyte_linalg::Vec2;
-use neophyte_ui_event::{MaybeInto, Parse};
+use neophyte_ui_event::{MaybeInto, Parse, Values};
+use std::collections::HashMap;
 use swash::{Setting, Style, Weight};
 
 /// Loaded fonts
 #[derive(Debug, Clone)]
 pub struct Fonts {
     fonts: Vec<FontFamily>,
+    /// Fonts from guifontwide, used for double-width cells
+    wide: Vec<FontFamily>,
+    size: FontSize,
+    /// Extra space added to grid cells around the glyphs
+    pub spacing: CellSpacing,
+    /// Codepoints for symbols that are scaled to fit the cell
+    pub symbol_ranges: Vec<SymbolRange>,
+    /// Whether Neovim performs Arabic joining itself, from the arabicshape
+    /// option. If not, joining is left to the shaper.
+    pub arabicshape: bool,
+    /// Fonts for text in particular highlight groups
+    highlight_fonts: Vec<HighlightFont>,
+    /// Matches highlight IDs to indices into highlight_fonts
+    highlight_lookup: HighlightLookup,
 }
 
 impl Default for Fonts {
@@ -52,45 +74,140 @@ impl Fonts {
                     bold_italic: None,
                 },
             ],
+            wide: vec![],
+            size: FontSize::default(),
+            spacing: CellSpacing::default(),
+            symbol_ranges: SymbolRange::DEFAULT.to_vec(),
+            arabicshape: true,
+            highlight_fonts: vec![],
+            highlight_lookup: HighlightLookup::default(),
         }
     }
 
     pub fn set_font_size(&mut self, size: FontSize) {
+        self.size = size;
         for font in self.fonts.iter_mut() {
             font.resize(size);
         }
+        self.resize_wide();
+        self.resize_highlight_fonts();
     }
 
     pub fn set_fonts(&mut self, fonts: Vec<FontSetting>, size: FontSize) {
-        let mut old = std::mem::take(&mut self.fonts);
-        self.fonts = fonts
+        self.size = size;
+        self.fonts = load_families(std::mem::take(&mut self.fonts), fonts, size);
+        if self.fonts.is_empty() {
+            self.fonts = Self::new().fonts;
+        }
+        self.resize_wide();
+        self.resize_highlight_fonts();
+    }
+
+    /// Set the fonts used for double-width cells. These use the same size as
+    /// the regular fonts but are scaled down if needed to fit in two cells.
+    pub fn set_wide_fonts(&mut self, fonts: Vec<FontSetting>) {
+        self.wide = load_families(std::mem::take(&mut self.wide), fonts, self.size);
+        self.resize_wide();
+    }
+
+    fn resize_wide(&mut self) {
+        let width = self.cell_size().x as f32 * 2.;
+        for family in self.wide.iter_mut() {
+            family.resize(self.size);
+            if family
+                .metrics()
+                .is_some_and(|metrics| metrics.width > width)
+            {
+                family.resize(FontSize::Width(width));
+            }
+        }
+    }
+
+    /// Set the fonts used for text in the given highlight groups in place of
+    /// the regular fonts
+    pub fn set_highlight_fonts(
+        &mut self,
+        fonts: Vec<(String, FontSetting)>,
+        highlight_names: &HashMap<HlId, Vec<String>>,
+    ) {
+        let mut old: Vec<_> = std::mem::take(&mut self.highlight_fonts)
             .into_iter()
-            .filter_map(move |font| {
-                if let Some(i) = old.iter().position(|old| old.setting == font) {
-                    let mut existing = old.swap_remove(i);
-                    existing.resize(size);
-                    Some(existing)
-                } else {
-                    let name = font.name.clone();
-                    match FontFamily::with_settings(font, size) {
-                        Ok(family) => Some(family),
-                        Err(e) => {
-                            log::warn!("Failed to load family {}: {e}", name);
-                            None
-                        }
-                    }
-                }
+            .map(|font| font.family)
+            .collect();
+        self.highlight_fonts = fonts
+            .into_iter()
+            .filter_map(|(group, setting)| {
+                load_family(&mut old, setting, self.size)
+                    .map(|family| HighlightFont { group, family })
             })
             .collect();
-        if self.fonts.is_empty() {
-            self.fonts = Self::new().fonts;
+        self.update_highlights(highlight_names);
+    }
+
+    /// Match highlight IDs to their font overrides. Call when the highlight
+    /// definitions change.
+    pub fn update_highlights(&mut self, highlight_names: &HashMap<HlId, Vec<String>>) {
+        self.highlight_lookup.update(
+            self.highlight_fonts.iter().map(|font| font.group.as_str()),
+            highlight_names,
+        );
+    }
+
+    fn resize_highlight_fonts(&mut self) {
+        for font in self.highlight_fonts.iter_mut() {
+            font.family.resize(self.size);
         }
     }
 
+    /// Whether the character is a symbol that should be scaled to fit the cell
+    pub fn is_symbol(&self, c: char) -> bool {
+        self.symbol_ranges.iter().any(|range| range.contains(c))
+    }
+
     pub fn families(&self) -> impl Iterator<Item = &FontFamily> {
         self.fonts.iter()
     }
 
+    /// The font families for double-width cells
+    pub fn wide_families(&self) -> impl Iterator<Item = &FontFamily> {
+        self.wide.iter()
+    }
+
+    /// The index of the first wide font family when indexing with
+    /// [Fonts::family]
+    pub fn wide_start(&self) -> usize {
+        self.fonts.len()
+    }
+
+    /// Whether the index from [Fonts::family] refers to a wide font family
+    pub fn is_wide_family(&self, index: usize) -> bool {
+        (self.wide_start()..self.highlight_start()).contains(&index)
+    }
+
+    /// The index of the first highlight group font family when indexing with
+    /// [Fonts::family]
+    pub fn highlight_start(&self) -> usize {
+        self.fonts.len() + self.wide.len()
+    }
+
+    /// The font family for text with the given highlight, if one was set for
+    /// any of its highlight groups, along with its index for [Fonts::family]
+    pub fn highlight_family(&self, hl_id: HlId) -> Option<(usize, &FontFamily)> {
+        let i = self.highlight_lookup.get(hl_id)?;
+        let font = self.highlight_fonts.get(i)?;
+        Some((self.highlight_start() + i, &font.family))
+    }
+
+    /// Gets the family with the given index. Indices past the regular families
+    /// refer to the wide families, followed by the highlight group families.
+    pub fn family(&self, index: usize) -> Option<&FontFamily> {
+        self.fonts
+            .iter()
+            .chain(self.wide.iter())
+            .chain(self.highlight_fonts.iter().map(|font| &font.family))
+            .nth(index)
+    }
+
     pub fn fonts(&self) -> impl Iterator<Item = (&Font, FontStyle)> {
         self.families().flat_map(|font| font.iter())
     }
@@ -102,12 +219,122 @@ impl Fonts {
             .unwrap()
     }
 
-    /// Get the cell size of the first loaded font
+    /// Get the cell size of the first loaded font, including cell spacing
     pub fn cell_size(&self) -> Vec2<u32> {
-        self.metrics().into_pixels().cell_size()
+        self.spacing.apply(self.metrics().into_pixels().cell_size())
+    }
+
+    /// The offset of glyphs from the corner of their cell that keeps them
+    /// centered within the cell spacing
+    pub fn glyph_offset(&self) -> Vec2<u32> {
+        (self.cell_size() - self.metrics().into_pixels().cell_size()) / 2
+    }
+}
+
+/// An inclusive range of codepoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SymbolRange {
+    pub first: char,
+    pub last: char,
+}
+
+impl SymbolRange {
+    /// The Unicode private use areas, where Nerd Fonts and other icon fonts
+    /// put their symbols
+    pub const DEFAULT: [Self; 3] = [
+        Self::new('\u{E000}', '\u{F8FF}'),
+        Self::new('\u{F0000}', '\u{FFFFD}'),
+        Self::new('\u{100000}', '\u{10FFFD}'),
+    ];
+
+    pub const fn new(first: char, last: char) -> Self {
+        Self { first, last }
+    }
+
+    pub fn contains(self, c: char) -> bool {
+        (self.first..=self.last).contains(&c)
+    }
+}
+
+impl Parse for SymbolRange {
+    fn parse(value: rmpv::Value) -> Option<Self> {
+        let mut iter = Values::new(value)?;
+        let first = char::from_u32(iter.next()?)?;
+        let last = char::from_u32(iter.next()?)?;
+        Some(Self::new(first, last))
+    }
+}
+
+/// Adjustments to the grid cell size from the font metrics
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellSpacing {
+    /// Extra pixels between rows, from the linespace option
+    pub linespace: u32,
+    /// Multiplier of the cell width
+    pub letter_spacing: f32,
+    /// Multiplier of the cell height
+    pub line_height: f32,
+}
+
+impl Default for CellSpacing {
+    fn default() -> Self {
+        Self {
+            linespace: 0,
+            letter_spacing: 1.,
+            line_height: 1.,
+        }
     }
 }
 
+impl CellSpacing {
+    /// Apply the spacing to a cell size from the font metrics. The result is
+    /// never smaller than the original cell.
+    pub fn apply(self, cell_size: Vec2<u32>) -> Vec2<u32> {
+        let scaled = cell_size.cast_as::<f32>() * Vec2::new(self.letter_spacing, self.line_height);
+        let scaled = Vec2::combine(scaled.map(f32::round).cast_as(), cell_size, u32::max);
+        scaled + Vec2::new(0, self.linespace)
+    }
+}
+
+/// Load the given font families, reusing previously loaded families where
+/// possible
+fn load_families(
+    mut old: Vec<FontFamily>,
+    fonts: Vec<FontSetting>,
+    size: FontSize,
+) -> Vec<FontFamily> {
+    fonts
+        .into_iter()
+        .filter_map(move |font| load_family(&mut old, font, size))
+        .collect()
+}
+
+/// Load the given font family, taking it from the previously loaded families
+/// if present
+fn load_family(old: &mut Vec<FontFamily>, font: FontSetting, size: FontSize) -> Option<FontFamily> {
+    if let Some(i) = old.iter().position(|old| old.setting == font) {
+        let mut existing = old.swap_remove(i);
+        existing.resize(size);
+        Some(existing)
+    } else {
+        let name = font.name.clone();
+        match FontFamily::with_settings(font, size) {
+            Ok(family) => Some(family),
+            Err(e) => {
+                log::warn!("Failed to load family {}: {e}", name);
+                None
+            }
+        }
+    }
+}
+
+/// A font family used for text in a highlight group
+#[derive(Clone, Debug)]
+struct HighlightFont {
+    group: String,
+    family: FontFamily,
+}
+
 #[derive(Clone, Debug)]
 pub struct FontFamily {
     /// The font name
@@ -166,6 +393,16 @@ impl FontFamily {
             }
         }
 
+        if out.setting.bold {
+            out.regular = out.bold.clone().or(out.regular);
+            out.italic = out.bold_italic.clone().or(out.italic);
+        }
+
+        if out.setting.italic {
+            out.regular = out.italic.clone().or(out.regular);
+            out.bold = out.bold_italic.clone().or(out.bold);
+        }
+
         if out.iter().count() == 0 {
             Err(FontFamilyError::Empty)
         } else {
@@ -250,6 +487,12 @@ pub struct FontSetting {
     pub name: String,
     pub features: Vec<SwashSetting<u16>>,
     pub variations: Vec<SwashSetting<f32>>,
+    /// Use the bold variants in place of the regular ones
+    pub bold: bool,
+    /// Use the italic variants in place of the regular ones
+    pub italic: bool,
+    pub edging: Edging,
+    pub hinting: Hinting,
 }
 
 impl FontSetting {
@@ -258,6 +501,34 @@ impl FontSetting {
             name,
             features: vec![],
             variations: vec![],
+            bold: false,
+            italic: false,
+            edging: Edging::default(),
+            hinting: Hinting::default(),
+        }
+    }
+}
+
+impl From<FontFace> for FontSetting {
+    fn from(face: FontFace) -> Self {
+        let FontFace {
+            name,
+            bold,
+            italic,
+            edging,
+            hinting,
+            features,
+        } = face;
+        Self {
+            features: features
+                .iter()
+                .map(|(name, value)| SwashSetting((name.as_str(), *value).into()))
+                .collect(),
+            bold,
+            italic,
+            edging,
+            hinting,
+            ..Self::with_name(name)
         }
     }
 }
@@ -270,18 +541,24 @@ impl Parse for FontSetting {
                 let mut name = None;
                 let mut features = vec![];
                 let mut variations = vec![];
+                let mut bold = false;
+                let mut italic = false;
                 for (k, v) in map {
                     match k.as_str()? {
                         "name" => name = Some(v.maybe_into()?),
                         "features" => features = v.maybe_into()?,
                         "variations" => variations = v.maybe_into()?,
+                        "bold" => bold = v.maybe_into()?,
+                        "italic" => italic = v.maybe_into()?,
                         _ => {}
                     }
                 }
                 Some(Self {
-                    name: name?,
                     features,
                     variations,
+                    bold,
+                    italic,
+                    ..Self::with_name(name?)
                 })
             }
             _ => None,
@@ -289,6 +566,19 @@ impl Parse for FontSetting {
     }
 }
 
+/// Parse a map of highlight group names to font settings
+pub fn parse_highlight_fonts(value: rmpv::Value) -> Option<Vec<(String, FontSetting)>> {
+    match value {
+        rmpv::Value::Map(map) => map
+            .into_iter()
+            .map(|(k, v)| Some((k.maybe_into()?, v.maybe_into()?)))
+            .collect(),
+        // An empty Lua table is sent as an array
+        rmpv::Value::Array(array) if array.is_empty() => Some(vec![]),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct SwashSetting<T: Copy>(pub Setting<T>);
 
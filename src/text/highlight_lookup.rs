@@ -0,0 +1,46 @@
+use crate::ui::HlId;
+use std::collections::HashMap;
+
+/// Matches highlight IDs to settings configured for highlight groups by name
+#[derive(Debug, Clone, Default)]
+pub struct HighlightLookup {
+    /// For each highlight ID, the index of its highest-priority highlight
+    /// group with a setting
+    by_highlight: Vec<Option<usize>>,
+}
+
+impl HighlightLookup {
+    /// Match highlight IDs to the given groups. Call when the groups or the
+    /// highlight definitions change.
+    pub fn update<'a>(
+        &mut self,
+        groups: impl Iterator<Item = &'a str> + Clone,
+        highlight_names: &HashMap<HlId, Vec<String>>,
+    ) {
+        self.by_highlight.clear();
+        if groups.clone().next().is_none() {
+            return;
+        }
+
+        for (&hl_id, names) in highlight_names {
+            // Later names take priority
+            let Some(i) = names
+                .iter()
+                .rev()
+                .find_map(|name| groups.clone().position(|group| group == name))
+            else {
+                continue;
+            };
+            let hl_id = hl_id as usize;
+            if hl_id >= self.by_highlight.len() {
+                self.by_highlight.resize(hl_id + 1, None);
+            }
+            self.by_highlight[hl_id] = Some(i);
+        }
+    }
+
+    /// The index of the group with a setting for the highlight
+    pub fn get(&self, hl_id: HlId) -> Option<usize> {
+        self.by_highlight.get(hl_id as usize).copied().flatten()
+    }
+}
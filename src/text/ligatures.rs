@@ -0,0 +1,87 @@
+use super::{fonts::SwashSetting, highlight_lookup::HighlightLookup};
+use crate::ui::HlId;
+use neophyte_ui_event::MaybeInto;
+use std::collections::HashMap;
+
+/// Controls where ligatures and other multi-character substitutions may form
+#[derive(Debug, Clone, Default)]
+pub struct Ligatures {
+    /// Break shaping runs around the cursor cell in every mode
+    pub break_at_cursor: bool,
+    /// Names of modes, such as 'insert', in which shaping runs break around
+    /// the cursor cell
+    pub break_at_cursor_modes: Vec<String>,
+    /// Font features applied on top of the font's own for cells in the given
+    /// highlight groups
+    highlight_features: Vec<HighlightFeatures>,
+    /// Matches highlight IDs to indices into highlight_features
+    lookup: HighlightLookup,
+}
+
+impl Ligatures {
+    /// Whether shaping runs should break around the cursor in the given mode
+    pub fn breaks_at_cursor(&self, mode: Option<&str>) -> bool {
+        self.break_at_cursor
+            || mode.is_some_and(|mode| self.break_at_cursor_modes.iter().any(|m| m == mode))
+    }
+
+    pub fn set_highlight_features(
+        &mut self,
+        highlight_features: Vec<HighlightFeatures>,
+        highlight_names: &HashMap<HlId, Vec<String>>,
+    ) {
+        self.highlight_features = highlight_features;
+        self.update_highlights(highlight_names);
+    }
+
+    /// Match highlight IDs to their feature overrides. Call when the highlight
+    /// definitions change.
+    pub fn update_highlights(&mut self, highlight_names: &HashMap<HlId, Vec<String>>) {
+        self.lookup.update(
+            self.highlight_features
+                .iter()
+                .map(|features| features.group.as_str()),
+            highlight_names,
+        );
+    }
+
+    /// An identifier for the feature overrides of the highlight, used to
+    /// break shaping runs where the features change
+    pub fn feature_index(&self, hl_id: HlId) -> Option<usize> {
+        self.lookup.get(hl_id)
+    }
+
+    pub fn features(&self, index: Option<usize>) -> &[SwashSetting<u16>] {
+        index
+            .and_then(|i| self.highlight_features.get(i))
+            .map(|features| features.features.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+/// Font features for a highlight group, such as disabling ligatures in strings
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighlightFeatures {
+    pub group: String,
+    pub features: Vec<SwashSetting<u16>>,
+}
+
+impl HighlightFeatures {
+    /// Parse from a map of highlight group names to font features
+    pub fn parse_map(value: rmpv::Value) -> Option<Vec<Self>> {
+        match value {
+            rmpv::Value::Map(map) => map
+                .into_iter()
+                .map(|(k, v)| {
+                    Some(Self {
+                        group: k.maybe_into()?,
+                        features: v.maybe_into()?,
+                    })
+                })
+                .collect(),
+            // An empty Lua table is sent as an array
+            rmpv::Value::Array(array) if array.is_empty() => Some(vec![]),
+            _ => None,
+        }
+    }
+}
@@ -1,4 +1,9 @@
 pub mod atlas;
+pub mod box_drawing;
 pub mod cache;
+pub mod emoji;
 pub mod font;
 pub mod fonts;
+pub mod highlight_lookup;
+pub mod ligatures;
+pub mod script;
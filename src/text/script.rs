@@ -0,0 +1,183 @@
+use swash::{
+    shape::Direction,
+    text::{Codepoint, Language, Script},
+};
+
+/// A range of cells that are shaped with the same script
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptRun {
+    pub script: Script,
+    /// The first cell in the run
+    pub start: usize,
+    /// One past the last cell in the run
+    pub end: usize,
+}
+
+/// Splits a line into runs of cells with the same script. Characters that are
+/// shared between scripts, such as spaces, digits, punctuation, and combining
+/// marks, join the run they follow, or the run after them at the start of the
+/// line.
+pub fn script_runs(line: &[char]) -> Vec<ScriptRun> {
+    let mut runs = vec![];
+    let mut current = None;
+    let mut start = 0;
+    for (i, &c) in line.iter().enumerate() {
+        let Some(script) = resolved_script(c) else {
+            continue;
+        };
+        match current {
+            Some(current_script) if current_script != script => {
+                runs.push(ScriptRun {
+                    script: current_script,
+                    start,
+                    end: i,
+                });
+                start = i;
+                current = Some(script);
+            }
+            Some(_) => {}
+            None => current = Some(script),
+        }
+    }
+
+    if start < line.len() {
+        runs.push(ScriptRun {
+            script: current.unwrap_or(Script::Latin),
+            start,
+            end: line.len(),
+        });
+    }
+    runs
+}
+
+/// Splits runs at the given cells so that no shaping happens across them
+pub fn break_runs(runs: Vec<ScriptRun>, breaks: &[usize]) -> Vec<ScriptRun> {
+    let mut out = Vec::with_capacity(runs.len() + breaks.len());
+    for mut run in runs {
+        for &at in breaks {
+            if run.start < at && at < run.end {
+                out.push(ScriptRun { end: at, ..run });
+                run.start = at;
+            }
+        }
+        out.push(run);
+    }
+    out
+}
+
+/// The script of the character, or None if it takes the script of the
+/// surrounding text
+fn resolved_script(c: char) -> Option<Script> {
+    match c.script() {
+        Script::Common | Script::Inherited | Script::Unknown => None,
+        script => Some(script),
+    }
+}
+
+/// The script to shape a run with. When Neovim handles Arabic joining, it
+/// sends presentation forms that must not be joined again, so Arabic runs are
+/// shaped without the script's joining features.
+pub fn shaping_script(script: Script, arabicshape: bool) -> Script {
+    if arabicshape && script == Script::Arabic {
+        Script::Common
+    } else {
+        script
+    }
+}
+
+/// The default language for the script, used to select language-specific
+/// glyph forms
+pub fn language(script: Script) -> Option<Language> {
+    let tag = match script {
+        Script::Arabic => "ar",
+        Script::Hebrew => "he",
+        Script::Devanagari => "hi",
+        Script::Bengali => "bn",
+        Script::Gujarati => "gu",
+        Script::Gurmukhi => "pa",
+        Script::Kannada => "kn",
+        Script::Malayalam => "ml",
+        Script::Tamil => "ta",
+        Script::Telugu => "te",
+        Script::Sinhala => "si",
+        Script::Thai => "th",
+        Script::Lao => "lo",
+        Script::Khmer => "km",
+        Script::Myanmar => "my",
+        Script::Tibetan => "bo",
+        Script::Syriac => "syr",
+        Script::Thaana => "dv",
+        _ => return None,
+    };
+    Language::parse(tag)
+}
+
+/// The writing direction of the script. Cells are always laid out in the order
+/// Neovim gives them, but right-to-left scripts need the direction for cursive
+/// attachment.
+pub fn direction(script: Script) -> Direction {
+    match script {
+        Script::Arabic
+        | Script::Hebrew
+        | Script::Syriac
+        | Script::Thaana
+        | Script::Nko
+        | Script::Samaritan
+        | Script::Mandaic
+        | Script::Adlam
+        | Script::HanifiRohingya
+        | Script::Yezidi => Direction::RightToLeft,
+        _ => Direction::LeftToRight,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    fn run(script: Script, start: usize, end: usize) -> ScriptRun {
+        ScriptRun { script, start, end }
+    }
+
+    #[test]
+    fn splits_mixed_scripts() {
+        // The CJK character is followed by its continuation cell
+        let line = chars("ab سلام 好\0!");
+        assert_eq!(
+            script_runs(&line),
+            [
+                run(Script::Latin, 0, 3),
+                run(Script::Arabic, 3, 8),
+                run(Script::Han, 8, 11),
+            ]
+        );
+    }
+
+    #[test]
+    fn leading_common_characters_join_the_next_run() {
+        assert_eq!(script_runs(&chars(" 1好")), [run(Script::Han, 0, 3)]);
+        assert_eq!(script_runs(&chars("  ")), [run(Script::Latin, 0, 2)]);
+        assert_eq!(script_runs(&[]), []);
+    }
+
+    #[test]
+    fn breaks_at_the_cursor() {
+        let runs = vec![run(Script::Latin, 0, 3), run(Script::Arabic, 3, 8)];
+        // The cursor on cell 5 breaks before and after it
+        assert_eq!(
+            break_runs(runs.clone(), &[5, 6]),
+            [
+                run(Script::Latin, 0, 3),
+                run(Script::Arabic, 3, 5),
+                run(Script::Arabic, 5, 6),
+                run(Script::Arabic, 6, 8),
+            ]
+        );
+        // Breaks at the edges of runs leave them whole
+        assert_eq!(break_runs(runs.clone(), &[0, 3, 8]), runs);
+    }
+}
@@ -22,15 +22,23 @@ impl IntoSrgb for Rgb {
     }
 }
 
-pub fn nice_s_curve(t: f32, length: f32) -> f32 {
-    let t = normalize(t, length);
-    let v = 1. - t;
-    1. - v * v
-}
-
-fn normalize(t: f32, length: f32) -> f32 {
-    let length = length.sqrt() + length.ln_1p();
-    (t / length).clamp(0., 1.)
+/// Parses a map of r, g, b, and a channels in the range 0-255
+pub fn parse_color(value: rmpv::Value) -> Option<[f32; 4]> {
+    let rmpv::Value::Map(map) = value else {
+        return None;
+    };
+    let mut rgb = Rgb::BLACK;
+    let mut a = 255u8;
+    for (k, v) in map {
+        match k.as_str()? {
+            "r" => rgb.r = v.maybe_into()?,
+            "g" => rgb.g = v.maybe_into()?,
+            "b" => rgb.b = v.maybe_into()?,
+            "a" => a = v.maybe_into()?,
+            _ => {}
+        }
+    }
+    Some(rgb.into_srgb(f32::from(a) / 255.))
 }
 
 #[allow(unused)]
@@ -47,6 +55,6 @@ macro_rules! time_execution {
     }};
 }
 
-use neophyte_ui_event::rgb::Rgb;
+use neophyte_ui_event::{MaybeInto, rgb::Rgb};
 #[allow(unused)]
 pub(crate) use time_execution;